@@ -1,28 +1,100 @@
+use anchor_client::solana_sdk::hash::Hash;
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::commitment_config::CommitmentLevel;
 use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
 use anchor_client::Cluster;
 use clap::*;
+use commons::lookup_table::DEFAULT_LOOKUP_TABLE_BIN_ARRAY_RADIUS;
+use lb_clmm::instructions::deposit::StrategyType;
+
+use crate::config::get_pair_alias;
+use crate::instructions::utils::BinRange;
+use crate::output::OutputFormat;
+use crate::seed_curve::SeedCurve;
 
 #[derive(Parser, Debug)]
 pub struct ConfigOverride {
+    /// Profile of the config file providing the defaults of the options below. Default: `default_profile` of the
+    /// config file, else "default". The config file is ~/.config/dlmm/config.toml, or $DLMM_CONFIG.
+    #[clap(global = true, long = "profile")]
+    pub profile: Option<String>,
     /// Cluster override
     ///
-    /// Values = mainnet, testnet, devnet, localnet.
-    /// Default: mainnet
-    #[clap(global = true, long = "provider.cluster", default_value_t = Cluster::Mainnet)]
-    pub cluster: Cluster,
+    /// Values = mainnet, testnet, devnet, localnet, or an RPC URL.
+    /// Default: the profile cluster, else mainnet
+    #[clap(global = true, long = "provider.cluster")]
+    pub cluster: Option<Cluster>,
     /// Wallet override
     ///
     /// Example: /path/to/wallet/keypair.json
-    /// Default: ~/.config/solana/id.json
+    /// Default: the profile wallet, else ~/.config/solana/id.json
+    /// A public key can be given instead of a keypair file with --export, when the wallet signs elsewhere.
+    #[clap(global = true, long = "provider.wallet")]
+    pub wallet: Option<String>,
+    /// Commitment of the RPC requests and transaction confirmations. Default: the profile commitment, else confirmed
+    #[clap(global = true, long = "commitment")]
+    pub commitment: Option<CommitmentLevel>,
+    /// Priority fee, in micro lamports per compute unit. Default: the profile priority fee, else 0
+    #[clap(global = true, long = "priority-fee")]
+    pub priority_fee: Option<u64>,
+    /// Simulate each transaction to set its compute unit limit, instead of the fixed limits of the commands.
+    /// The priority fee stays --priority-fee unless --priority-fee-percentile is set.
+    #[clap(global = true, long = "auto-compute-budget")]
+    pub auto_compute_budget: bool,
+    /// Margin added to the simulated compute units, in bps. Default: 1000
+    #[clap(
+        global = true,
+        long = "compute-unit-margin-bps",
+        requires = "auto_compute_budget"
+    )]
+    pub compute_unit_margin_bps: Option<u16>,
+    /// Set the priority fee to this percentile (0 - 100) of the recent prioritization fees paid to write the pair,
+    /// reserves, bin arrays and other writable accounts of each transaction. Implies --auto-compute-budget.
     #[clap(
         global = true,
-        long = "provider.wallet",
-        default_value_t = String::from(shellexpand::tilde("~/.config/solana/id.json"))
+        long = "priority-fee-percentile",
+        conflicts_with = "priority_fee",
+        value_parser = clap::value_parser!(u8).range(0..=100)
     )]
-    pub wallet: String,
-    /// Priority fee
-    #[clap(global = true, long = "priority-fee", default_value_t = 0)]
-    pub priority_fee: u64,
+    pub priority_fee_percentile: Option<u8>,
+    /// Max priority fee paid by a transaction, in lamports, with --auto-compute-budget or --priority-fee-percentile
+    #[clap(global = true, long = "max-priority-fee")]
+    pub max_priority_fee: Option<u64>,
+    /// Output format of the read commands. json output is versioned per command by `schema_version`.
+    #[clap(global = true, long = "output", value_enum, default_value_t = OutputFormat::Table)]
+    pub output: OutputFormat,
+    /// Simulate the transactions of state-changing commands instead of sending them.
+    /// Prints the logs, compute units consumed, decoded events and token balance changes.
+    #[clap(global = true, long = "simulate")]
+    pub simulate: bool,
+    /// Write the transactions of state-changing commands to this file instead of sending them, for offline or multisig signing.
+    /// Each transaction is exported with its base64 message, required signers, blockhash and a summary. Send them with `submit`.
+    #[clap(global = true, long = "export", conflicts_with = "simulate")]
+    pub export: Option<String>,
+    /// Sign the exported transactions with the wallet keypair.
+    #[clap(global = true, long = "sign-only", requires = "export")]
+    pub sign_only: bool,
+    /// Blockhash of the exported transactions, so every signer rebuilds and signs the same messages.
+    #[clap(global = true, long = "blockhash", requires = "export")]
+    pub blockhash: Option<Hash>,
+    /// Durable nonce account providing the blockhash of the exported transactions, so they don't expire before being signed.
+    #[clap(
+        global = true,
+        long = "nonce",
+        requires = "export",
+        conflicts_with = "blockhash",
+        value_parser = parse_pubkey
+    )]
+    pub nonce: Option<Pubkey>,
+    /// Authority of the durable nonce account. Default: the wallet
+    #[clap(global = true, long = "nonce-authority", requires = "nonce", value_parser = parse_pubkey)]
+    pub nonce_authority: Option<Pubkey>,
+    /// Address lookup table to compile the transactions against, as v0 transactions. Can be repeated.
+    /// Create one for a pair with `setup-pair-lookup-table`. Swaps then take more bin arrays, and more instructions fit in a transaction.
+    #[clap(global = true, long = "lookup-table", conflicts_with = "export", value_parser = parse_pubkey)]
+    pub lookup_tables: Vec<Pubkey>,
 }
 
 fn parse_bin_liquidity_removal(src: &str) -> Result<(i32, f64), Error> {
@@ -41,6 +113,38 @@ fn parse_bin_liquidity_removal(src: &str) -> Result<(i32, f64), Error> {
     Ok((bin_id, bps_to_remove))
 }
 
+fn parse_bin_weight(src: &str) -> Result<(i32, u16), Error> {
+    let mut parsed_str: Vec<&str> = src.split(',').collect();
+
+    let weight = parsed_str
+        .pop()
+        .and_then(|s| s.parse::<u16>().ok())
+        .ok_or_else(|| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    let bin_id = parsed_str
+        .pop()
+        .and_then(|s| s.parse::<i32>().ok())
+        .ok_or_else(|| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    Ok((bin_id, weight))
+}
+
+fn parse_bin_amount(src: &str) -> Result<(i32, f64), Error> {
+    let mut parsed_str: Vec<&str> = src.split(',').collect();
+
+    let amount = parsed_str
+        .pop()
+        .and_then(|s| s.parse::<f64>().ok())
+        .ok_or_else(|| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    let bin_id = parsed_str
+        .pop()
+        .and_then(|s| s.parse::<i32>().ok())
+        .ok_or_else(|| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    Ok((bin_id, amount))
+}
+
 fn parse_bin_liquidity_distribution(src: &str) -> Result<(i32, f64, f64), Error> {
     let mut parsed_str: Vec<&str> = src.split(',').collect();
 
@@ -62,6 +166,49 @@ fn parse_bin_liquidity_distribution(src: &str) -> Result<(i32, f64, f64), Error>
     Ok((delta_id, dist_x, dist_y))
 }
 
+/// Public key, or pair alias of the profile
+fn parse_pubkey(src: &str) -> Result<Pubkey, String> {
+    if let Some(lb_pair) = get_pair_alias(src) {
+        return Ok(lb_pair);
+    }
+    Pubkey::from_str(src).map_err(|_| {
+        format!(
+            "{} is neither a public key nor a pair alias of the profile",
+            src
+        )
+    })
+}
+
+fn parse_mint_price(src: &str) -> Result<(Pubkey, f64), Error> {
+    let (mint, price) = src
+        .split_once('=')
+        .ok_or_else(|| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    let mint =
+        parse_pubkey(mint).map_err(|_| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    let price = price
+        .parse::<f64>()
+        .map_err(|_| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    Ok((mint, price))
+}
+
+fn parse_signer_signature(src: &str) -> Result<(Pubkey, Signature), Error> {
+    let (pubkey, signature) = src
+        .split_once('=')
+        .ok_or_else(|| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    let pubkey = parse_pubkey(pubkey)
+        .map_err(|_| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    let signature = signature
+        .parse::<Signature>()
+        .map_err(|_| clap::error::Error::new(error::ErrorKind::InvalidValue))?;
+
+    Ok((pubkey, signature))
+}
+
 #[derive(Debug, Clone, ValueEnum)]
 pub enum SelectiveRounding {
     Up,
@@ -69,15 +216,111 @@ pub enum SelectiveRounding {
     None,
 }
 
+/// Two-sided strategies of `add_liquidity_by_strategy`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum LiquidityStrategy {
+    SpotBalanced,
+    CurveBalanced,
+    BidAskBalanced,
+    /// Only token Y is deposited to the active bin
+    SpotImbalanced,
+    /// Only token Y is deposited to the active bin
+    CurveImbalanced,
+    /// Only token Y is deposited to the active bin
+    BidAskImbalanced,
+}
+
+impl From<LiquidityStrategy> for StrategyType {
+    fn from(strategy: LiquidityStrategy) -> Self {
+        match strategy {
+            LiquidityStrategy::SpotBalanced => StrategyType::SpotBalanced,
+            LiquidityStrategy::CurveBalanced => StrategyType::CurveBalanced,
+            LiquidityStrategy::BidAskBalanced => StrategyType::BidAskBalanced,
+            LiquidityStrategy::SpotImbalanced => StrategyType::SpotImBalanced,
+            LiquidityStrategy::CurveImbalanced => StrategyType::CurveImBalanced,
+            LiquidityStrategy::BidAskImbalanced => StrategyType::BidAskImBalanced,
+        }
+    }
+}
+
+/// One-sided strategies of `add_liquidity_by_strategy_one_side`
+#[derive(Debug, Clone, Copy, ValueEnum)]
+pub enum OneSideLiquidityStrategy {
+    Spot,
+    Curve,
+    BidAsk,
+}
+
+impl From<OneSideLiquidityStrategy> for StrategyType {
+    fn from(strategy: OneSideLiquidityStrategy) -> Self {
+        match strategy {
+            OneSideLiquidityStrategy::Spot => StrategyType::SpotOneSide,
+            OneSideLiquidityStrategy::Curve => StrategyType::CurveOneSide,
+            OneSideLiquidityStrategy::BidAsk => StrategyType::BidAskOneSide,
+        }
+    }
+}
+
+/// Token of a one-sided deposit
+#[derive(Debug, Clone, Copy, PartialEq, Eq, ValueEnum)]
+pub enum TokenSide {
+    X,
+    Y,
+}
+
+/// Bins of a deposit, by UI price or by bin id. The whole position when omitted.
+#[derive(Parser, Debug, Clone, Copy)]
+pub struct DepositBinRange {
+    /// Lowest price of the bins to deposit to, in token Y per token X.
+    #[clap(long, requires = "max_price", conflicts_with_all = ["min_bin_id", "max_bin_id"])]
+    pub min_price: Option<f64>,
+    /// Highest price of the bins to deposit to, in token Y per token X.
+    #[clap(long, requires = "min_price")]
+    pub max_price: Option<f64>,
+    /// Lowest bin to deposit to.
+    #[clap(long, allow_negative_numbers = true, requires = "max_bin_id")]
+    pub min_bin_id: Option<i32>,
+    /// Highest bin to deposit to.
+    #[clap(long, allow_negative_numbers = true, requires = "min_bin_id")]
+    pub max_bin_id: Option<i32>,
+}
+
+impl From<DepositBinRange> for Option<BinRange> {
+    fn from(range: DepositBinRange) -> Self {
+        match range {
+            DepositBinRange {
+                min_price: Some(min_price),
+                max_price: Some(max_price),
+                ..
+            } => Some(BinRange::Price {
+                min_price,
+                max_price,
+            }),
+            DepositBinRange {
+                min_bin_id: Some(min_bin_id),
+                max_bin_id: Some(max_bin_id),
+                ..
+            } => Some(BinRange::BinId {
+                min_bin_id,
+                max_bin_id,
+            }),
+            _ => None,
+        }
+    }
+}
+
 #[derive(Parser, Debug)]
 pub enum Command {
     /// Create a new liquidity pair.
     InitializePair {
         /// Preset parameter pubkey. Get the pubkey from list_all_binstep command.
+        #[clap(value_parser = parse_pubkey)]
         preset_parameter: Pubkey,
         /// Token X mint of the liquidity pair. Eg: BTC. This should be the base token.
+        #[clap(value_parser = parse_pubkey)]
         token_mint_x: Pubkey,
         /// Token Y mint of the liquidity pair. Eg: USDC. This should be the quote token.
+        #[clap(value_parser = parse_pubkey)]
         token_mint_y: Pubkey,
         /// The initial price of the liquidity pair. Eg: 24123.12312412 USDC per 1 BTC.
         initial_price: f64,
@@ -88,11 +331,13 @@ pub enum Command {
         #[clap(long, allow_negative_numbers = true)]
         bin_array_index: i64,
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
     },
     /// Initialize bin array for the given liquidity pair based on price range. For example: Initialize bin arrays for BTC/USDC from 20000 -> 30000 price.
     InitializeBinArrayWithPriceRange {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Lower bound of the price.
         lower_price: f64,
@@ -102,6 +347,7 @@ pub enum Command {
     /// Initialize bin array for the given liquidity pair based on bin range. For example: Initialize bin arrays for BTC/USDC from bin 5660 -> 6600.
     InitializeBinArrayWithBinRange {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Lower bound of the bin range.
         #[clap(long, allow_negative_numbers = true)]
@@ -113,17 +359,20 @@ pub enum Command {
     /// Initialize position for the given liquidity pair based on price range.
     InitializePositionWithPriceRange {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Lower bound of the price.
         lower_price: f64,
         /// Width of the position. Start with 1 until 70.
         width: i32,
         /// NFT mint for alpha access
+        #[clap(value_parser = parse_pubkey)]
         nft_mint: Option<Pubkey>,
     },
     /// Initialize position for the given liquidity pair based on bin range.
     InitializePosition {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Lower bound of the bin range.
         #[clap(long, allow_negative_numbers = true)]
@@ -131,13 +380,16 @@ pub enum Command {
         /// Width of the position. Start with 1 until 70.
         width: i32,
         /// NFT mint for alpha access
+        #[clap(value_parser = parse_pubkey)]
         nft_mint: Option<Pubkey>,
     },
     /// Deposit liquidity to the position of the given liquidity pair.
     AddLiquidity {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Position for the deposit.
+        #[clap(value_parser = parse_pubkey)]
         position: Pubkey,
         /// Amount of token X to be deposited.
         amount_x: u64,
@@ -151,9 +403,96 @@ pub enum Command {
         #[clap(long, value_parser = parse_bin_liquidity_distribution, value_delimiter = ' ', allow_hyphen_values = true)]
         bin_liquidity_distribution: Vec<(i32, f64, f64)>,
     },
+    /// Deposit to the position with a strategy spreading both tokens over a range of bins.
+    AddLiquidityByStrategy {
+        /// Position for the deposit.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+        /// UI amount of token X to be deposited. Eg: 1.5
+        amount_x: f64,
+        /// UI amount of token Y to be deposited.
+        amount_y: f64,
+        #[clap(flatten)]
+        bin_range: DepositBinRange,
+        /// Liquidity strategy of the deposit.
+        #[clap(long, value_enum, default_value_t = LiquidityStrategy::SpotBalanced)]
+        strategy: LiquidityStrategy,
+        /// Max number of bins the active bin can move by before the deposit fails.
+        #[clap(long, default_value_t = 3)]
+        max_active_bin_slippage: i32,
+    },
+    /// Deposit a single token to the position with a strategy spreading it over a range of bins. Token X only goes to
+    /// the bins from the active bin, token Y to the bins up to it.
+    AddLiquidityByStrategyOneSide {
+        /// Position for the deposit.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+        /// Token to be deposited.
+        #[clap(value_enum)]
+        token: TokenSide,
+        /// UI amount of the token to be deposited.
+        amount: f64,
+        #[clap(flatten)]
+        bin_range: DepositBinRange,
+        /// Liquidity strategy of the deposit.
+        #[clap(long, value_enum, default_value_t = OneSideLiquidityStrategy::Spot)]
+        strategy: OneSideLiquidityStrategy,
+        /// Max number of bins the active bin can move by before the deposit fails.
+        #[clap(long, default_value_t = 3)]
+        max_active_bin_slippage: i32,
+    },
+    /// Deposit to the position, splitting both tokens among the bins by weight.
+    AddLiquidityByWeight {
+        /// Position for the deposit.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+        /// UI amount of token X to be deposited.
+        amount_x: f64,
+        /// UI amount of token Y to be deposited.
+        amount_y: f64,
+        /// Weights of the bins. "<BIN_ID,WEIGHT BIN_ID,WEIGHT ...>" where WEIGHT is a non-zero u16.
+        /// For example: --bin-weights "-1,1 0,2 1,1"
+        #[clap(long, value_parser = parse_bin_weight, value_delimiter = ' ', allow_hyphen_values = true, required = true)]
+        bin_weights: Vec<(i32, u16)>,
+        /// Max number of bins the active bin can move by before the deposit fails.
+        #[clap(long, default_value_t = 3)]
+        max_active_bin_slippage: i32,
+    },
+    /// Deposit a single token to the position, splitting it among the bins by weight. Token X only goes to the bins
+    /// from the active bin, token Y to the bins up to it.
+    AddLiquidityOneSide {
+        /// Position for the deposit.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+        /// Token to be deposited.
+        #[clap(value_enum)]
+        token: TokenSide,
+        /// UI amount of the token to be deposited.
+        amount: f64,
+        /// Weights of the bins. "<BIN_ID,WEIGHT BIN_ID,WEIGHT ...>" where WEIGHT is a non-zero u16.
+        #[clap(long, value_parser = parse_bin_weight, value_delimiter = ' ', allow_hyphen_values = true, required = true)]
+        bin_weights: Vec<(i32, u16)>,
+        /// Max number of bins the active bin can move by before the deposit fails.
+        #[clap(long, default_value_t = 3)]
+        max_active_bin_slippage: i32,
+    },
+    /// Deposit an exact amount of a single token to each bin of the position.
+    AddLiquidityOneSidePrecise {
+        /// Position for the deposit.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+        /// Token to be deposited.
+        #[clap(value_enum)]
+        token: TokenSide,
+        /// UI amounts of the bins. "<BIN_ID,AMOUNT BIN_ID,AMOUNT ...>".
+        /// For example: --bin-amounts "10,0.5 11,0.75"
+        #[clap(long, value_parser = parse_bin_amount, value_delimiter = ' ', allow_hyphen_values = true, required = true)]
+        bin_amounts: Vec<(i32, f64)>,
+    },
     /// Remove liquidity from the position of the given liquidity pair.
     RemoveLiquidity {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Bin liquidity information to be remove. "<BIN_ID,BPS_TO_REMOVE, BIN_ID,BPS_TO_REMOVE, ...>" where
         /// BIN_ID = bin id to withdraw
@@ -161,29 +500,51 @@ pub enum Command {
         #[clap(long, value_parser = parse_bin_liquidity_removal, value_delimiter = ' ', allow_hyphen_values = true)]
         bin_liquidity_removal: Vec<(i32, f64)>,
         /// Position to be withdraw.
+        #[clap(value_parser = parse_pubkey)]
         position: Pubkey,
     },
     /// Trade token X -> Y, or vice versa.
     SwapExactIn {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Amount of token to be sell.
         amount_in: u64,
         /// Buy direction. true = buy token Y, false = buy token X.
         #[clap(long)]
         swap_for_y: bool,
+        /// Slippage tolerance on the quoted amount out, in bps. Default: the profile slippage, else 100
+        #[clap(long)]
+        slippage_bps: Option<u16>,
+        /// Minimum amount out. Overrides --slippage-bps.
+        #[clap(long)]
+        min_amount_out: Option<u64>,
+        /// Token account of the host receiving a share of the swap fee. Must hold the input token.
+        #[clap(long, value_parser = parse_pubkey)]
+        host_fee_account: Option<Pubkey>,
     },
     SwapExactOut {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Amount of token to be buy.
         amount_out: u64,
         /// Buy direction. true = buy token Y, false = buy token X.
         #[clap(long)]
         swap_for_y: bool,
+        /// Slippage tolerance on the quoted amount in, in bps. Default: the profile slippage, else 100
+        #[clap(long)]
+        slippage_bps: Option<u16>,
+        /// Maximum amount in, fee included. Overrides --slippage-bps.
+        #[clap(long)]
+        max_amount_in: Option<u64>,
+        /// Token account of the host receiving a share of the swap fee. Must hold the input token.
+        #[clap(long, value_parser = parse_pubkey)]
+        host_fee_account: Option<Pubkey>,
     },
     SwapWithPriceImpact {
         /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Amount of token to be sell.
         amount_in: u64,
@@ -192,44 +553,178 @@ pub enum Command {
         swap_for_y: bool,
         /// Allowed price impact in bps.
         price_impact_bps: u16,
+        /// Token account of the host receiving a share of the swap fee. Must hold the input token.
+        #[clap(long, value_parser = parse_pubkey)]
+        host_fee_account: Option<Pubkey>,
+    },
+    /// Quote a swap of an exact amount in. No wallet needed.
+    QuoteExactIn {
+        /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
+        lb_pair: Pubkey,
+        /// Amount of token to be sell.
+        amount_in: u64,
+        /// Buy direction. true = buy token Y, false = buy token X.
+        #[clap(long)]
+        swap_for_y: bool,
+        /// Account file written by `solana account <ADDRESS> --output json`, used instead of the cluster state of that account. Can be repeated.
+        #[clap(long = "account-file")]
+        account_files: Vec<String>,
+    },
+    /// Quote a swap of an exact amount out. No wallet needed.
+    QuoteExactOut {
+        /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
+        lb_pair: Pubkey,
+        /// Amount of token to be buy.
+        amount_out: u64,
+        /// Buy direction. true = buy token Y, false = buy token X.
+        #[clap(long)]
+        swap_for_y: bool,
+        /// Account file written by `solana account <ADDRESS> --output json`, used instead of the cluster state of that account. Can be repeated.
+        #[clap(long = "account-file")]
+        account_files: Vec<String>,
+    },
+    /// Quote a swap with price impact, and check it against the allowed price impact. No wallet needed.
+    QuotePriceImpact {
+        /// Address of the liquidity pair.
+        #[clap(value_parser = parse_pubkey)]
+        lb_pair: Pubkey,
+        /// Amount of token to be sell.
+        amount_in: u64,
+        /// Buy direction. true = buy token Y, false = buy token X.
+        #[clap(long)]
+        swap_for_y: bool,
+        /// Allowed price impact in bps.
+        price_impact_bps: u16,
+        /// Account file written by `solana account <ADDRESS> --output json`, used instead of the cluster state of that account. Can be repeated.
+        #[clap(long = "account-file")]
+        account_files: Vec<String>,
     },
     /// Show information of the given liquidity pair.
     ShowPair {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
+        /// UI price of each reward token in token Y, ordered by reward index. Used to compute the reward APR. Not required for rewards in token X or Y.
+        #[clap(long, value_delimiter = ',')]
+        reward_prices: Vec<f64>,
     },
     /// Show information of the given position.
     ShowPosition {
+        #[clap(value_parser = parse_pubkey)]
         position: Pubkey,
+        /// UI price of each reward token in token Y, ordered by reward index. Used to compute the reward APR. Not required for rewards in token X or Y.
+        #[clap(long, value_delimiter = ',')]
+        reward_prices: Vec<f64>,
     },
 
     ClaimReward {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         reward_index: u64,
+        #[clap(value_parser = parse_pubkey)]
         position: Pubkey,
     },
     UpdateRewardDuration {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         reward_index: u64,
         reward_duration: u64,
     },
     UpdateRewardFunder {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         reward_index: u64,
+        #[clap(value_parser = parse_pubkey)]
         funder: Pubkey,
     },
     /// Close liquidity position.
     ClosePosition {
         /// Address of the position.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+    },
+    /// Withdraw all the liquidity of the position.
+    RemoveAllLiquidity {
+        /// Address of the position.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+    },
+    /// Migrate a legacy position of the wallet to a new position account. The legacy account is closed.
+    MigratePosition {
+        /// Address of the legacy position.
+        #[clap(value_parser = parse_pubkey, required_unless_present = "all")]
+        position: Option<Pubkey>,
+        /// Migrate every legacy position of the wallet, in batches.
+        #[clap(long, conflicts_with = "position")]
+        all: bool,
+    },
+    /// Upgrade bin arrays of a pair to the current layout version. Anyone can migrate them.
+    MigrateBinArray {
+        /// Address of the pair.
+        #[clap(value_parser = parse_pubkey)]
+        lb_pair: Pubkey,
+        /// Bin array to migrate. Repeatable.
+        #[clap(long = "bin-array", value_parser = parse_pubkey, required_unless_present = "all")]
+        bin_arrays: Vec<Pubkey>,
+        /// Migrate every bin array of the pair on the legacy layout, in batches.
+        #[clap(long, conflicts_with = "bin_arrays")]
+        all: bool,
+    },
+    /// Set the operator of a position owned by the wallet. The operator can deposit, withdraw and claim for the owner.
+    UpdatePositionOperator {
+        /// Address of the position.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+        /// New operator of the position.
+        #[clap(value_parser = parse_pubkey)]
+        operator: Pubkey,
+    },
+    /// Checkpoint the pending fees and rewards of the position, without claiming them.
+    UpdateFeesAndRewards {
+        /// Address of the position.
+        #[clap(value_parser = parse_pubkey)]
         position: Pubkey,
     },
     /// Claim fee
     ClaimFee {
         /// Address of the position.
+        #[clap(value_parser = parse_pubkey)]
         position: Pubkey,
     },
+    /// Claim the pending fees and rewards of every position of the wallet, in as few transactions as possible
+    ClaimAll {
+        /// Claim the operator-managed positions paying their fees to this fee owner, instead of the positions owned by the wallet.
+        #[clap(long, value_parser = parse_pubkey)]
+        fee_owner: Option<Pubkey>,
+    },
+    /// Move an out of range position back around the active bin. Removes the liquidity, claims the fees and rewards and closes the position,
+    /// optionally swaps to the target ratio through the pair, then opens a new position and deposits with the strategy.
+    /// Progress is kept in <POSITION>_rebalance-position_journal.json, so a failed run resumes from the failed step.
+    RebalancePosition {
+        /// Address of the position.
+        #[clap(value_parser = parse_pubkey)]
+        position: Pubkey,
+        /// Liquidity strategy of the new position.
+        #[clap(long, value_enum, default_value_t = LiquidityStrategy::SpotBalanced)]
+        strategy: LiquidityStrategy,
+        /// Width of the new position, centered on the active bin. Start with 1 until 70.
+        #[clap(long, default_value_t = 70)]
+        width: i32,
+        /// Share of the value, in token Y, to hold in token X before the deposit. Swaps through the pair to reach it. No swap when omitted.
+        #[clap(long)]
+        target_x_ratio: Option<f64>,
+        /// Slippage tolerance on the quoted amount out of the swap, in bps. Default: the profile slippage, else 100
+        #[clap(long)]
+        slippage_bps: Option<u16>,
+        /// Max number of bins the active bin can move by before the deposit fails.
+        #[clap(long, default_value_t = 3)]
+        max_active_bin_slippage: i32,
+    },
     /// Increase an oracle observation sample length
     IncreaseLength {
         /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Length to add
         length_to_add: u64,
@@ -237,12 +732,14 @@ pub enum Command {
 
     ShowPresetParameter {
         /// Preset parameter pubkey. Get from ListAllBinStep
+        #[clap(value_parser = parse_pubkey)]
         preset_parameter: Pubkey,
     },
 
     ListAllBinStep,
 
     SimulateSwapDemand {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         x_amount: f64, // ex: 10 jup
         y_amount: f64, // ex: 1k usdc
@@ -251,10 +748,10 @@ pub enum Command {
 
     InitializeCustomizablePermissionlessLbPair {
         /// Token X address
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         token_mint_x: Pubkey,
         /// Token Y address
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         token_mint_y: Pubkey,
         /// Bin step
         #[clap(long)]
@@ -282,7 +779,7 @@ pub enum Command {
     /// Seed liquidity
     SeedLiquidity {
         /// Address of the pair
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Base position path
         #[clap(long)]
@@ -297,11 +794,22 @@ pub enum Command {
         #[clap(long)]
         max_price: f64,
         /// Base pubkey
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         base_pubkey: Pubkey,
-        /// Curvature
+        /// Curvature of the power curve. Shorthand of `--curve power:<CURVATURE>`.
+        #[clap(long, conflicts_with = "curve", required_unless_present = "curve")]
+        curvature: Option<f64>,
+        /// Curve of the liquidity over the price range. power:CURVATURE, linear:START,END, exponential:RATE,
+        /// bell:MEAN_PRICE,SIGMA, tranche:PRICE=SHARE,... or csv:PATH of price,weight rows.
+        #[clap(long)]
+        curve: Option<SeedCurve>,
+        /// Render the amount seeded to each bin in the terminal, without seeding
         #[clap(long)]
-        curvature: f64,
+        plot: bool,
+        /// Print the bin range, the positions, bin arrays and token accounts to create, the rent and the
+        /// transaction fees, without seeding. `--output json` exports the plan.
+        #[clap(long)]
+        plan: bool,
         /// Position owner path
         #[clap(long)]
         position_owner_path: String,
@@ -313,7 +821,7 @@ pub enum Command {
     /// Seed liquidity by operator
     SeedLiquidityByOperator {
         /// Address of the pair
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Base position path
         #[clap(long)]
@@ -328,16 +836,27 @@ pub enum Command {
         #[clap(long)]
         max_price: f64,
         /// Base pubkey
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         base_pubkey: Pubkey,
-        /// Curvature
+        /// Curvature of the power curve. Shorthand of `--curve power:<CURVATURE>`.
+        #[clap(long, conflicts_with = "curve", required_unless_present = "curve")]
+        curvature: Option<f64>,
+        /// Curve of the liquidity over the price range. power:CURVATURE, linear:START,END, exponential:RATE,
+        /// bell:MEAN_PRICE,SIGMA, tranche:PRICE=SHARE,... or csv:PATH of price,weight rows.
         #[clap(long)]
-        curvature: f64,
-        /// position owner
+        curve: Option<SeedCurve>,
+        /// Render the amount seeded to each bin in the terminal, without seeding
+        #[clap(long)]
+        plot: bool,
+        /// Print the bin range, the positions, bin arrays and token accounts to create, the rent and the
+        /// transaction fees, without seeding. `--output json` exports the plan.
         #[clap(long)]
+        plan: bool,
+        /// position owner
+        #[clap(long, value_parser = parse_pubkey)]
         position_owner: Pubkey,
         /// fee owner
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         fee_owner: Pubkey,
         /// lock release point
         #[clap(long)]
@@ -349,13 +868,13 @@ pub enum Command {
 
     SeedLiquiditySingleBin {
         /// Address of the pair
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Base position path
         #[clap(long)]
         base_position_path: String,
         /// Base position pubkey
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         base_pubkey: Pubkey,
         /// amount of x
         #[clap(long)]
@@ -372,13 +891,13 @@ pub enum Command {
 
     SeedLiquiditySingleBinByOperator {
         /// Address of the pair
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Base position path
         #[clap(long)]
         base_position_path: String,
         /// Base position pubkey
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         base_pubkey: Pubkey,
         /// amount of x
         #[clap(long)]
@@ -387,32 +906,94 @@ pub enum Command {
         #[clap(long)]
         price: f64,
         /// Position owner
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         position_owner: Pubkey,
         /// lock release point
         #[clap(long)]
         lock_release_point: u64,
         /// fee owner
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         fee_owner: Pubkey,
         /// Selective rounding
         #[clap(long)]
         selective_rounding: SelectiveRounding,
     },
 
+    /// List and compare the pairs of two mints: permissionless pairs of every preset parameter, customizable and permission pairs.
+    FindPairs {
+        /// Mint of one of the tokens. Prices are shown in token B per token A.
+        #[clap(value_parser = parse_pubkey)]
+        token_a_mint: Pubkey,
+        /// Mint of the other token.
+        #[clap(value_parser = parse_pubkey)]
+        token_b_mint: Pubkey,
+        /// Price range around the active price counted in the depth, in bps.
+        #[clap(long, default_value_t = 200)]
+        depth_bps: u16,
+    },
+
     GetAllPositionsForAnOwner {
         /// Address of the pair
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// owner of position
-        #[clap(long)]
+        #[clap(long, value_parser = parse_pubkey)]
         owner: Pubkey,
     },
 
+    /// Positions owned or operated by a wallet across all pairs, valued in token Y.
+    Portfolio {
+        /// Owner or operator of the positions
+        #[clap(long, value_parser = parse_pubkey)]
+        owner: Pubkey,
+        /// UI price of a token Y mint in a reference currency, to value the pairs quoted in it. Format: MINT=PRICE. Can be repeated.
+        #[clap(long = "quote-price", value_parser = parse_mint_price)]
+        quote_prices: Vec<(Pubkey, f64)>,
+    },
+
+    /// Create an address lookup table for a pair, or extend an existing one, with the pair, reserves, mints, oracle,
+    /// event authority, bitmap extension and the bin arrays around the active bin. Use it with --lookup-table.
+    SetupPairLookupTable {
+        /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
+        lb_pair: Pubkey,
+        /// Lookup table to extend. A new one owned by the wallet is created when missing.
+        #[clap(long, value_parser = parse_pubkey)]
+        table: Option<Pubkey>,
+        /// Bin arrays on each side of the active bin array to add
+        #[clap(long, default_value_t = DEFAULT_LOOKUP_TABLE_BIN_ARRAY_RADIUS)]
+        bin_array_radius: i64,
+    },
+
+    /// Send transactions exported with --export once their signatures are collected. Simulating transactions
+    /// exported with --lookup-table takes the same --lookup-table.
+    Submit {
+        /// Export files of the same transactions. The signatures found in all of them are combined.
+        #[clap(required = true)]
+        files: Vec<String>,
+        /// Signature collected out of band. Format: PUBKEY=SIGNATURE
+        #[clap(long = "signature", value_parser = parse_signer_signature)]
+        signatures: Vec<(Pubkey, Signature)>,
+    },
+
+    /// Read or write the settings of the profile in the config file.
+    #[clap(subcommand)]
+    Config(ConfigCommand),
+
     #[clap(flatten)]
     Admin(AdminCommand),
 }
 
+#[derive(Debug, Parser)]
+pub enum ConfigCommand {
+    /// Print a setting of the profile, or all of them. Keys: cluster, wallet, commitment, slippage_bps, priority_fee,
+    /// priority_fee_percentile, max_priority_fee and pairs.<ALIAS>
+    Get { key: Option<String> },
+    /// Write a setting of the profile. The config file and the profile are created when missing.
+    /// Example: config set pairs.sol-usdc <PAIR>
+    Set { key: String, value: String },
+}
+
 #[derive(Parser, Debug)]
 #[clap(version, about, author)]
 pub struct Cli {
@@ -422,6 +1003,28 @@ pub struct Cli {
     pub command: Command,
 }
 
+impl Command {
+    /// Read-only commands never sign, so they run without a wallet keypair file. `submit` only sends transactions
+    /// which are already signed.
+    pub fn is_read_only(&self) -> bool {
+        matches!(
+            self,
+            Command::QuoteExactIn { .. }
+                | Command::QuoteExactOut { .. }
+                | Command::QuotePriceImpact { .. }
+                | Command::ShowPair { .. }
+                | Command::ShowPosition { .. }
+                | Command::ShowPresetParameter { .. }
+                | Command::ListAllBinStep
+                | Command::FindPairs { .. }
+                | Command::GetAllPositionsForAnOwner { .. }
+                | Command::Portfolio { .. }
+                | Command::Submit { .. }
+                | Command::Admin(AdminCommand::CheckMyBalance { .. })
+        )
+    }
+}
+
 #[derive(Debug, Parser)]
 pub enum AdminCommand {
     /// Create a new permission liquidity pair. It allow liquidity fragmentation with exact bin step.
@@ -429,8 +1032,10 @@ pub enum AdminCommand {
         /// Bin step of the liquidity pair. It decide the bps when between bins.
         bin_step: u16,
         /// Token X mint of the liquidity pair. Eg: BTC. This should be the base token.
+        #[clap(value_parser = parse_pubkey)]
         token_mint_x: Pubkey,
         /// Token Y mint of the liquidity pair. Eg: USDC. This should be the quote token.
+        #[clap(value_parser = parse_pubkey)]
         token_mint_y: Pubkey,
         /// The initial price of the liquidity pair. Eg: 24123.12312412 USDC per 1 BTC.
         initial_price: f64,
@@ -447,14 +1052,17 @@ pub enum AdminCommand {
     /// Toggle pool status
     TogglePoolStatus {
         /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
     },
 
     /// Remove liquidity by price range
     RemoveLiquidityByPriceRange {
         /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         // base position path
+        #[clap(value_parser = parse_pubkey)]
         base_position_key: Pubkey,
         /// min price
         min_price: f64,
@@ -464,8 +1072,10 @@ pub enum AdminCommand {
 
     CheckMyBalance {
         /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         // base position path
+        #[clap(value_parser = parse_pubkey)]
         base_position_key: Pubkey,
         /// min price
         min_price: f64,
@@ -475,30 +1085,60 @@ pub enum AdminCommand {
 
     SetActivationPoint {
         /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Activation point
         activation_point: u64,
     },
 
     WithdrawProtocolFee {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         amount_x: u64,
         amount_y: u64,
     },
 
     InitializeReward {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
+        #[clap(value_parser = parse_pubkey)]
         reward_mint: Pubkey,
         reward_index: u64,
         reward_duration: u64,
+        #[clap(value_parser = parse_pubkey)]
         funder: Pubkey,
     },
     FundReward {
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         reward_index: u64,
         funding_amount: u64,
     },
 
+    /// Update the base factor and the protocol share of a pair. Prints the current and new static parameters, and
+    /// sends nothing when they are unchanged.
+    UpdateFeeParameters {
+        /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
+        lb_pair: Pubkey,
+        /// Portion of swap fees retained by the protocol, in bps. Default: unchanged
+        #[clap(long)]
+        protocol_share: Option<u16>,
+        /// Base factor. base_fee_rate = base_factor * bin_step. Default: unchanged
+        #[clap(long, conflicts_with = "base_fee_bps")]
+        base_factor: Option<u16>,
+        /// Base fee in bps, converted to the base factor with the bin step of the pair.
+        #[clap(long)]
+        base_fee_bps: Option<u16>,
+    },
+    /// Withdraw the reward emitted while the active bin had no liquidity to the funder, once the reward ended.
+    WithdrawIneligibleReward {
+        /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
+        lb_pair: Pubkey,
+        reward_index: u64,
+    },
+
     InitializePresetParameter {
         /// Bin step. Represent the price increment / decrement.
         bin_step: u16,
@@ -524,11 +1164,13 @@ pub enum AdminCommand {
     },
     ClosePresetParameter {
         /// Preset parameter pubkey. Get from ListAllBinStep
+        #[clap(value_parser = parse_pubkey)]
         preset_parameter: Pubkey,
     },
 
     SetPreActivationDuration {
         /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Preactivation duration
         pre_activation_duration: u16,
@@ -536,8 +1178,10 @@ pub enum AdminCommand {
 
     SetPreActivationSwapAddress {
         /// Address of the pair
+        #[clap(value_parser = parse_pubkey)]
         lb_pair: Pubkey,
         /// Preactivation swap address
+        #[clap(value_parser = parse_pubkey)]
         pre_activation_swap_address: Pubkey,
     },
 }
@@ -0,0 +1,376 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs;
+use std::path::Path;
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::commitment_config::{CommitmentConfig, CommitmentLevel};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::Cluster;
+use anyhow::*;
+use serde::{Deserialize, Serialize};
+
+use crate::args::{ConfigCommand, ConfigOverride};
+use crate::output::{format_optional, print_key_values};
+
+pub const DEFAULT_PROFILE: &str = "default";
+const DEFAULT_CONFIG_PATH: &str = "~/.config/dlmm/config.toml";
+const DEFAULT_WALLET_PATH: &str = "~/.config/solana/id.json";
+const DEFAULT_SLIPPAGE_BPS: u16 = 100;
+
+/// Keys of `config get` and `config set`. Pair aliases are set with `pairs.<ALIAS>`.
+pub const PROFILE_KEYS: &[&str] = &[
+    "cluster",
+    "wallet",
+    "commitment",
+    "slippage_bps",
+    "priority_fee",
+    "priority_fee_percentile",
+    "max_priority_fee",
+];
+
+thread_local! {
+    /// Pair aliases of the profile, loaded before the command line is parsed so the public key arguments accept them
+    static PAIR_ALIASES: RefCell<BTreeMap<String, Pubkey>> = RefCell::new(BTreeMap::new());
+}
+
+/// Config file holding the named profiles.
+#[derive(Debug, Default, Serialize, Deserialize)]
+pub struct ConfigFile {
+    /// Profile used without `--profile`
+    pub default_profile: Option<String>,
+    #[serde(default)]
+    pub profiles: BTreeMap<String, Profile>,
+}
+
+/// Defaults of the global options, and pair aliases usable wherever a public key is expected.
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Profile {
+    /// mainnet, testnet, devnet, localnet, or an RPC URL
+    pub cluster: Option<String>,
+    pub wallet: Option<String>,
+    pub commitment: Option<CommitmentLevel>,
+    /// Slippage tolerance of the swaps, in bps
+    pub slippage_bps: Option<u16>,
+    /// Compute unit price, in micro lamports
+    pub priority_fee: Option<u64>,
+    pub priority_fee_percentile: Option<u8>,
+    /// Max priority fee paid by a transaction, in lamports
+    pub max_priority_fee: Option<u64>,
+    /// Pair address by alias, such as `sol-usdc`
+    #[serde(default)]
+    pub pairs: BTreeMap<String, String>,
+}
+
+/// Options of an invocation: the command line options, else the profile, else the defaults.
+#[derive(Debug)]
+pub struct ResolvedConfig {
+    pub cluster: Cluster,
+    pub wallet: String,
+    pub commitment: CommitmentConfig,
+    pub slippage_bps: u16,
+    pub priority_fee: u64,
+    pub priority_fee_percentile: Option<u8>,
+    pub max_priority_fee: Option<u64>,
+}
+
+/// Config file path: `$DLMM_CONFIG`, else ~/.config/dlmm/config.toml
+pub fn get_config_path() -> String {
+    std::env::var("DLMM_CONFIG")
+        .unwrap_or_else(|_| shellexpand::tilde(DEFAULT_CONFIG_PATH).to_string())
+}
+
+/// Value of `--profile` in the command line arguments. The profile is needed before the arguments are parsed, to
+/// resolve the pair aliases.
+pub fn get_profile_arg(args: &[String]) -> Option<String> {
+    args.iter().enumerate().find_map(|(i, arg)| {
+        if arg == "--profile" {
+            return args.get(i + 1).cloned();
+        }
+        arg.strip_prefix("--profile=").map(str::to_string)
+    })
+}
+
+/// Pair address of the alias `alias` in the profile loaded by `set_pair_aliases`.
+pub fn get_pair_alias(alias: &str) -> Option<Pubkey> {
+    PAIR_ALIASES.with(|aliases| aliases.borrow().get(alias).copied())
+}
+
+pub fn set_pair_aliases(profile: &Profile) -> Result<()> {
+    let aliases = profile.get_pair_aliases()?;
+    PAIR_ALIASES.with(|pair_aliases| *pair_aliases.borrow_mut() = aliases);
+    Ok(())
+}
+
+fn parse_cluster(cluster: &str) -> Result<Cluster> {
+    Cluster::from_str(cluster).map_err(|err| anyhow!("Invalid cluster {}: {}", cluster, err))
+}
+
+impl ConfigFile {
+    /// Read the config file. It's empty when the file doesn't exist.
+    pub fn read(path: &str) -> Result<Self> {
+        if !Path::new(path).exists() {
+            return Ok(Self::default());
+        }
+        let content =
+            fs::read_to_string(path).with_context(|| format!("Failed to read {}", path))?;
+        toml::from_str(&content).with_context(|| format!("Invalid config file {}", path))
+    }
+
+    pub fn write(&self, path: &str) -> Result<()> {
+        if let Some(dir) = Path::new(path).parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, toml::to_string_pretty(self)?)
+            .with_context(|| format!("Failed to write {}", path))
+    }
+
+    /// Name of the profile selected by `--profile`, else by `default_profile`.
+    pub fn get_profile_name(&self, profile_arg: Option<&str>) -> String {
+        profile_arg
+            .or(self.default_profile.as_deref())
+            .unwrap_or(DEFAULT_PROFILE)
+            .to_string()
+    }
+
+    /// Profile `name`. The default profile may be missing, in which case it's empty.
+    pub fn get_profile(&self, name: &str) -> Result<Profile> {
+        match self.profiles.get(name) {
+            Some(profile) => Ok(profile.clone()),
+            None if name == DEFAULT_PROFILE => Ok(Profile::default()),
+            None => Err(anyhow!(
+                "Profile {} not found in {}",
+                name,
+                get_config_path()
+            )),
+        }
+    }
+}
+
+impl Profile {
+    pub fn get_pair_aliases(&self) -> Result<BTreeMap<String, Pubkey>> {
+        self.pairs
+            .iter()
+            .map(|(alias, lb_pair)| {
+                let lb_pair = Pubkey::from_str(lb_pair).map_err(|_| {
+                    anyhow!("Pair alias {} is not a public key: {}", alias, lb_pair)
+                })?;
+                Ok((alias.clone(), lb_pair))
+            })
+            .collect()
+    }
+
+    /// Value of `key`, None when unset.
+    pub fn get(&self, key: &str) -> Result<Option<String>> {
+        if let Some(alias) = key.strip_prefix("pairs.") {
+            return Ok(self.pairs.get(alias).cloned());
+        }
+        let value = match key {
+            "cluster" => self.cluster.clone(),
+            "wallet" => self.wallet.clone(),
+            "commitment" => self.commitment.map(|commitment| commitment.to_string()),
+            "slippage_bps" => self.slippage_bps.map(|value| value.to_string()),
+            "priority_fee" => self.priority_fee.map(|value| value.to_string()),
+            "priority_fee_percentile" => {
+                self.priority_fee_percentile.map(|value| value.to_string())
+            }
+            "max_priority_fee" => self.max_priority_fee.map(|value| value.to_string()),
+            _ => bail!(
+                "Unknown key {}. Keys: {}, pairs.<ALIAS>",
+                key,
+                PROFILE_KEYS.join(", ")
+            ),
+        };
+        Ok(value)
+    }
+
+    /// Set `key` to `value`, validated as the command line option it provides the default of.
+    pub fn set(&mut self, key: &str, value: &str) -> Result<()> {
+        if let Some(alias) = key.strip_prefix("pairs.") {
+            ensure!(!alias.is_empty(), "Missing pair alias in {}", key);
+            ensure!(
+                Pubkey::from_str(alias).is_err(),
+                "Pair alias {} is a public key",
+                alias
+            );
+            Pubkey::from_str(value).map_err(|_| anyhow!("{} is not a public key", value))?;
+            self.pairs.insert(alias.to_string(), value.to_string());
+            return Ok(());
+        }
+        match key {
+            "cluster" => {
+                parse_cluster(value)?;
+                self.cluster = Some(value.to_string());
+            }
+            "wallet" => self.wallet = Some(value.to_string()),
+            "commitment" => {
+                self.commitment = Some(
+                    CommitmentLevel::from_str(value)
+                        .map_err(|_| anyhow!("Invalid commitment {}", value))?,
+                )
+            }
+            "slippage_bps" => self.slippage_bps = Some(value.parse()?),
+            "priority_fee" => self.priority_fee = Some(value.parse()?),
+            "priority_fee_percentile" => {
+                let percentile = value.parse()?;
+                ensure!(percentile <= 100, "Percentile {} is over 100", percentile);
+                self.priority_fee_percentile = Some(percentile);
+            }
+            "max_priority_fee" => self.max_priority_fee = Some(value.parse()?),
+            _ => bail!(
+                "Unknown key {}. Keys: {}, pairs.<ALIAS>",
+                key,
+                PROFILE_KEYS.join(", ")
+            ),
+        }
+        Ok(())
+    }
+
+    /// Options of an invocation with this profile. Options given on the command line take precedence.
+    pub fn resolve(&self, config_override: &ConfigOverride) -> Result<ResolvedConfig> {
+        let cluster = match (&config_override.cluster, &self.cluster) {
+            (Some(cluster), _) => cluster.clone(),
+            (None, Some(cluster)) => parse_cluster(cluster)?,
+            (None, None) => Cluster::Mainnet,
+        };
+        let wallet = config_override
+            .wallet
+            .clone()
+            .or_else(|| self.wallet.clone())
+            .unwrap_or_else(|| DEFAULT_WALLET_PATH.to_string());
+
+        // A priority fee given on the command line replaces the fee policy of the profile as a whole
+        let (priority_fee, priority_fee_percentile) = match (
+            config_override.priority_fee,
+            config_override.priority_fee_percentile,
+        ) {
+            (None, None) => (self.priority_fee, self.priority_fee_percentile),
+            command_line => command_line,
+        };
+
+        Ok(ResolvedConfig {
+            cluster,
+            wallet: shellexpand::tilde(&wallet).to_string(),
+            commitment: CommitmentConfig {
+                commitment: config_override
+                    .commitment
+                    .or(self.commitment)
+                    .unwrap_or(CommitmentLevel::Confirmed),
+            },
+            slippage_bps: self.slippage_bps.unwrap_or(DEFAULT_SLIPPAGE_BPS),
+            priority_fee: priority_fee.unwrap_or(0),
+            priority_fee_percentile,
+            max_priority_fee: config_override.max_priority_fee.or(self.max_priority_fee),
+        })
+    }
+}
+
+/// Run `config get` or `config set` on the profile `profile_name` of the config file at `path`.
+pub fn run_config_command(
+    command: ConfigCommand,
+    mut config_file: ConfigFile,
+    profile_name: &str,
+    path: &str,
+) -> Result<()> {
+    match command {
+        ConfigCommand::Get { key: Some(key) } => {
+            let profile = config_file.get_profile(profile_name)?;
+            println!("{}", format_optional(profile.get(&key)?));
+        }
+        ConfigCommand::Get { key: None } => {
+            let profile = config_file.get_profile(profile_name)?;
+            let mut rows = vec![("profile".to_string(), profile_name.to_string())];
+            for key in PROFILE_KEYS {
+                rows.push((key.to_string(), format_optional(profile.get(key)?)));
+            }
+            for (alias, lb_pair) in profile.pairs.iter() {
+                rows.push((format!("pairs.{}", alias), lb_pair.clone()));
+            }
+            print_key_values(
+                &rows
+                    .iter()
+                    .map(|(key, value)| (key.as_str(), value.clone()))
+                    .collect::<Vec<_>>(),
+            );
+        }
+        ConfigCommand::Set { key, value } => {
+            config_file
+                .profiles
+                .entry(profile_name.to_string())
+                .or_default()
+                .set(&key, &value)?;
+            config_file.write(path)?;
+            println!(
+                "Set {} of profile {} to {} in {}",
+                key, profile_name, value, path
+            );
+        }
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_profile_arg() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        assert_eq!(
+            get_profile_arg(&args(&["cli", "--profile", "devnet", "show-pair"])),
+            Some("devnet".to_string())
+        );
+        assert_eq!(
+            get_profile_arg(&args(&["cli", "--profile=devnet", "show-pair"])),
+            Some("devnet".to_string())
+        );
+        assert_eq!(get_profile_arg(&args(&["cli", "show-pair"])), None);
+    }
+
+    #[test]
+    fn test_profile_set_get() {
+        let lb_pair = Pubkey::new_unique();
+        let mut profile = Profile::default();
+        profile.set("pairs.sol-usdc", &lb_pair.to_string()).unwrap();
+        profile.set("commitment", "finalized").unwrap();
+        profile.set("slippage_bps", "50").unwrap();
+
+        assert_eq!(profile.get_pair_aliases().unwrap()["sol-usdc"], lb_pair);
+        assert_eq!(
+            profile.get("commitment").unwrap(),
+            Some("finalized".to_string())
+        );
+        assert_eq!(profile.get("priority_fee").unwrap(), None);
+
+        assert!(profile.set("pairs.sol-usdc", "not-a-pubkey").is_err());
+        assert!(profile.set("priority_fee_percentile", "101").is_err());
+        assert!(profile.set("unknown", "1").is_err());
+    }
+
+    #[test]
+    fn test_config_file_toml() {
+        let config_file: ConfigFile = toml::from_str(
+            r#"
+            default_profile = "devnet"
+
+            [profiles.devnet]
+            cluster = "devnet"
+            priority_fee_percentile = 75
+
+            [profiles.devnet.pairs]
+            sol-usdc = "11111111111111111111111111111111"
+            "#,
+        )
+        .unwrap();
+
+        assert_eq!(config_file.get_profile_name(None), "devnet");
+        assert_eq!(config_file.get_profile_name(Some("mainnet")), "mainnet");
+        let profile = config_file.get_profile("devnet").unwrap();
+        assert_eq!(profile.priority_fee_percentile, Some(75));
+        assert_eq!(profile.pairs.len(), 1);
+        assert!(config_file.get_profile("mainnet").is_err());
+        assert!(config_file.get_profile(DEFAULT_PROFILE).is_ok());
+    }
+}
@@ -0,0 +1,346 @@
+use std::cell::RefCell;
+use std::collections::BTreeMap;
+use std::fs::File;
+use std::io::{BufReader, BufWriter};
+use std::ops::Deref;
+
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::compute_budget;
+use anchor_client::solana_sdk::hash::{hash, Hash};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::{Message, VersionedMessage};
+use anchor_client::solana_sdk::nonce::state::{State as NonceState, Versions as NonceVersions};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::{system_instruction, system_program};
+use anchor_client::Program;
+use anyhow::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+
+use crate::lookup_table::LookupTables;
+
+pub const EXPORT_FILE_VERSION: u32 = 1;
+
+/// lb_clmm instructions, used to name the instructions of an exported transaction.
+const LB_CLMM_INSTRUCTIONS: &[&str] = &[
+    "initialize_lb_pair",
+    "initialize_customizable_permissionless_lb_pair",
+    "initialize_permission_lb_pair",
+    "initialize_bin_array_bitmap_extension",
+    "initialize_bin_array",
+    "add_liquidity",
+    "add_liquidity_by_weight",
+    "add_liquidity_by_strategy",
+    "add_liquidity_by_strategy_one_side",
+    "add_liquidity_one_side",
+    "add_liquidity_one_side_precise",
+    "remove_liquidity",
+    "remove_all_liquidity",
+    "initialize_position",
+    "initialize_position_pda",
+    "initialize_position_by_operator",
+    "update_position_operator",
+    "swap",
+    "swap_exact_out",
+    "swap_with_price_impact",
+    "withdraw_protocol_fee",
+    "initialize_reward",
+    "fund_reward",
+    "update_reward_funder",
+    "update_reward_duration",
+    "claim_reward",
+    "claim_fee",
+    "close_position",
+    "update_fee_parameters",
+    "increase_oracle_length",
+    "initialize_preset_parameter",
+    "close_preset_parameter",
+    "toggle_pair_status",
+    "migrate_position",
+    "migrate_bin_array",
+    "update_fees_and_rewards",
+    "withdraw_ineligible_reward",
+    "set_activation_point",
+    "set_pre_activation_duration",
+    "set_pre_activation_swap_address",
+];
+
+/// File written by `--export` and read by `submit`.
+#[derive(Debug, Serialize, Deserialize)]
+pub struct ExportFile {
+    pub version: u32,
+    pub transactions: Vec<ExportedTransaction>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ExportedTransaction {
+    /// Command line the transaction was built by
+    pub command: String,
+    /// Human readable description of the instructions
+    pub summary: Vec<String>,
+    /// Base64 encoded serialized message, legacy or v0 when compiled against lookup tables. This is what the
+    /// signers sign.
+    pub message: String,
+    pub blockhash: String,
+    /// Set when `blockhash` is the durable nonce of this account
+    pub nonce_account: Option<String>,
+    /// Required signers, in the order of the transaction signatures
+    pub signers: Vec<String>,
+    /// Signatures collected so far, by signer
+    pub signatures: BTreeMap<String, String>,
+}
+
+impl ExportFile {
+    pub fn read(path: &str) -> Result<Self> {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path))?;
+        let export_file: ExportFile = serde_json::from_reader(BufReader::new(file))?;
+        ensure!(
+            export_file.version == EXPORT_FILE_VERSION,
+            "Unsupported export file version {} in {}",
+            export_file.version,
+            path
+        );
+        Ok(export_file)
+    }
+
+    pub fn write(&self, path: &str) -> Result<()> {
+        let file = File::create(path).with_context(|| format!("Failed to create {}", path))?;
+        serde_json::to_writer_pretty(BufWriter::new(file), self)?;
+        Ok(())
+    }
+}
+
+impl ExportedTransaction {
+    pub fn message_bytes(&self) -> Result<Vec<u8>> {
+        Ok(STANDARD.decode(&self.message)?)
+    }
+}
+
+#[derive(Debug, Clone, Copy)]
+pub enum BlockhashSource {
+    /// Latest blockhash of the cluster. The transactions must be signed and submitted before it expires.
+    Recent,
+    /// Blockhash given on the command line, for signing without network access
+    Fixed(Hash),
+    /// Durable nonce. An `advance_nonce_account` instruction is prepended to the transactions.
+    Nonce { account: Pubkey, authority: Pubkey },
+}
+
+/// Collects the transactions of a command run with `--export` into the export file.
+#[derive(Debug)]
+pub struct TransactionExport {
+    path: String,
+    blockhash_source: BlockhashSource,
+    /// Wallet keypair signing the exported messages with `--sign-only`
+    signer: Option<Keypair>,
+    transactions: RefCell<Vec<ExportedTransaction>>,
+}
+
+impl TransactionExport {
+    pub fn new(path: String, blockhash_source: BlockhashSource, signer: Option<Keypair>) -> Self {
+        Self {
+            path,
+            blockhash_source,
+            signer,
+            transactions: RefCell::new(vec![]),
+        }
+    }
+
+    /// Add a transaction to the export file. The file is rewritten after every transaction so the
+    /// transactions exported before a failure are kept. `signers` are the extra local signers of the
+    /// transaction, such as new account keypairs, which only exist in this run. They always sign the
+    /// exported message, as it could never be submitted otherwise. The message is compiled against `lookup_tables`
+    /// as a v0 message when given.
+    pub async fn add<C: Deref<Target = impl Signer> + Clone>(
+        &self,
+        program: &Program<C>,
+        mut instructions: Vec<Instruction>,
+        signers: &[&dyn Signer],
+        lookup_tables: Option<&LookupTables>,
+    ) -> Result<()> {
+        let (blockhash, nonce_account) = match self.blockhash_source {
+            BlockhashSource::Recent => (program.async_rpc().get_latest_blockhash().await?, None),
+            BlockhashSource::Fixed(blockhash) => (blockhash, None),
+            BlockhashSource::Nonce { account, authority } => {
+                let nonce = program.async_rpc().get_account(&account).await?;
+                instructions.insert(
+                    0,
+                    system_instruction::advance_nonce_account(&account, &authority),
+                );
+                (get_durable_nonce(&account, &nonce)?, Some(account))
+            }
+        };
+
+        let (message, account_keys) = match lookup_tables {
+            Some(lookup_tables) => {
+                let message = lookup_tables.compile(&instructions, blockhash)?;
+                let account_keys = lookup_tables.get_account_keys(&message)?;
+                (message, account_keys)
+            }
+            None => {
+                let message =
+                    Message::new_with_blockhash(&instructions, Some(&program.payer()), &blockhash);
+                let account_keys = message.account_keys.clone();
+                (VersionedMessage::Legacy(message), account_keys)
+            }
+        };
+        let message_bytes = message.serialize();
+        let required_signers =
+            &message.static_account_keys()[..usize::from(message.header().num_required_signatures)];
+
+        let mut signatures = BTreeMap::new();
+        let wallet_signer = self.signer.as_ref().map(|signer| signer as &dyn Signer);
+        for signer in signers.iter().copied().chain(wallet_signer) {
+            if required_signers.contains(&signer.pubkey()) {
+                let signature = signer.try_sign_message(&message_bytes)?;
+                signatures.insert(signer.pubkey().to_string(), signature.to_string());
+            }
+        }
+
+        let transaction = ExportedTransaction {
+            command: std::env::args().collect::<Vec<_>>().join(" "),
+            summary: summarize_message(&message, &account_keys),
+            message: STANDARD.encode(&message_bytes),
+            blockhash: blockhash.to_string(),
+            nonce_account: nonce_account.map(|account| account.to_string()),
+            signers: required_signers.iter().map(Pubkey::to_string).collect(),
+            signatures,
+        };
+
+        println!("Exported transaction {}", self.transactions.borrow().len());
+        for line in transaction.summary.iter() {
+            println!("  {line}");
+        }
+        println!("  Blockhash: {}", transaction.blockhash);
+        println!("  Signers (Pubkey=Signature):");
+        for signer in transaction.signers.iter() {
+            match transaction.signatures.get(signer) {
+                Some(signature) => println!("    {signer}={signature}"),
+                None => println!("    {signer} (missing)"),
+            }
+        }
+
+        self.transactions.borrow_mut().push(transaction);
+
+        let export_file = ExportFile {
+            version: EXPORT_FILE_VERSION,
+            transactions: self.transactions.borrow().clone(),
+        };
+        export_file.write(&self.path)?;
+        println!("Written to {}", self.path);
+
+        Ok(())
+    }
+}
+
+fn get_durable_nonce(nonce_account: &Pubkey, account: &Account) -> Result<Hash> {
+    ensure!(
+        account.owner == system_program::ID,
+        "{} is not a nonce account",
+        nonce_account
+    );
+    let versions: NonceVersions = bincode::deserialize(&account.data)?;
+    match versions.state() {
+        NonceState::Initialized(data) => Ok(data.blockhash()),
+        NonceState::Uninitialized => Err(anyhow!(
+            "Nonce account {} is not initialized",
+            nonce_account
+        )),
+    }
+}
+
+fn instruction_name(program_id: &Pubkey, data: &[u8]) -> String {
+    if *program_id == lb_clmm::ID {
+        let name = LB_CLMM_INSTRUCTIONS.iter().find(|name| {
+            let sighash = hash(format!("global:{}", name).as_bytes()).to_bytes();
+            data.get(..8) == Some(&sighash[..8])
+        });
+        return match name {
+            Some(name) => format!("lb_clmm::{}", name),
+            None => "lb_clmm::unknown".to_string(),
+        };
+    }
+
+    if *program_id == system_program::ID {
+        "system_program".to_string()
+    } else if *program_id == compute_budget::ID {
+        "compute_budget".to_string()
+    } else if *program_id == spl_associated_token_account::ID {
+        "associated_token_account".to_string()
+    } else if *program_id == anchor_spl::token::ID {
+        "token".to_string()
+    } else {
+        program_id.to_string()
+    }
+}
+
+/// `account_keys` are the keys of `message` in the order its instructions index them, including the keys loaded
+/// from lookup tables.
+fn summarize_message(message: &VersionedMessage, account_keys: &[Pubkey]) -> Vec<String> {
+    let mut summary = vec![format!("Fee payer: {}", account_keys[0])];
+    for (i, ix) in message.instructions().iter().enumerate() {
+        let program_id = account_keys[usize::from(ix.program_id_index)];
+        summary.push(format!(
+            "Instruction {}: {}",
+            i,
+            instruction_name(&program_id, &ix.data)
+        ));
+        for account_index in ix.accounts.iter() {
+            let index = usize::from(*account_index);
+            let mut flags = vec![];
+            if message.is_maybe_writable(index) {
+                flags.push("writable");
+            }
+            if message.is_signer(index) {
+                flags.push("signer");
+            }
+            summary.push(format!("  {} {}", account_keys[index], flags.join(" ")));
+        }
+    }
+    summary
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+    use anchor_client::solana_sdk::message::v0;
+    use anchor_lang::InstructionData;
+
+    #[test]
+    fn test_summarize_message() {
+        let admin = Pubkey::new_unique();
+        let lb_pair = Pubkey::new_unique();
+        let ix = Instruction {
+            program_id: lb_clmm::ID,
+            accounts: vec![
+                anchor_lang::prelude::AccountMeta::new_readonly(admin, true),
+                anchor_lang::prelude::AccountMeta::new(lb_pair, false),
+            ],
+            data: lb_clmm::instruction::TogglePairStatus {}.data(),
+        };
+        let message = Message::new(&[ix.clone()], Some(&admin));
+        let account_keys = message.account_keys.clone();
+
+        let summary = summarize_message(&VersionedMessage::Legacy(message), &account_keys);
+        assert_eq!(summary[1], "Instruction 0: lb_clmm::toggle_pair_status");
+        assert_eq!(summary[2], format!("  {} writable signer", admin));
+        assert_eq!(summary[3], format!("  {} writable", lb_pair));
+
+        // The pair is loaded from the lookup table, after the static keys
+        let lookup_table = AddressLookupTableAccount {
+            key: Pubkey::new_unique(),
+            addresses: vec![lb_pair],
+        };
+        let message =
+            v0::Message::try_compile(&admin, &[ix], &[lookup_table], Hash::default()).unwrap();
+        let mut account_keys = message.account_keys.clone();
+        account_keys.push(lb_pair);
+
+        let summary = summarize_message(&VersionedMessage::V0(message), &account_keys);
+        assert_eq!(summary[2], format!("  {} writable signer", admin));
+        assert_eq!(summary[3], format!("  {} writable", lb_pair));
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
 use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
@@ -27,7 +27,7 @@ pub struct AddLiquidityParam {
 pub async fn add_liquidity<C: Deref<Target = impl Signer> + Clone>(
     params: AddLiquidityParam,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let AddLiquidityParam {
@@ -113,17 +113,14 @@ pub async fn add_liquidity<C: Deref<Target = impl Signer> + Clone>(
 
     let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(1_400_000);
 
-    let request_builder = program.request();
-    let signature = request_builder
+    let request_builder = program
+        .request()
         .instruction(compute_budget_ix)
         .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Add Liquidity. Signature: {:#?}", signature);
-
-    signature?;
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Add Liquidity. Signature: {}", signature);
+    }
 
     Ok(())
 }
@@ -0,0 +1,79 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{compute_budget_instructions, BinRange, LiquidityTarget};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::instruction;
+use lb_clmm::instructions::deposit::{
+    LiquidityParameterByStrategy, StrategyParameters, StrategyType,
+};
+
+#[derive(Debug)]
+pub struct AddLiquidityByStrategyParameters {
+    pub position: Pubkey,
+    pub amount_x: f64,
+    pub amount_y: f64,
+    /// Whole position when `None`
+    pub bin_range: Option<BinRange>,
+    pub strategy_type: StrategyType,
+    pub max_active_bin_slippage: i32,
+}
+
+pub async fn add_liquidity_by_strategy<C: Deref<Target = impl Signer> + Clone>(
+    params: AddLiquidityByStrategyParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let AddLiquidityByStrategyParameters {
+        position,
+        amount_x,
+        amount_y,
+        bin_range,
+        strategy_type,
+        max_active_bin_slippage,
+    } = params;
+
+    let target = LiquidityTarget::fetch(program, position).await?;
+    let (min_bin_id, max_bin_id) = target.get_bin_range(bin_range)?;
+    let amount_x = target.to_amount(amount_x, false)?;
+    let amount_y = target.to_amount(amount_y, true)?;
+
+    let accounts = target
+        .get_modify_liquidity_accounts(program, transaction_config, compute_unit_price.clone())
+        .await?;
+
+    println!(
+        "Deposit {} X and {} Y to position {} (bins {} - {}, {:?})",
+        amount_x, amount_y, position, min_bin_id, max_bin_id, strategy_type
+    );
+
+    let ix = instruction::AddLiquidityByStrategy {
+        liquidity_parameter: LiquidityParameterByStrategy {
+            amount_x,
+            amount_y,
+            active_id: target.lb_pair_state.active_id,
+            max_active_bin_slippage,
+            strategy_parameters: StrategyParameters {
+                min_bin_id,
+                max_bin_id,
+                strategy_type,
+                parameteres: [0u8; 64],
+            },
+        },
+    };
+
+    let request_builder = compute_budget_instructions(1_400_000, compute_unit_price)
+        .into_iter()
+        .fold(program.request(), |bld, ix| bld.instruction(ix))
+        .accounts(accounts)
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Add liquidity by strategy. Signature: {}", signature);
+    }
+
+    Ok(())
+}
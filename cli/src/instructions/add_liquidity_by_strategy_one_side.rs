@@ -0,0 +1,92 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{compute_budget_instructions, BinRange, LiquidityTarget};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::instruction;
+use lb_clmm::instructions::deposit::{
+    LiquidityParameterByStrategyOneSide, StrategyParameters, StrategyType,
+};
+
+#[derive(Debug)]
+pub struct AddLiquidityByStrategyOneSideParameters {
+    pub position: Pubkey,
+    /// Deposit token Y when true, else token X
+    pub token_y: bool,
+    pub amount: f64,
+    /// Whole position when `None`
+    pub bin_range: Option<BinRange>,
+    pub strategy_type: StrategyType,
+    pub max_active_bin_slippage: i32,
+}
+
+pub async fn add_liquidity_by_strategy_one_side<C: Deref<Target = impl Signer> + Clone>(
+    params: AddLiquidityByStrategyOneSideParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let AddLiquidityByStrategyOneSideParameters {
+        position,
+        token_y,
+        amount,
+        bin_range,
+        strategy_type,
+        max_active_bin_slippage,
+    } = params;
+
+    let target = LiquidityTarget::fetch(program, position).await?;
+    let (min_bin_id, max_bin_id) = target.get_bin_range(bin_range)?;
+    target.ensure_bins_on_side(min_bin_id, max_bin_id, token_y)?;
+    let amount = target.to_amount(amount, token_y)?;
+
+    let accounts = target
+        .get_modify_liquidity_one_side_accounts(
+            program,
+            token_y,
+            transaction_config,
+            compute_unit_price.clone(),
+        )
+        .await?;
+
+    println!(
+        "Deposit {} {} to position {} (bins {} - {}, {:?})",
+        amount,
+        if token_y { "Y" } else { "X" },
+        position,
+        min_bin_id,
+        max_bin_id,
+        strategy_type
+    );
+
+    let ix = instruction::AddLiquidityByStrategyOneSide {
+        liquidity_parameter: LiquidityParameterByStrategyOneSide {
+            amount,
+            active_id: target.lb_pair_state.active_id,
+            max_active_bin_slippage,
+            strategy_parameters: StrategyParameters {
+                min_bin_id,
+                max_bin_id,
+                strategy_type,
+                parameteres: [0u8; 64],
+            },
+        },
+    };
+
+    let request_builder = compute_budget_instructions(1_400_000, compute_unit_price)
+        .into_iter()
+        .fold(program.request(), |bld, ix| bld.instruction(ix))
+        .accounts(accounts)
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!(
+            "Add liquidity by strategy one side. Signature: {}",
+            signature
+        );
+    }
+
+    Ok(())
+}
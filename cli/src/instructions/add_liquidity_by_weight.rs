@@ -0,0 +1,73 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{compute_budget_instructions, get_bin_weights, LiquidityTarget};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::instruction;
+use lb_clmm::instructions::deposit::LiquidityParameterByWeight;
+
+#[derive(Debug)]
+pub struct AddLiquidityByWeightParameters {
+    pub position: Pubkey,
+    pub amount_x: f64,
+    pub amount_y: f64,
+    /// (bin id, weight)
+    pub bin_weights: Vec<(i32, u16)>,
+    pub max_active_bin_slippage: i32,
+}
+
+pub async fn add_liquidity_by_weight<C: Deref<Target = impl Signer> + Clone>(
+    params: AddLiquidityByWeightParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let AddLiquidityByWeightParameters {
+        position,
+        amount_x,
+        amount_y,
+        bin_weights,
+        max_active_bin_slippage,
+    } = params;
+
+    let target = LiquidityTarget::fetch(program, position).await?;
+    let bin_liquidity_dist = get_bin_weights(&target, bin_weights)?;
+    let amount_x = target.to_amount(amount_x, false)?;
+    let amount_y = target.to_amount(amount_y, true)?;
+
+    let accounts = target
+        .get_modify_liquidity_accounts(program, transaction_config, compute_unit_price.clone())
+        .await?;
+
+    println!(
+        "Deposit {} X and {} Y to position {} ({} bins)",
+        amount_x,
+        amount_y,
+        position,
+        bin_liquidity_dist.len()
+    );
+
+    let ix = instruction::AddLiquidityByWeight {
+        liquidity_parameter: LiquidityParameterByWeight {
+            amount_x,
+            amount_y,
+            active_id: target.lb_pair_state.active_id,
+            max_active_bin_slippage,
+            bin_liquidity_dist,
+        },
+    };
+
+    let request_builder = compute_budget_instructions(1_400_000, compute_unit_price)
+        .into_iter()
+        .fold(program.request(), |bld, ix| bld.instruction(ix))
+        .accounts(accounts)
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Add liquidity by weight. Signature: {}", signature);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,80 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{compute_budget_instructions, get_bin_weights, LiquidityTarget};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::instruction;
+use lb_clmm::instructions::deposit::LiquidityOneSideParameter;
+
+#[derive(Debug)]
+pub struct AddLiquidityOneSideParameters {
+    pub position: Pubkey,
+    /// Deposit token Y when true, else token X
+    pub token_y: bool,
+    pub amount: f64,
+    /// (bin id, weight)
+    pub bin_weights: Vec<(i32, u16)>,
+    pub max_active_bin_slippage: i32,
+}
+
+pub async fn add_liquidity_one_side<C: Deref<Target = impl Signer> + Clone>(
+    params: AddLiquidityOneSideParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let AddLiquidityOneSideParameters {
+        position,
+        token_y,
+        amount,
+        bin_weights,
+        max_active_bin_slippage,
+    } = params;
+
+    let target = LiquidityTarget::fetch(program, position).await?;
+    let bin_liquidity_dist = get_bin_weights(&target, bin_weights)?;
+    let min_bin_id = bin_liquidity_dist[0].bin_id;
+    let max_bin_id = bin_liquidity_dist[bin_liquidity_dist.len() - 1].bin_id;
+    target.ensure_bins_on_side(min_bin_id, max_bin_id, token_y)?;
+    let amount = target.to_amount(amount, token_y)?;
+
+    let accounts = target
+        .get_modify_liquidity_one_side_accounts(
+            program,
+            token_y,
+            transaction_config,
+            compute_unit_price.clone(),
+        )
+        .await?;
+
+    println!(
+        "Deposit {} {} to position {} ({} bins)",
+        amount,
+        if token_y { "Y" } else { "X" },
+        position,
+        bin_liquidity_dist.len()
+    );
+
+    let ix = instruction::AddLiquidityOneSide {
+        liquidity_parameter: LiquidityOneSideParameter {
+            amount,
+            active_id: target.lb_pair_state.active_id,
+            max_active_bin_slippage,
+            bin_liquidity_dist,
+        },
+    };
+
+    let request_builder = compute_budget_instructions(1_400_000, compute_unit_price)
+        .into_iter()
+        .fold(program.request(), |bld, ix| bld.instruction(ix))
+        .accounts(accounts)
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Add liquidity one side. Signature: {}", signature);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,90 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{
+    compress_bin_amounts, compute_budget_instructions, LiquidityTarget,
+};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::instruction;
+use lb_clmm::instructions::deposit::AddLiquiditySingleSidePreciseParameter;
+
+#[derive(Debug)]
+pub struct AddLiquidityOneSidePreciseParameters {
+    pub position: Pubkey,
+    /// Deposit token Y when true, else token X
+    pub token_y: bool,
+    /// (bin id, UI amount)
+    pub bin_amounts: Vec<(i32, f64)>,
+}
+
+pub async fn add_liquidity_one_side_precise<C: Deref<Target = impl Signer> + Clone>(
+    params: AddLiquidityOneSidePreciseParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let AddLiquidityOneSidePreciseParameters {
+        position,
+        token_y,
+        mut bin_amounts,
+    } = params;
+
+    let target = LiquidityTarget::fetch(program, position).await?;
+
+    bin_amounts.sort_by_key(|(bin_id, _)| *bin_id);
+    let mut amounts = vec![];
+    for (i, &(bin_id, ui_amount)) in bin_amounts.iter().enumerate() {
+        target.ensure_bin_in_position(bin_id)?;
+        ensure!(
+            i == 0 || bin_amounts[i - 1].0 != bin_id,
+            "Bin {} is given more than once",
+            bin_id
+        );
+        amounts.push((bin_id, target.to_amount(ui_amount, token_y)?));
+    }
+    let (bins, decompress_multiplier) = compress_bin_amounts(&amounts)?;
+    target.ensure_bins_on_side(bins[0].bin_id, bins[bins.len() - 1].bin_id, token_y)?;
+
+    let accounts = target
+        .get_modify_liquidity_one_side_accounts(
+            program,
+            token_y,
+            transaction_config,
+            compute_unit_price.clone(),
+        )
+        .await?;
+
+    let amount = bins
+        .iter()
+        .map(|bin| u64::from(bin.amount) * decompress_multiplier)
+        .sum::<u64>();
+    println!(
+        "Deposit {} {} to position {} ({} bins, decompress multiplier {})",
+        amount,
+        if token_y { "Y" } else { "X" },
+        position,
+        bins.len(),
+        decompress_multiplier
+    );
+
+    let ix = instruction::AddLiquidityOneSidePrecise {
+        parameter: AddLiquiditySingleSidePreciseParameter {
+            bins,
+            decompress_multiplier,
+        },
+    };
+
+    let request_builder = compute_budget_instructions(1_400_000, compute_unit_price)
+        .into_iter()
+        .fold(program.request(), |bld, ix| bld.instruction(ix))
+        .accounts(accounts)
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Add liquidity one side precise. Signature: {}", signature);
+    }
+
+    Ok(())
+}
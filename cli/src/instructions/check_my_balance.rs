@@ -1,5 +1,7 @@
 use crate::math::get_id_from_price;
 use crate::math::price_per_token_to_per_lamport;
+use crate::math::to_ui_amount;
+use crate::output::{print_key_values, print_report, OutputFormat, Report};
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anchor_spl::token_interface::Mint;
 use anyhow::*;
@@ -13,6 +15,7 @@ use lb_clmm::state::bin::BinArray;
 use lb_clmm::state::lb_pair::LbPair;
 use lb_clmm::state::position::PositionV2;
 use lb_clmm::utils::pda::*;
+use serde::Serialize;
 use std::ops::Deref;
 use std::result::Result::Ok;
 #[derive(Debug)]
@@ -23,9 +26,44 @@ pub struct CheckMyBalanceParameters {
     pub max_price: f64,
 }
 
+#[derive(Debug, Serialize)]
+pub struct BalanceReport {
+    pub lb_pair: String,
+    pub active_id: i32,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub ui_amount_x: f64,
+    pub ui_amount_y: f64,
+    pub fee_x_pending: u64,
+    pub fee_y_pending: u64,
+    pub ui_fee_x_pending: f64,
+    pub ui_fee_y_pending: f64,
+}
+
+impl Report for BalanceReport {
+    const COMMAND: &'static str = "check-my-balance";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        print_key_values(&[
+            ("Pair", self.lb_pair.clone()),
+            ("Active bin", self.active_id.to_string()),
+            (
+                "Amount",
+                format!("{} X, {} Y", self.ui_amount_x, self.ui_amount_y),
+            ),
+            (
+                "Pending fee",
+                format!("{} X, {} Y", self.ui_fee_x_pending, self.ui_fee_y_pending),
+            ),
+        ]);
+    }
+}
+
 pub async fn check_my_balance<C: Deref<Target = impl Signer> + Clone>(
     params: CheckMyBalanceParameters,
     program: &Program<C>,
+    output: OutputFormat,
 ) -> Result<()> {
     let CheckMyBalanceParameters {
         lb_pair,
@@ -40,8 +78,6 @@ pub async fn check_my_balance<C: Deref<Target = impl Signer> + Clone>(
 
     let lb_pair_state: LbPair = program.account(lb_pair).await?;
 
-    println!("active bin {}", lb_pair_state.active_id);
-
     let bin_step = lb_pair_state.bin_step;
     let min_price_per_lamport = price_per_token_to_per_lamport(
         min_price,
@@ -107,20 +143,20 @@ pub async fn check_my_balance<C: Deref<Target = impl Signer> + Clone>(
             Err(_err) => continue, // TODO handle rpc call here
         }
     }
-    let total_amount_x =
-        total_amount_x as f64 / (10u64.pow(token_mint_base.decimals as u32) as f64);
-    let total_amount_y =
-        total_amount_y as f64 / (10u64.pow(token_mint_quote.decimals as u32) as f64);
-
-    let total_fee_x_pending =
-        total_fee_x_pending as f64 / (10u64.pow(token_mint_base.decimals as u32) as f64);
-    let total_fee_y_pending =
-        total_fee_y_pending as f64 / (10u64.pow(token_mint_quote.decimals as u32) as f64);
-
-    println!(
-        "amount_x {total_amount_x} amount_y {total_amount_y} fee_x_pending {total_fee_x_pending} fee_y_pending {total_fee_y_pending}"
-    );
-    Ok(())
+    let report = BalanceReport {
+        lb_pair: lb_pair.to_string(),
+        active_id: lb_pair_state.active_id,
+        amount_x: total_amount_x,
+        amount_y: total_amount_y,
+        ui_amount_x: to_ui_amount(total_amount_x, token_mint_base.decimals),
+        ui_amount_y: to_ui_amount(total_amount_y, token_mint_quote.decimals),
+        fee_x_pending: total_fee_x_pending,
+        fee_y_pending: total_fee_y_pending,
+        ui_fee_x_pending: to_ui_amount(total_fee_x_pending, token_mint_base.decimals),
+        ui_fee_y_pending: to_ui_amount(total_fee_y_pending, token_mint_quote.decimals),
+    };
+
+    print_report(output, &report)
 }
 
 pub struct BinArrayManager<'a> {
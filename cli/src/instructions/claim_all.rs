@@ -0,0 +1,304 @@
+use std::collections::{BTreeSet, HashMap};
+use std::ops::Deref;
+
+use anchor_client::solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::spl_token;
+use anyhow::*;
+use commons::position::{get_position_pending_fees, get_position_pending_rewards};
+use lb_clmm::accounts;
+use lb_clmm::events;
+use lb_clmm::instruction;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::state::position::PositionV2;
+use lb_clmm::utils::pda::{derive_event_authority_pda, derive_reward_vault_pda};
+use spl_associated_token_account::instruction::create_associated_token_account;
+
+use crate::instructions::utils::{
+    derive_position_bin_arrays, get_clock, get_multiple_accounts_chunked, get_owned_positions,
+    pack_instructions, BudgetedInstruction, POSITION_V2_FEE_OWNER_OFFSET,
+};
+use crate::output::print_table;
+use crate::transaction::{
+    get_transaction_events, parse_event, send_or_simulate, TransactionConfig,
+};
+
+/// Compute units budgeted for a claim_fee or claim_reward instruction
+const CLAIM_COMPUTE_UNITS: u32 = 350_000;
+/// Compute units budgeted for an associated token account creation
+const CREATE_ATA_COMPUTE_UNITS: u32 = 50_000;
+
+#[derive(Debug)]
+pub struct ClaimAllParameters {
+    /// Claim the positions whose fees go to this fee owner, instead of the positions of the wallet.
+    /// The wallet must be the owner or the operator of these positions.
+    pub fee_owner: Option<Pubkey>,
+}
+
+/// Positions the wallet can claim, owned or operated by it.
+async fn get_claimable_positions<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    fee_owner: Option<Pubkey>,
+) -> Result<Vec<(Pubkey, PositionV2)>> {
+    let wallet = program.payer();
+
+    let Some(fee_owner) = fee_owner else {
+        let mut positions = vec![];
+        for position in get_owned_positions(program, wallet).await? {
+            if position.version == 1 {
+                println!(
+                    "Skip legacy position {}. Migrate it to claim its fees and rewards.",
+                    position.key
+                );
+                continue;
+            }
+            positions.push((position.key, position.state));
+        }
+        return Ok(positions);
+    };
+
+    let positions: Vec<(Pubkey, PositionV2)> = program
+        .accounts(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            POSITION_V2_FEE_OWNER_OFFSET,
+            &fee_owner.to_bytes(),
+        ))])
+        .await?;
+
+    Ok(positions
+        .into_iter()
+        .filter(|(key, position)| {
+            let authorized = position.owner == wallet || position.operator == wallet;
+            if !authorized {
+                println!(
+                    "Skip position {}. The wallet is neither its owner nor its operator.",
+                    key
+                );
+            }
+            authorized
+        })
+        .collect())
+}
+
+pub async fn claim_all<C: Deref<Target = impl Signer> + Clone>(
+    params: ClaimAllParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let ClaimAllParameters { fee_owner } = params;
+    let wallet = program.payer();
+
+    let positions = get_claimable_positions(program, fee_owner).await?;
+
+    let mut pair_keys = positions
+        .iter()
+        .map(|(_, position)| position.lb_pair)
+        .collect::<Vec<_>>();
+    pair_keys.sort();
+    pair_keys.dedup();
+    let lb_pairs = get_multiple_accounts_chunked(program, &pair_keys)
+        .await?
+        .into_iter()
+        .zip(pair_keys.iter())
+        .map(|(account, key)| {
+            let account = account.with_context(|| format!("Pair {} not found", key))?;
+            Ok((*key, LbPair::try_deserialize(&mut account.data.as_ref())?))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let mut bin_array_keys = vec![];
+    for (_, position) in positions.iter() {
+        bin_array_keys.extend(derive_position_bin_arrays(
+            position.lb_pair,
+            position.lower_bin_id,
+        )?);
+    }
+    bin_array_keys.sort();
+    bin_array_keys.dedup();
+    let bin_arrays = get_multiple_accounts_chunked(program, &bin_array_keys)
+        .await?
+        .into_iter()
+        .zip(bin_array_keys.iter())
+        .filter_map(|(account, key)| Some((*key, account?)))
+        .map(|(key, account)| Ok((key, BinArray::try_deserialize(&mut account.data.as_ref())?)))
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let clock = get_clock(program).await?;
+    let (event_authority, _bump) = derive_event_authority_pda();
+
+    // (owner, mint) of the token accounts receiving the claims
+    let mut token_accounts = BTreeSet::new();
+    let mut claim_ixs = vec![];
+
+    for (position, position_state) in positions.iter() {
+        let lb_pair = position_state.lb_pair;
+        let lb_pair_state = &lb_pairs[&lb_pair];
+        let [bin_array_lower, bin_array_upper] =
+            derive_position_bin_arrays(lb_pair, position_state.lower_bin_id)?;
+        let position_bin_arrays = [bin_array_lower, bin_array_upper]
+            .iter()
+            .filter_map(|key| bin_arrays.get(key).copied())
+            .collect::<Vec<_>>();
+
+        let (fee_x, fee_y) = get_position_pending_fees(position_state, &position_bin_arrays)?;
+        if fee_x > 0 || fee_y > 0 {
+            let fee_receiver = if position_state.fee_owner == Pubkey::default() {
+                position_state.owner
+            } else {
+                position_state.fee_owner
+            };
+            token_accounts.insert((fee_receiver, lb_pair_state.token_x_mint));
+            token_accounts.insert((fee_receiver, lb_pair_state.token_y_mint));
+
+            claim_ixs.push(BudgetedInstruction {
+                instruction: Instruction {
+                    program_id: lb_clmm::ID,
+                    accounts: accounts::ClaimFee {
+                        bin_array_lower,
+                        bin_array_upper,
+                        lb_pair,
+                        sender: wallet,
+                        position: *position,
+                        reserve_x: lb_pair_state.reserve_x,
+                        reserve_y: lb_pair_state.reserve_y,
+                        token_program: anchor_spl::token::ID,
+                        token_x_mint: lb_pair_state.token_x_mint,
+                        token_y_mint: lb_pair_state.token_y_mint,
+                        user_token_x: get_associated_token_address(
+                            &fee_receiver,
+                            &lb_pair_state.token_x_mint,
+                        ),
+                        user_token_y: get_associated_token_address(
+                            &fee_receiver,
+                            &lb_pair_state.token_y_mint,
+                        ),
+                        event_authority,
+                        program: lb_clmm::ID,
+                    }
+                    .to_account_metas(None),
+                    data: instruction::ClaimFee {}.data(),
+                },
+                compute_units: CLAIM_COMPUTE_UNITS,
+            });
+        }
+
+        let rewards = get_position_pending_rewards(
+            lb_pair_state,
+            position_state,
+            &position_bin_arrays,
+            clock.unix_timestamp as u64,
+        )?;
+        for (reward_index, reward_info) in lb_pair_state.reward_infos.iter().enumerate() {
+            if !reward_info.initialized() || rewards[reward_index] == 0 {
+                continue;
+            }
+            token_accounts.insert((wallet, reward_info.mint));
+
+            let reward_index = reward_index as u64;
+            let (reward_vault, _bump) = derive_reward_vault_pda(lb_pair, reward_index);
+            claim_ixs.push(BudgetedInstruction {
+                instruction: Instruction {
+                    program_id: lb_clmm::ID,
+                    accounts: accounts::ClaimReward {
+                        bin_array_lower,
+                        bin_array_upper,
+                        lb_pair,
+                        reward_vault,
+                        reward_mint: reward_info.mint,
+                        token_program: anchor_spl::token::ID,
+                        position: *position,
+                        user_token_account: get_associated_token_address(
+                            &wallet,
+                            &reward_info.mint,
+                        ),
+                        sender: wallet,
+                        event_authority,
+                        program: lb_clmm::ID,
+                    }
+                    .to_account_metas(None),
+                    data: instruction::ClaimReward { reward_index }.data(),
+                },
+                compute_units: CLAIM_COMPUTE_UNITS,
+            });
+        }
+    }
+
+    if claim_ixs.is_empty() {
+        println!("Nothing to claim");
+        return Ok(());
+    }
+
+    let token_accounts = token_accounts.into_iter().collect::<Vec<_>>();
+    let token_account_keys = token_accounts
+        .iter()
+        .map(|(owner, mint)| get_associated_token_address(owner, mint))
+        .collect::<Vec<_>>();
+    let existing_token_accounts =
+        get_multiple_accounts_chunked(program, &token_account_keys).await?;
+
+    // Token accounts are created ahead of the claims using them
+    let mut instructions = vec![];
+    for ((owner, mint), account) in token_accounts.iter().zip(existing_token_accounts) {
+        if account.is_none() {
+            instructions.push(BudgetedInstruction {
+                instruction: create_associated_token_account(&wallet, owner, mint, &spl_token::ID),
+                compute_units: CREATE_ATA_COMPUTE_UNITS,
+            });
+        }
+    }
+    instructions.extend(claim_ixs);
+
+    let transactions = pack_instructions(
+        instructions,
+        &wallet,
+        transaction_config.lookup_tables,
+        compute_unit_price,
+    )?;
+    println!(
+        "Claim the fees and rewards of {} positions in {} transactions",
+        positions.len(),
+        transactions.len()
+    );
+
+    let mut rows = vec![];
+    for (i, transaction) in transactions.into_iter().enumerate() {
+        let mut request_builder = program.request();
+        for ix in transaction {
+            request_builder = request_builder.instruction(ix);
+        }
+
+        let Some(signature) =
+            send_or_simulate(program, request_builder, transaction_config).await?
+        else {
+            continue;
+        };
+        println!("Claim all {}. Signature: {}", i, signature);
+
+        for ix_data in get_transaction_events(program, &signature).await? {
+            if let Some(event) = parse_event::<events::ClaimFee>(&ix_data) {
+                rows.push(vec![
+                    event.position.to_string(),
+                    "Fee".to_string(),
+                    format!("{} X, {} Y", event.fee_x, event.fee_y),
+                ]);
+            } else if let Some(event) = parse_event::<events::ClaimReward>(&ix_data) {
+                rows.push(vec![
+                    event.position.to_string(),
+                    format!("Reward {}", event.reward_index),
+                    event.total_reward.to_string(),
+                ]);
+            }
+        }
+    }
+
+    if !rows.is_empty() {
+        println!();
+        print_table(&["Position", "Claim", "Amount"], &rows);
+    }
+
+    Ok(())
+}
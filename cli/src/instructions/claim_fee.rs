@@ -1,9 +1,7 @@
-use super::utils::{get_bin_arrays_for_position, get_or_create_ata};
-use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+use super::utils::{compute_budget_instructions, get_bin_arrays_for_position, get_or_create_ata};
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::instruction::Instruction;
-use anchor_client::{
-    solana_client::rpc_config::RpcSendTransactionConfig, solana_sdk::signer::Signer, Program,
-};
+use anchor_client::{solana_sdk::signer::Signer, Program};
 use anchor_lang::prelude::Pubkey;
 use anyhow::*;
 use lb_clmm::accounts;
@@ -16,7 +14,7 @@ use std::ops::Deref;
 pub async fn claim_fee<C: Deref<Target = impl Signer> + Clone>(
     position: Pubkey,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let position_state: PositionV2 = program.account(position).await?;
@@ -82,22 +80,15 @@ pub async fn claim_fee<C: Deref<Target = impl Signer> + Clone>(
     };
 
     let ix = instruction::ClaimFee {};
-    let mut builder = program
-        .request()
-        .accounts(accounts)
-        .args(ix)
-        .instruction(ComputeBudgetInstruction::set_compute_unit_limit(350_000));
-    if let Some(compute_unit_price_ix) = compute_unit_price {
-        builder = builder.instruction(compute_unit_price_ix);
+    let mut builder = program.request();
+    for compute_budget_ix in compute_budget_instructions(350_000, compute_unit_price) {
+        builder = builder.instruction(compute_budget_ix);
     }
+    let builder = builder.accounts(accounts).args(ix);
 
-    let signature = builder
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Claim fee. Signature: {:#?}", signature);
-
-    signature?;
+    if let Some(signature) = send_or_simulate(program, builder, transaction_config).await? {
+        println!("Claim fee. Signature: {}", signature);
+    }
 
     Ok(())
 }
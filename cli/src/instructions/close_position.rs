@@ -1,4 +1,4 @@
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::Program;
@@ -15,7 +15,7 @@ use super::utils::get_bin_arrays_for_position;
 pub async fn close_position<C: Deref<Target = impl Signer> + Clone>(
     position: Pubkey,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<()> {
     let position_state: PositionV2 = program.account(position).await?;
     let [bin_array_lower, bin_array_upper] = get_bin_arrays_for_position(program, position).await?;
@@ -36,17 +36,14 @@ pub async fn close_position<C: Deref<Target = impl Signer> + Clone>(
     let ix = instruction::ClosePosition {};
     let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(1_400_000);
 
-    let request_builder = program.request();
-    let signature = request_builder
+    let request_builder = program
+        .request()
         .instruction(compute_budget_ix)
         .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Close position. Signature: {:#?}", signature);
-
-    signature?;
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Close position. Signature: {}", signature);
+    }
 
     Ok(())
 }
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
 use anchor_lang::ToAccountMetas;
@@ -11,7 +11,7 @@ use lb_clmm::instruction;
 pub async fn close_preset_parameter<C: Deref<Target = impl Signer> + Clone>(
     preset_parameter: Pubkey,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Pubkey> {
     let accounts = accounts::ClosePresetParameter {
         admin: program.payer(),
@@ -22,19 +22,13 @@ pub async fn close_preset_parameter<C: Deref<Target = impl Signer> + Clone>(
 
     let ix = instruction::ClosePresetParameter {};
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!(
-        "Close preset parameter {}. Signature: {signature:#?}",
-        preset_parameter
-    );
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!(
+            "Close preset parameter {}. Signature: {signature}",
+            preset_parameter
+        );
+    }
 
     Ok(preset_parameter)
 }
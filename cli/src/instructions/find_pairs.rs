@@ -0,0 +1,278 @@
+use std::collections::BTreeMap;
+use std::ops::Deref;
+
+use anchor_client::solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::{Mint, TokenAccount};
+use anyhow::*;
+use commons::pair_discovery::{
+    derive_pair_candidates, get_depth_bin_array_pubkeys, get_pair_depth,
+    LB_PAIR_TOKEN_X_MINT_OFFSET, LB_PAIR_TOKEN_Y_MINT_OFFSET,
+};
+use lb_clmm::pair_action_access::ActivationType;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::lb_pair::{LbPair, PairStatus, PairType};
+use lb_clmm::state::preset_parameters::PresetParameter;
+use serde::Serialize;
+
+use crate::instructions::utils::get_multiple_accounts_chunked;
+use crate::math::{fee_rate_to_fee_pct, get_ui_price_from_id, to_ui_amount};
+use crate::output::{print_key_values, print_report, print_table, OutputFormat, Report};
+
+#[derive(Debug)]
+pub struct FindPairsParameters {
+    pub token_a_mint: Pubkey,
+    pub token_b_mint: Pubkey,
+    /// Price range around the active bin price counted in the depth
+    pub depth_bps: u16,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairComparisonReport {
+    pub address: String,
+    /// Permissionless, Permission or CustomizablePermissionless
+    pub pair_type: String,
+    pub token_x_mint: String,
+    pub token_y_mint: String,
+    pub bin_step: u16,
+    pub base_fee_pct: f64,
+    pub current_fee_pct: f64,
+    /// Enabled or Disabled
+    pub status: String,
+    /// Slot or Timestamp
+    pub activation_type: String,
+    pub activation_point: u64,
+    pub active_id: i32,
+    /// UI price of token X in token Y at the active bin
+    pub current_price: f64,
+    /// UI price of token A in token B at the active bin
+    pub price_a_in_b: f64,
+    pub ui_reserve_x: f64,
+    pub ui_reserve_y: f64,
+    /// Liquidity of the bins priced within `depth_bps` of the active bin price
+    pub ui_depth_x: f64,
+    pub ui_depth_y: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PairsReport {
+    pub token_a_mint: String,
+    pub token_b_mint: String,
+    pub depth_bps: u16,
+    /// Ordered by bin step, then base fee
+    pub pairs: Vec<PairComparisonReport>,
+}
+
+impl Report for PairsReport {
+    const COMMAND: &'static str = "find-pairs";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        print_key_values(&[
+            ("Token A", self.token_a_mint.clone()),
+            ("Token B", self.token_b_mint.clone()),
+            ("Pairs", self.pairs.len().to_string()),
+        ]);
+
+        let rows = self
+            .pairs
+            .iter()
+            .map(|pair| {
+                vec![
+                    pair.address.clone(),
+                    pair.pair_type.clone(),
+                    pair.bin_step.to_string(),
+                    format!("{}%", pair.base_fee_pct),
+                    format!("{}%", pair.current_fee_pct),
+                    pair.status.clone(),
+                    format!("{} {}", pair.activation_type, pair.activation_point),
+                    pair.price_a_in_b.to_string(),
+                    format!("{} X, {} Y", pair.ui_reserve_x, pair.ui_reserve_y),
+                    format!("{} X, {} Y", pair.ui_depth_x, pair.ui_depth_y),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        println!();
+        print_table(
+            &[
+                "Pair",
+                "Type",
+                "Bin step",
+                "Base fee",
+                "Current fee",
+                "Status",
+                "Activation",
+                "Price A in B",
+                "Reserves",
+                &format!("Depth ±{} bps", self.depth_bps),
+            ],
+            &rows,
+        );
+    }
+}
+
+fn mints_filter(token_x_mint: Pubkey, token_y_mint: Pubkey) -> Vec<RpcFilterType> {
+    vec![
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            LB_PAIR_TOKEN_X_MINT_OFFSET,
+            &token_x_mint.to_bytes(),
+        )),
+        RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            LB_PAIR_TOKEN_Y_MINT_OFFSET,
+            &token_y_mint.to_bytes(),
+        )),
+    ]
+}
+
+/// Pairs of the two mints, in either token order.
+async fn get_pairs<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    token_a_mint: Pubkey,
+    token_b_mint: Pubkey,
+) -> Result<BTreeMap<Pubkey, LbPair>> {
+    let preset_parameters = program
+        .accounts::<PresetParameter>(vec![])
+        .await?
+        .into_iter()
+        .map(|(_, preset_parameter)| preset_parameter)
+        .collect::<Vec<_>>();
+
+    let candidates = derive_pair_candidates(token_a_mint, token_b_mint, &preset_parameters);
+    let mut pairs = BTreeMap::new();
+    for (key, account) in candidates
+        .iter()
+        .zip(get_multiple_accounts_chunked(program, &candidates).await?)
+    {
+        if let Some(account) = account {
+            pairs.insert(*key, LbPair::try_deserialize(&mut account.data.as_ref())?);
+        }
+    }
+
+    // Permission pairs are derived from their base key
+    for (token_x_mint, token_y_mint) in [(token_a_mint, token_b_mint), (token_b_mint, token_a_mint)]
+    {
+        let found: Vec<(Pubkey, LbPair)> = program
+            .accounts(mints_filter(token_x_mint, token_y_mint))
+            .await?;
+        pairs.extend(found);
+    }
+
+    Ok(pairs)
+}
+
+pub async fn find_pairs<C: Deref<Target = impl Signer> + Clone>(
+    params: FindPairsParameters,
+    program: &Program<C>,
+    output: OutputFormat,
+) -> Result<()> {
+    let FindPairsParameters {
+        token_a_mint,
+        token_b_mint,
+        depth_bps,
+    } = params;
+
+    let pairs = get_pairs(program, token_a_mint, token_b_mint).await?;
+
+    let token_a: Mint = program.account(token_a_mint).await?;
+    let token_b: Mint = program.account(token_b_mint).await?;
+
+    let reserve_keys = pairs
+        .values()
+        .flat_map(|lb_pair| [lb_pair.reserve_x, lb_pair.reserve_y])
+        .collect::<Vec<_>>();
+    let reserves = reserve_keys
+        .iter()
+        .zip(get_multiple_accounts_chunked(program, &reserve_keys).await?)
+        .filter_map(|(key, account)| Some((*key, account?)))
+        .map(|(key, account)| {
+            Ok((
+                key,
+                TokenAccount::try_deserialize(&mut account.data.as_ref())?.amount,
+            ))
+        })
+        .collect::<Result<BTreeMap<_, _>>>()?;
+
+    let mut bin_array_keys = vec![];
+    for (key, lb_pair) in pairs.iter() {
+        bin_array_keys.extend(get_depth_bin_array_pubkeys(*key, lb_pair, depth_bps)?);
+    }
+    let bin_arrays = bin_array_keys
+        .iter()
+        .zip(get_multiple_accounts_chunked(program, &bin_array_keys).await?)
+        .filter_map(|(key, account)| Some((*key, account?)))
+        .map(|(key, account)| Ok((key, BinArray::try_deserialize(&mut account.data.as_ref())?)))
+        .collect::<Result<BTreeMap<_, _>>>()?;
+
+    let mut reports = vec![];
+    for (key, lb_pair) in pairs.iter() {
+        let (x_decimals, y_decimals) = if lb_pair.token_x_mint == token_a_mint {
+            (token_a.decimals, token_b.decimals)
+        } else {
+            (token_b.decimals, token_a.decimals)
+        };
+
+        let pair_bin_arrays = get_depth_bin_array_pubkeys(*key, lb_pair, depth_bps)?
+            .iter()
+            .filter_map(|key| bin_arrays.get(key).copied())
+            .collect::<Vec<_>>();
+        let depth = get_pair_depth(lb_pair, &pair_bin_arrays, depth_bps)?;
+
+        let current_price =
+            get_ui_price_from_id(lb_pair.active_id, lb_pair.bin_step, x_decimals, y_decimals)?;
+        let price_a_in_b = if lb_pair.token_x_mint == token_a_mint {
+            current_price
+        } else {
+            1.0 / current_price
+        };
+
+        reports.push(PairComparisonReport {
+            address: key.to_string(),
+            pair_type: format!("{:?}", PairType::try_from(lb_pair.pair_type)?),
+            token_x_mint: lb_pair.token_x_mint.to_string(),
+            token_y_mint: lb_pair.token_y_mint.to_string(),
+            bin_step: lb_pair.bin_step,
+            base_fee_pct: fee_rate_to_fee_pct(lb_pair.get_base_fee()?)
+                .context("get_base_fee convert to percentage overflow")?,
+            current_fee_pct: fee_rate_to_fee_pct(lb_pair.get_total_fee()?)
+                .context("get_total_fee convert to percentage overflow")?,
+            status: format!("{:?}", PairStatus::try_from(lb_pair.status)?),
+            activation_type: format!("{:?}", ActivationType::try_from(lb_pair.activation_type)?),
+            activation_point: lb_pair.activation_point,
+            active_id: lb_pair.active_id,
+            current_price,
+            price_a_in_b,
+            ui_reserve_x: to_ui_amount(
+                reserves
+                    .get(&lb_pair.reserve_x)
+                    .copied()
+                    .unwrap_or_default(),
+                x_decimals,
+            ),
+            ui_reserve_y: to_ui_amount(
+                reserves
+                    .get(&lb_pair.reserve_y)
+                    .copied()
+                    .unwrap_or_default(),
+                y_decimals,
+            ),
+            ui_depth_x: to_ui_amount(depth.amount_x, x_decimals),
+            ui_depth_y: to_ui_amount(depth.amount_y, y_decimals),
+        });
+    }
+    reports.sort_by(|a, b| {
+        a.bin_step
+            .cmp(&b.bin_step)
+            .then(a.base_fee_pct.total_cmp(&b.base_fee_pct))
+    });
+
+    let report = PairsReport {
+        token_a_mint: token_a_mint.to_string(),
+        token_b_mint: token_b_mint.to_string(),
+        depth_bps,
+        pairs: reports,
+    };
+
+    print_report(output, &report)
+}
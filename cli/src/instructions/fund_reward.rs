@@ -1,5 +1,5 @@
 use crate::instructions::utils::get_or_create_ata;
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anyhow::*;
@@ -20,7 +20,7 @@ pub struct FundRewardParams {
 pub async fn fund_reward<C: Deref<Target = impl Signer> + Clone>(
     params: FundRewardParams,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let FundRewardParams {
@@ -69,16 +69,10 @@ pub async fn fund_reward<C: Deref<Target = impl Signer> + Clone>(
         carry_forward: true,
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Fund reward. Signature: {:#?}", signature);
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Fund reward. Signature: {}", signature);
+    }
 
     Ok(())
 }
@@ -3,21 +3,74 @@ use anchor_client::{solana_sdk::signer::Signer, Program};
 use anchor_lang::prelude::Pubkey;
 use anyhow::*;
 use lb_clmm::state::position::PositionV2;
+use serde::Serialize;
 use std::ops::Deref;
 
+use crate::output::{print_report, print_table, OutputFormat, Report};
+
+#[derive(Debug, Serialize)]
+pub struct OwnerPositionReport {
+    pub position: String,
+    pub fee_owner: String,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+}
+
+#[derive(Debug, Serialize)]
+pub struct OwnerPositionsReport {
+    pub lb_pair: String,
+    pub owner: String,
+    pub positions: Vec<OwnerPositionReport>,
+}
+
+impl Report for OwnerPositionsReport {
+    const COMMAND: &'static str = "get-all-positions-for-an-owner";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        let rows = self
+            .positions
+            .iter()
+            .map(|position| {
+                vec![
+                    position.position.clone(),
+                    position.fee_owner.clone(),
+                    format!("{} - {}", position.lower_bin_id, position.upper_bin_id),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        print_table(&["Position", "Fee owner", "Bin range"], &rows);
+    }
+}
+
 pub async fn get_all_positions<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
     lb_pair: Pubkey,
     owner: Pubkey,
+    output: OutputFormat,
 ) -> Result<()> {
-    let positions: Vec<(Pubkey, PositionV2)> = program
+    let mut positions: Vec<(Pubkey, PositionV2)> = program
         .accounts(vec![
             RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8, &lb_pair.to_bytes())),
             RpcFilterType::Memcmp(Memcmp::new_base58_encoded(8 + 32, &owner.to_bytes())),
         ])
         .await?;
-    for (key, val) in positions {
-        println!("position {} fee owner {}", key, val.fee_owner);
-    }
-    Ok(())
+    positions.sort_by_key(|(_, position)| position.lower_bin_id);
+
+    let report = OwnerPositionsReport {
+        lb_pair: lb_pair.to_string(),
+        owner: owner.to_string(),
+        positions: positions
+            .iter()
+            .map(|(key, position)| OwnerPositionReport {
+                position: key.to_string(),
+                fee_owner: position.fee_owner.to_string(),
+                lower_bin_id: position.lower_bin_id,
+                upper_bin_id: position.upper_bin_id,
+            })
+            .collect(),
+    };
+
+    print_report(output, &report)
 }
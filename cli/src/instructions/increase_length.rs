@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::{
     solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, solana_sdk::system_program, Program,
 };
@@ -19,7 +19,7 @@ pub struct IncreaseLengthParams {
 pub async fn increase_length<C: Deref<Target = impl Signer> + Clone>(
     params: IncreaseLengthParams,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<()> {
     let IncreaseLengthParams {
         lb_pair,
@@ -39,16 +39,10 @@ pub async fn increase_length<C: Deref<Target = impl Signer> + Clone>(
 
     let ix = instruction::IncreaseOracleLength { length_to_add };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Increase oracle {oracle} length. Signature: {signature:#?}");
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Increase oracle {oracle} length. Signature: {signature}");
+    }
 
     Ok(())
 }
@@ -1,7 +1,9 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
-use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::{
+    solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program, RequestBuilder,
+};
 
 use anyhow::*;
 use lb_clmm::accounts;
@@ -14,16 +16,12 @@ pub struct InitBinArrayParameters {
     pub bin_array_index: i64,
 }
 
-pub async fn initialize_bin_array<C: Deref<Target = impl Signer> + Clone>(
-    params: InitBinArrayParameters,
+/// Request initializing the bin array `bin_array_index` of the pair, with the bin array address.
+pub fn initialize_bin_array_request<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
-) -> Result<Pubkey> {
-    let InitBinArrayParameters {
-        lb_pair,
-        bin_array_index,
-    } = params;
-
+    lb_pair: Pubkey,
+    bin_array_index: i64,
+) -> (Pubkey, RequestBuilder<'_, C>) {
     let (bin_array, _bump) = derive_bin_array_pda(lb_pair, bin_array_index);
 
     let accounts = accounts::InitializeBinArray {
@@ -37,16 +35,24 @@ pub async fn initialize_bin_array<C: Deref<Target = impl Signer> + Clone>(
         index: bin_array_index,
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
+    (bin_array, program.request().accounts(accounts).args(ix))
+}
 
-    println!("Initialize Bin Array {bin_array}. Signature: {signature:#?}");
+pub async fn initialize_bin_array<C: Deref<Target = impl Signer> + Clone>(
+    params: InitBinArrayParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+) -> Result<Pubkey> {
+    let InitBinArrayParameters {
+        lb_pair,
+        bin_array_index,
+    } = params;
 
-    signature?;
+    let (bin_array, request_builder) =
+        initialize_bin_array_request(program, lb_pair, bin_array_index);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Initialize Bin Array {bin_array}. Signature: {signature}");
+    }
 
     Ok(bin_array)
 }
@@ -1,7 +1,8 @@
 use std::ops::Deref;
 
-use crate::instructions::initialize_bin_array::*;
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::instructions::initialize_bin_array::initialize_bin_array_request;
+use crate::journal::Journal;
+use crate::transaction::TransactionConfig;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
 use anyhow::*;
@@ -17,7 +18,7 @@ pub struct InitBinArrayWithBinRangeParameters {
 pub async fn initialize_bin_array_with_bin_range<C: Deref<Target = impl Signer> + Clone>(
     params: InitBinArrayWithBinRangeParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Vec<Pubkey>> {
     let InitBinArrayWithBinRangeParameters {
         lb_pair,
@@ -26,17 +27,31 @@ pub async fn initialize_bin_array_with_bin_range<C: Deref<Target = impl Signer>
     } = params;
 
     let mut bin_arrays_pubkey = vec![];
+    let mut journal = Journal::open("initialize-bin-arrays", lb_pair, transaction_config)?;
 
     let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(lower_bin_id)?;
     let upper_bin_array_idx = BinArray::bin_id_to_bin_array_index(upper_bin_id)?;
 
     for idx in lower_bin_array_idx..=upper_bin_array_idx {
-        let params = InitBinArrayParameters {
-            bin_array_index: idx.into(),
-            lb_pair,
-        };
-        let bin_array_pubkey = initialize_bin_array(params, program, transaction_config).await?;
-        bin_arrays_pubkey.push(bin_array_pubkey);
+        let (bin_array, request_builder) =
+            initialize_bin_array_request(program, lb_pair, idx.into());
+        let landed =
+            move || async move { Ok(program.async_rpc().get_account(&bin_array).await.is_ok()) };
+
+        if let Some(signature) = journal
+            .run_step(
+                program,
+                &format!("initialize_bin_array:{}", idx),
+                landed,
+                request_builder,
+                &[],
+                transaction_config,
+            )
+            .await?
+        {
+            println!("Initialize Bin Array {bin_array}. Signature: {signature}");
+        }
+        bin_arrays_pubkey.push(bin_array);
     }
 
     Ok(bin_arrays_pubkey)
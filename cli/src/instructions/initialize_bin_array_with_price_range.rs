@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::TransactionConfig;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
 use anyhow::*;
@@ -24,7 +24,7 @@ pub struct InitBinArrayWithPriceRangeParameters {
 pub async fn initialize_bin_array_with_price_range<C: Deref<Target = impl Signer> + Clone>(
     params: InitBinArrayWithPriceRangeParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Vec<Pubkey>> {
     let InitBinArrayWithPriceRangeParameters {
         lb_pair,
@@ -1,4 +1,4 @@
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anchor_spl::token::Mint;
@@ -35,7 +35,7 @@ pub async fn initialize_customizable_permissionless_lb_pair<
 >(
     params: InitCustomizablePermissionlessLbPairParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<Pubkey> {
     let InitCustomizablePermissionlessLbPairParameters {
@@ -118,16 +118,10 @@ pub async fn initialize_customizable_permissionless_lb_pair<
         },
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Initialize Customizable LB pair {lb_pair}. Signature: {signature:#?}");
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Initialize Customizable LB pair {lb_pair}. Signature: {signature}");
+    }
 
     println!("{lb_pair}");
 
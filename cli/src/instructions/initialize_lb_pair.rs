@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anchor_spl::token::Mint;
 use anyhow::*;
@@ -23,7 +23,7 @@ pub struct InitLbPairParameters {
 pub async fn initialize_lb_pair<C: Deref<Target = impl Signer> + Clone>(
     params: InitLbPairParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Pubkey> {
     let InitLbPairParameters {
         preset_parameter,
@@ -87,17 +87,10 @@ pub async fn initialize_lb_pair<C: Deref<Target = impl Signer> + Clone>(
         bin_step,
     };
 
-    let request_builder = program.request();
-
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Initialize LB pair {lb_pair}. Signature: {signature:#?}");
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Initialize LB pair {lb_pair}. Signature: {signature}");
+    }
 
     Ok(lb_pair)
 }
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate_with_signers, TransactionConfig};
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
@@ -32,7 +32,7 @@ pub struct InitPermissionLbPairParameters {
 pub async fn initialize_permission_lb_pair<C: Deref<Target = impl Signer> + Clone>(
     params: InitPermissionLbPairParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Pubkey> {
     let InitPermissionLbPairParameters {
         bin_step,
@@ -102,17 +102,17 @@ pub async fn initialize_permission_lb_pair<C: Deref<Target = impl Signer> + Clon
         },
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .signer(&base_keypair)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Initialize Permission LB pair {lb_pair}. Signature: {signature:#?}");
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate_with_signers(
+        program,
+        request_builder,
+        &[&base_keypair],
+        transaction_config,
+    )
+    .await?
+    {
+        println!("Initialize Permission LB pair {lb_pair}. Signature: {signature}");
+    }
 
     println!("{lb_pair}");
 
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate_with_signers, TransactionConfig};
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
@@ -24,7 +24,7 @@ pub struct InitPositionParameters {
 pub async fn initialize_position<C: Deref<Target = impl Signer> + Clone>(
     params: InitPositionParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Pubkey> {
     let InitPositionParameters {
         lb_pair,
@@ -62,20 +62,20 @@ pub async fn initialize_position<C: Deref<Target = impl Signer> + Clone>(
         width,
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .signer(&position_keypair)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!(
-        "Initialize position {}. Signature: {signature:#?}",
-        position_keypair.pubkey()
-    );
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate_with_signers(
+        program,
+        request_builder,
+        &[&position_keypair],
+        transaction_config,
+    )
+    .await?
+    {
+        println!(
+            "Initialize position {}. Signature: {signature}",
+            position_keypair.pubkey()
+        );
+    }
 
     Ok(position_keypair.pubkey())
 }
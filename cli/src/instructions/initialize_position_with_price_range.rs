@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::TransactionConfig;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
 use anyhow::*;
@@ -23,7 +23,7 @@ pub struct InitPositionWithPriceRangeParameters {
 pub async fn initialize_position_with_price_range<C: Deref<Target = impl Signer> + Clone>(
     params: InitPositionWithPriceRangeParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Pubkey> {
     let InitPositionWithPriceRangeParameters {
         lb_pair,
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
 use anchor_lang::ToAccountMetas;
@@ -37,7 +37,7 @@ pub struct InitPresetParameters {
 pub async fn initialize_preset_parameter<C: Deref<Target = impl Signer> + Clone>(
     params: InitPresetParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<Pubkey> {
     let InitPresetParameters {
         base_factor,
@@ -77,19 +77,13 @@ pub async fn initialize_preset_parameter<C: Deref<Target = impl Signer> + Clone>
         },
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!(
-        "Initialize preset parameter {}. Signature: {signature:#?}",
-        preset_parameter
-    );
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!(
+            "Initialize preset parameter {}. Signature: {signature}",
+            preset_parameter
+        );
+    }
 
     Ok(preset_parameter)
 }
@@ -1,4 +1,4 @@
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use lb_clmm::utils::pda::derive_event_authority_pda;
 use std::ops::Deref;
@@ -19,7 +19,7 @@ pub struct InitializeRewardParams {
 pub async fn initialize_reward<C: Deref<Target = impl Signer> + Clone>(
     params: InitializeRewardParams,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<()> {
     let InitializeRewardParams {
         lb_pair,
@@ -54,16 +54,10 @@ pub async fn initialize_reward<C: Deref<Target = impl Signer> + Clone>(
         funder,
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Initialize reward. Signature: {signature:#?}");
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Initialize reward. Signature: {signature}");
+    }
 
     Ok(())
 }
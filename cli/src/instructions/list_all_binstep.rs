@@ -1,23 +1,70 @@
 use anchor_client::{solana_sdk::signer::Signer, Program};
 use lb_clmm::constants::FEE_PRECISION;
 use lb_clmm::state::preset_parameters::PresetParameter;
+use serde::Serialize;
 use std::ops::Deref;
 
 use anyhow::*;
 
+use crate::output::{print_report, print_table, OutputFormat, Report};
+
+#[derive(Debug, Serialize)]
+pub struct BinStepReport {
+    pub preset_parameter: String,
+    pub bin_step: u16,
+    pub base_factor: u16,
+    pub base_fee_pct: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct BinStepsReport {
+    pub preset_parameters: Vec<BinStepReport>,
+}
+
+impl Report for BinStepsReport {
+    const COMMAND: &'static str = "list-all-bin-step";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        let rows = self
+            .preset_parameters
+            .iter()
+            .map(|preset| {
+                vec![
+                    preset.preset_parameter.clone(),
+                    preset.bin_step.to_string(),
+                    format!("{}%", preset.base_fee_pct),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        print_table(&["Preset parameter", "Bin step", "Base fee"], &rows);
+    }
+}
+
+/// Base fee of the preset parameter in percentage.
+pub fn get_base_fee_pct(param: &PresetParameter) -> f64 {
+    (param.bin_step as u128 * param.base_factor as u128 * 1000) as f64 / FEE_PRECISION as f64
+}
+
 pub async fn list_all_binstep<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
+    output: OutputFormat,
 ) -> Result<()> {
-    let preset_parameters = program.accounts::<PresetParameter>(vec![]).await?;
-
-    for (key, param) in preset_parameters {
-        let base_fee = (param.bin_step as u128 * param.base_factor as u128 * 1000) as f64
-            / FEE_PRECISION as f64;
-        println!(
-            "Preset Pubkey: {}. Bin step {}. Base fee: {}%",
-            key, param.bin_step, base_fee
-        );
-    }
+    let mut preset_parameters = program.accounts::<PresetParameter>(vec![]).await?;
+    preset_parameters.sort_by_key(|(_, param)| (param.bin_step, param.base_factor));
+
+    let report = BinStepsReport {
+        preset_parameters: preset_parameters
+            .iter()
+            .map(|(key, param)| BinStepReport {
+                preset_parameter: key.to_string(),
+                bin_step: param.bin_step,
+                base_factor: param.base_factor,
+                base_fee_pct: get_base_fee_pct(param),
+            })
+            .collect(),
+    };
 
-    Ok(())
+    print_report(output, &report)
 }
@@ -0,0 +1,134 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{pack_instructions, BudgetedInstruction};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use anchor_client::solana_sdk::instruction::{AccountMeta, Instruction};
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use anyhow::*;
+use lb_clmm::accounts;
+use lb_clmm::instruction;
+use lb_clmm::state::bin::{BinArray, LayoutVersion};
+
+/// Compute units budgeted for a migrate_bin_array instruction of one bin array
+const MIGRATE_BIN_ARRAY_COMPUTE_UNITS: u32 = 50_000;
+/// Offset of `BinArray::version`, after the discriminator and `index`
+const BIN_ARRAY_VERSION_OFFSET: usize = 8 + 8;
+/// Offset of `BinArray::lb_pair`, after `version` and `_padding`
+const BIN_ARRAY_LB_PAIR_OFFSET: usize = BIN_ARRAY_VERSION_OFFSET + 1 + 7;
+
+#[derive(Debug)]
+pub struct MigrateBinArrayParameters {
+    pub lb_pair: Pubkey,
+    /// Bin arrays to migrate. Every bin array of the pair with the legacy layout when empty.
+    pub bin_arrays: Vec<Pubkey>,
+}
+
+/// Bin arrays of the pair still on the `LayoutVersion::V0` layout, by index.
+async fn get_legacy_bin_arrays<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    lb_pair: Pubkey,
+) -> Result<Vec<(Pubkey, BinArray)>> {
+    let mut bin_arrays: Vec<(Pubkey, BinArray)> = program
+        .accounts(vec![
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                BIN_ARRAY_LB_PAIR_OFFSET,
+                &lb_pair.to_bytes(),
+            )),
+            RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+                BIN_ARRAY_VERSION_OFFSET,
+                &[u8::from(LayoutVersion::V0)],
+            )),
+        ])
+        .await?;
+    bin_arrays.sort_by_key(|(_, bin_array)| bin_array.index);
+    Ok(bin_arrays)
+}
+
+/// Upgrade bin arrays of a pair to the current `LayoutVersion`, in as few transactions as fit. Bin arrays already
+/// upgraded are left as is by the program.
+pub async fn migrate_bin_array<C: Deref<Target = impl Signer> + Clone>(
+    params: MigrateBinArrayParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let MigrateBinArrayParameters {
+        lb_pair,
+        bin_arrays,
+    } = params;
+
+    let bin_arrays = if bin_arrays.is_empty() {
+        get_legacy_bin_arrays(program, lb_pair)
+            .await?
+            .into_iter()
+            .map(|(key, _)| key)
+            .collect()
+    } else {
+        bin_arrays
+    };
+
+    if bin_arrays.is_empty() {
+        println!("No legacy bin array in pair {}", lb_pair);
+        return Ok(());
+    }
+
+    let mut instructions = vec![];
+    for bin_array in bin_arrays.iter() {
+        // The bin arrays to migrate are the remaining accounts
+        let mut accounts = accounts::MigrateBinArray { lb_pair }.to_account_metas(None);
+        accounts.push(AccountMeta::new(*bin_array, false));
+
+        instructions.push(BudgetedInstruction {
+            instruction: Instruction {
+                program_id: lb_clmm::ID,
+                accounts,
+                data: instruction::MigrateBinArray {}.data(),
+            },
+            compute_units: MIGRATE_BIN_ARRAY_COMPUTE_UNITS,
+        });
+    }
+
+    let transactions = pack_instructions(
+        instructions,
+        &program.payer(),
+        transaction_config.lookup_tables,
+        compute_unit_price,
+    )?;
+    println!(
+        "Migrate {} bin arrays in {} transactions",
+        bin_arrays.len(),
+        transactions.len()
+    );
+
+    let mut migrated = 0;
+    for (i, transaction) in transactions.into_iter().enumerate() {
+        // Leave out the compute budget instructions
+        let migrated_in_transaction = transaction
+            .iter()
+            .filter(|ix| ix.program_id == lb_clmm::ID)
+            .count();
+
+        let request_builder = transaction
+            .into_iter()
+            .fold(program.request(), |bld, ix| bld.instruction(ix));
+        let Some(signature) =
+            send_or_simulate(program, request_builder, transaction_config).await?
+        else {
+            continue;
+        };
+
+        migrated += migrated_in_transaction;
+        println!(
+            "Migrate bin arrays {}. Migrated {}/{}. Signature: {}",
+            i,
+            migrated,
+            bin_arrays.len(),
+            signature
+        );
+    }
+
+    Ok(())
+}
@@ -0,0 +1,168 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{
+    derive_position_bin_arrays, get_owned_positions, migrate_legacy_position, pack_instructions,
+    BudgetedInstruction,
+};
+use crate::output::print_table;
+use crate::transaction::{send_or_simulate_with_signers, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::{InstructionData, ToAccountMetas};
+
+use anyhow::*;
+use lb_clmm::accounts;
+use lb_clmm::instruction;
+use lb_clmm::state::position::{Position, PositionV2};
+use lb_clmm::utils::pda::derive_event_authority_pda;
+
+/// Compute units budgeted for a migrate_position instruction
+const MIGRATE_POSITION_COMPUTE_UNITS: u32 = 150_000;
+
+#[derive(Debug)]
+pub struct MigratePositionParameters {
+    /// Legacy position to migrate. Every legacy position of the wallet when `None`.
+    pub position: Option<Pubkey>,
+}
+
+fn migrate_position_ix(
+    owner: Pubkey,
+    position_v1: Pubkey,
+    position_state: &PositionV2,
+    position_v2: Pubkey,
+) -> Result<Instruction> {
+    let [bin_array_lower, bin_array_upper] =
+        derive_position_bin_arrays(position_state.lb_pair, position_state.lower_bin_id)?;
+    let (event_authority, _bump) = derive_event_authority_pda();
+
+    Ok(Instruction {
+        program_id: lb_clmm::ID,
+        accounts: accounts::MigratePosition {
+            position_v2,
+            position_v1,
+            lb_pair: position_state.lb_pair,
+            bin_array_lower,
+            bin_array_upper,
+            owner,
+            system_program: anchor_client::solana_sdk::system_program::ID,
+            rent_receiver: owner,
+            event_authority,
+            program: lb_clmm::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::MigratePosition {}.data(),
+    })
+}
+
+/// Migrate legacy positions of the wallet to new `PositionV2` accounts, in as few transactions as fit. The legacy
+/// accounts are closed, and their rent returned to the wallet.
+pub async fn migrate_position<C: Deref<Target = impl Signer> + Clone>(
+    params: MigratePositionParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let MigratePositionParameters { position } = params;
+    let owner = program.payer();
+
+    let legacy_positions = match position {
+        Some(position) => {
+            let position_state: Position = program.account(position).await?;
+            ensure!(
+                position_state.owner == owner,
+                "Position {} is owned by {}",
+                position,
+                position_state.owner
+            );
+            vec![(position, migrate_legacy_position(position_state)?)]
+        }
+        None => get_owned_positions(program, owner)
+            .await?
+            .into_iter()
+            .filter(|position| position.version == 1)
+            .map(|position| (position.key, position.state))
+            .collect(),
+    };
+
+    if legacy_positions.is_empty() {
+        println!("No legacy position owned by {}", owner);
+        return Ok(());
+    }
+
+    let mut new_positions = vec![];
+    let mut instructions = vec![];
+    for (position_v1, position_state) in legacy_positions.iter() {
+        let position_v2 = Keypair::new();
+        instructions.push(BudgetedInstruction {
+            instruction: migrate_position_ix(
+                owner,
+                *position_v1,
+                position_state,
+                position_v2.pubkey(),
+            )?,
+            compute_units: MIGRATE_POSITION_COMPUTE_UNITS,
+        });
+        new_positions.push(position_v2);
+    }
+
+    let transactions = pack_instructions(
+        instructions,
+        &owner,
+        transaction_config.lookup_tables,
+        compute_unit_price,
+    )?;
+    println!(
+        "Migrate {} legacy positions in {} transactions",
+        legacy_positions.len(),
+        transactions.len()
+    );
+
+    let mut rows = vec![];
+    let mut migrated = 0;
+    for (i, transaction) in transactions.into_iter().enumerate() {
+        // The new positions are signers of the migrate_position instructions of the transaction
+        let mut signers: Vec<&dyn Signer> = vec![];
+        let mut migrated_in_transaction = vec![];
+        for (position_v2, (position_v1, _)) in new_positions.iter().zip(legacy_positions.iter()) {
+            let is_signer = transaction.iter().any(|ix| {
+                ix.accounts
+                    .iter()
+                    .any(|meta| meta.is_signer && meta.pubkey == position_v2.pubkey())
+            });
+            if is_signer {
+                signers.push(position_v2);
+                migrated_in_transaction.push((*position_v1, position_v2.pubkey()));
+            }
+        }
+
+        let request_builder = transaction
+            .into_iter()
+            .fold(program.request(), |bld, ix| bld.instruction(ix));
+        let Some(signature) =
+            send_or_simulate_with_signers(program, request_builder, &signers, transaction_config)
+                .await?
+        else {
+            continue;
+        };
+
+        migrated += migrated_in_transaction.len();
+        println!(
+            "Migrate positions {}. Migrated {}/{}. Signature: {}",
+            i,
+            migrated,
+            legacy_positions.len(),
+            signature
+        );
+        for (position_v1, position_v2) in migrated_in_transaction {
+            rows.push(vec![position_v1.to_string(), position_v2.to_string()]);
+        }
+    }
+
+    if !rows.is_empty() {
+        println!();
+        print_table(&["Legacy position", "Position"], &rows);
+    }
+
+    Ok(())
+}
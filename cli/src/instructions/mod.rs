@@ -1,9 +1,16 @@
 pub mod add_liquidity;
+pub mod add_liquidity_by_strategy;
+pub mod add_liquidity_by_strategy_one_side;
+pub mod add_liquidity_by_weight;
+pub mod add_liquidity_one_side;
+pub mod add_liquidity_one_side_precise;
 pub mod check_my_balance;
+pub mod claim_all;
 pub mod claim_fee;
 pub mod claim_reward;
 pub mod close_position;
 pub mod close_preset_parameter;
+pub mod find_pairs;
 pub mod fund_reward;
 pub mod get_all_positions;
 pub mod increase_length;
@@ -18,22 +25,37 @@ pub mod initialize_position_with_price_range;
 pub mod initialize_preset_parameter;
 pub mod initialize_reward;
 pub mod list_all_binstep;
+pub mod migrate_bin_array;
+pub mod migrate_position;
+pub mod portfolio;
+pub mod quote;
+pub mod rebalance_position;
+pub mod remove_all_liquidity;
 pub mod remove_liquidity;
 pub mod remove_liquidity_by_price_range;
 pub mod seed_liquidity;
 pub mod seed_liquidity_from_operator;
 pub mod seed_liquidity_single_bin;
 pub mod seed_liquidity_single_bin_by_operator;
+pub mod seed_plan;
 pub mod set_activation_point;
 pub mod set_pre_activation_duration;
 pub mod set_pre_activation_swap_address;
+pub mod setup_pair_lookup_table;
 pub mod show_pair;
+pub mod show_position;
+pub mod show_preset_parameter;
 pub mod simulate_swap_demand;
+pub mod submit;
 pub mod swap_exact_in;
 pub mod swap_exact_out;
 pub mod swap_with_price_impact;
 pub mod toggle_pair_status;
+pub mod update_fee_parameters;
+pub mod update_fees_and_rewards;
+pub mod update_position_operator;
 pub mod update_reward_duration;
 pub mod update_reward_funder;
 pub mod utils;
+pub mod withdraw_ineligible_reward;
 pub mod withdraw_protocol_fee;
@@ -0,0 +1,354 @@
+use std::collections::{BTreeMap, HashMap};
+use std::ops::Deref;
+
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::AccountDeserialize;
+use anchor_spl::token::Mint;
+use anyhow::*;
+use commons::position::{
+    deserialize_bin_array, get_position_amounts, get_position_pending_fees,
+    get_position_pending_rewards,
+};
+use lb_clmm::state::lb_pair::LbPair;
+use serde::Serialize;
+
+use crate::instructions::utils::{
+    derive_position_bin_arrays, get_clock, get_multiple_accounts_chunked, get_owned_positions,
+    OwnedPosition,
+};
+use crate::math::{get_ui_price_from_id, to_ui_amount};
+use crate::output::{
+    format_optional, print_key_values, print_report, print_table, OutputFormat, Report,
+};
+
+#[derive(Debug)]
+pub struct PortfolioParameters {
+    pub owner: Pubkey,
+    /// UI price of a token Y mint in the reference currency
+    pub quote_prices: Vec<(Pubkey, f64)>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioRewardReport {
+    pub mint: String,
+    pub ui_amount: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioPositionReport {
+    pub position: String,
+    /// 1 for the legacy `Position` account, 2 for `PositionV2`
+    pub version: u8,
+    /// owner or operator
+    pub role: String,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub in_range: bool,
+    pub ui_amount_x: f64,
+    pub ui_amount_y: f64,
+    /// Value of the liquidity in token Y at the active bin price
+    pub value_in_y: f64,
+    pub ui_fee_x_pending: f64,
+    pub ui_fee_y_pending: f64,
+    /// Value of the pending fees in token Y at the active bin price
+    pub fee_value_in_y: f64,
+    pub pending_rewards: Vec<PortfolioRewardReport>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioPairReport {
+    pub lb_pair: String,
+    pub token_x_mint: String,
+    pub token_y_mint: String,
+    /// UI price of token X in token Y of the active bin
+    pub price: f64,
+    pub positions: Vec<PortfolioPositionReport>,
+    pub value_in_y: f64,
+    pub fee_value_in_y: f64,
+    /// Liquidity and pending fee value in the reference currency. None when token Y has no reference price.
+    pub reference_value: Option<f64>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PortfolioReport {
+    pub owner: String,
+    pub position_count: usize,
+    pub pairs: Vec<PortfolioPairReport>,
+    /// Sum of the pair reference values. Pairs without a reference price are left out.
+    pub total_reference_value: Option<f64>,
+    /// Pending rewards of all the positions, by reward mint
+    pub pending_rewards: Vec<PortfolioRewardReport>,
+}
+
+impl Report for PortfolioReport {
+    const COMMAND: &'static str = "portfolio";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        print_key_values(&[
+            ("Owner", self.owner.clone()),
+            ("Positions", self.position_count.to_string()),
+            ("Pairs", self.pairs.len().to_string()),
+            (
+                "Total reference value",
+                format_optional(self.total_reference_value),
+            ),
+        ]);
+
+        for pair in self.pairs.iter() {
+            println!();
+            print_key_values(&[
+                ("Pair", pair.lb_pair.clone()),
+                ("Token X", pair.token_x_mint.clone()),
+                ("Token Y", pair.token_y_mint.clone()),
+                ("Price", pair.price.to_string()),
+                ("Value in Y", pair.value_in_y.to_string()),
+                ("Pending fee value in Y", pair.fee_value_in_y.to_string()),
+                ("Reference value", format_optional(pair.reference_value)),
+            ]);
+
+            let rows = pair
+                .positions
+                .iter()
+                .map(|position| {
+                    vec![
+                        position.position.clone(),
+                        format!("v{}", position.version),
+                        position.role.clone(),
+                        format!("{} - {}", position.lower_bin_id, position.upper_bin_id),
+                        position.in_range.to_string(),
+                        position.ui_amount_x.to_string(),
+                        position.ui_amount_y.to_string(),
+                        position.value_in_y.to_string(),
+                        format!(
+                            "{} X, {} Y",
+                            position.ui_fee_x_pending, position.ui_fee_y_pending
+                        ),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            println!();
+            print_table(
+                &[
+                    "Position",
+                    "Version",
+                    "Role",
+                    "Bin range",
+                    "In range",
+                    "Amount X",
+                    "Amount Y",
+                    "Value in Y",
+                    "Pending fee",
+                ],
+                &rows,
+            );
+        }
+
+        if !self.pending_rewards.is_empty() {
+            let rows = self
+                .pending_rewards
+                .iter()
+                .map(|reward| vec![reward.mint.clone(), reward.ui_amount.to_string()])
+                .collect::<Vec<_>>();
+            println!();
+            print_table(&["Reward mint", "Pending"], &rows);
+        }
+    }
+}
+
+pub async fn portfolio<C: Deref<Target = impl Signer> + Clone>(
+    params: PortfolioParameters,
+    program: &Program<C>,
+    output: OutputFormat,
+) -> Result<()> {
+    let PortfolioParameters {
+        owner,
+        quote_prices,
+    } = params;
+    let quote_prices = quote_prices.into_iter().collect::<HashMap<_, _>>();
+
+    let positions = get_owned_positions(program, owner).await?;
+
+    let mut positions_by_pair: BTreeMap<Pubkey, Vec<OwnedPosition>> = BTreeMap::new();
+    for position in positions {
+        positions_by_pair
+            .entry(position.state.lb_pair)
+            .or_default()
+            .push(position);
+    }
+
+    let pair_keys = positions_by_pair.keys().copied().collect::<Vec<_>>();
+    let lb_pairs = get_multiple_accounts_chunked(program, &pair_keys)
+        .await?
+        .into_iter()
+        .zip(pair_keys.iter())
+        .map(|(account, key)| {
+            let account = account.with_context(|| format!("Pair {} not found", key))?;
+            Ok((*key, LbPair::try_deserialize(&mut account.data.as_ref())?))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let mut mint_keys = lb_pairs
+        .values()
+        .flat_map(|lb_pair| {
+            let reward_mints = lb_pair
+                .reward_infos
+                .iter()
+                .filter(|reward_info| reward_info.initialized())
+                .map(|reward_info| reward_info.mint);
+            [lb_pair.token_x_mint, lb_pair.token_y_mint]
+                .into_iter()
+                .chain(reward_mints)
+        })
+        .collect::<Vec<_>>();
+    mint_keys.sort();
+    mint_keys.dedup();
+    let mint_decimals = get_multiple_accounts_chunked(program, &mint_keys)
+        .await?
+        .into_iter()
+        .zip(mint_keys.iter())
+        .map(|(account, key)| {
+            let account = account.with_context(|| format!("Mint {} not found", key))?;
+            Ok((
+                *key,
+                Mint::try_deserialize(&mut account.data.as_ref())?.decimals,
+            ))
+        })
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let mut bin_array_keys = vec![];
+    for position in positions_by_pair.values().flatten() {
+        bin_array_keys.extend(derive_position_bin_arrays(
+            position.state.lb_pair,
+            position.state.lower_bin_id,
+        )?);
+    }
+    bin_array_keys.sort();
+    bin_array_keys.dedup();
+    let bin_arrays = get_multiple_accounts_chunked(program, &bin_array_keys)
+        .await?
+        .into_iter()
+        .zip(bin_array_keys.iter())
+        .filter_map(|(account, key)| Some((*key, account?)))
+        .map(|(key, account)| Ok((key, deserialize_bin_array(&account.data)?)))
+        .collect::<Result<HashMap<_, _>>>()?;
+
+    let clock = get_clock(program).await?;
+
+    let mut position_count = 0;
+    let mut pairs = vec![];
+    let mut total_pending_rewards: BTreeMap<Pubkey, f64> = BTreeMap::new();
+
+    for (lb_pair, positions) in positions_by_pair.iter() {
+        let lb_pair_state = &lb_pairs[lb_pair];
+        let x_decimals = mint_decimals[&lb_pair_state.token_x_mint];
+        let y_decimals = mint_decimals[&lb_pair_state.token_y_mint];
+        let price = get_ui_price_from_id(
+            lb_pair_state.active_id,
+            lb_pair_state.bin_step,
+            x_decimals,
+            y_decimals,
+        )?;
+
+        let mut position_reports = vec![];
+        for position in positions.iter() {
+            let position_bin_arrays =
+                derive_position_bin_arrays(*lb_pair, position.state.lower_bin_id)?
+                    .iter()
+                    .filter_map(|key| bin_arrays.get(key).copied())
+                    .collect::<Vec<_>>();
+
+            let (amount_x, amount_y) = get_position_amounts(&position.state, &position_bin_arrays)?;
+            let (fee_x, fee_y) = get_position_pending_fees(&position.state, &position_bin_arrays)?;
+            let rewards = get_position_pending_rewards(
+                lb_pair_state,
+                &position.state,
+                &position_bin_arrays,
+                clock.unix_timestamp as u64,
+            )?;
+
+            let mut pending_rewards = vec![];
+            for (reward_info, &amount) in lb_pair_state.reward_infos.iter().zip(rewards.iter()) {
+                if !reward_info.initialized() {
+                    continue;
+                }
+                let ui_amount = to_ui_amount(amount, mint_decimals[&reward_info.mint]);
+                *total_pending_rewards.entry(reward_info.mint).or_default() += ui_amount;
+                pending_rewards.push(PortfolioRewardReport {
+                    mint: reward_info.mint.to_string(),
+                    ui_amount,
+                });
+            }
+
+            let ui_amount_x = to_ui_amount(amount_x, x_decimals);
+            let ui_amount_y = to_ui_amount(amount_y, y_decimals);
+            let ui_fee_x_pending = to_ui_amount(fee_x, x_decimals);
+            let ui_fee_y_pending = to_ui_amount(fee_y, y_decimals);
+
+            position_reports.push(PortfolioPositionReport {
+                position: position.key.to_string(),
+                version: position.version,
+                role: position.role.to_string(),
+                lower_bin_id: position.state.lower_bin_id,
+                upper_bin_id: position.state.upper_bin_id,
+                in_range: position
+                    .state
+                    .id_within_position(lb_pair_state.active_id)
+                    .is_ok(),
+                ui_amount_x,
+                ui_amount_y,
+                value_in_y: ui_amount_x * price + ui_amount_y,
+                ui_fee_x_pending,
+                ui_fee_y_pending,
+                fee_value_in_y: ui_fee_x_pending * price + ui_fee_y_pending,
+                pending_rewards,
+            });
+        }
+        position_reports.sort_by_key(|position| position.lower_bin_id);
+        position_count += position_reports.len();
+
+        let value_in_y = position_reports
+            .iter()
+            .map(|position| position.value_in_y)
+            .sum::<f64>();
+        let fee_value_in_y = position_reports
+            .iter()
+            .map(|position| position.fee_value_in_y)
+            .sum::<f64>();
+
+        pairs.push(PortfolioPairReport {
+            lb_pair: lb_pair.to_string(),
+            token_x_mint: lb_pair_state.token_x_mint.to_string(),
+            token_y_mint: lb_pair_state.token_y_mint.to_string(),
+            price,
+            positions: position_reports,
+            value_in_y,
+            fee_value_in_y,
+            reference_value: quote_prices
+                .get(&lb_pair_state.token_y_mint)
+                .map(|quote_price| (value_in_y + fee_value_in_y) * quote_price),
+        });
+    }
+
+    let reference_values = pairs
+        .iter()
+        .filter_map(|pair| pair.reference_value)
+        .collect::<Vec<_>>();
+
+    let report = PortfolioReport {
+        owner: owner.to_string(),
+        position_count,
+        pairs,
+        total_reference_value: (!reference_values.is_empty())
+            .then(|| reference_values.iter().sum()),
+        pending_rewards: total_pending_rewards
+            .into_iter()
+            .map(|(mint, ui_amount)| PortfolioRewardReport {
+                mint: mint.to_string(),
+                ui_amount,
+            })
+            .collect(),
+    };
+
+    print_report(output, &report)
+}
@@ -0,0 +1,285 @@
+use std::collections::HashMap;
+use std::fs::File;
+use std::io::BufReader;
+use std::ops::Deref;
+
+use anchor_client::solana_account_decoder::UiAccount;
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_client::solana_sdk::sysvar::SysvarId;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::AccountDeserialize;
+use anyhow::*;
+use commons::quote::{
+    get_bin_array_pubkeys_for_swap, get_price_impact_pct, quote_exact_in, quote_exact_out,
+};
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::bin_array_bitmap_extension::BinArrayBitmapExtension;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::utils::pda::derive_bin_array_bitmap_extension;
+use serde::{Deserialize, Serialize};
+
+use crate::output::{format_optional, print_key_values, print_report, OutputFormat, Report};
+
+/// Number of bin arrays with liquidity loaded for a quote. Swaps crossing more bin arrays can't be quoted.
+const QUOTE_BIN_ARRAY_COUNT: u8 = 10;
+
+#[derive(Debug, Clone, Copy)]
+pub enum QuoteMode {
+    ExactIn {
+        amount_in: u64,
+    },
+    ExactOut {
+        amount_out: u64,
+    },
+    /// Exact in swap checked against the max price impact of `swap-with-price-impact`
+    PriceImpact {
+        amount_in: u64,
+        max_price_impact_bps: u16,
+    },
+}
+
+#[derive(Debug)]
+pub struct QuoteParameters {
+    pub lb_pair: Pubkey,
+    pub swap_for_y: bool,
+    pub mode: QuoteMode,
+    /// Account files written by `solana account <ADDRESS> --output json`. The quote uses them
+    /// instead of the cluster state for the accounts they contain.
+    pub account_files: Vec<String>,
+}
+
+#[derive(Debug, Serialize)]
+pub struct QuoteReport {
+    pub lb_pair: String,
+    /// exact-in, exact-out or price-impact
+    pub mode: String,
+    pub swap_for_y: bool,
+    pub active_id: i32,
+    /// Amount in, fee included
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+    /// Execution price, fee excluded, against the active bin price
+    pub price_impact_pct: f64,
+    /// Bin arrays crossed by the swap, in swap order
+    pub bin_arrays: Vec<String>,
+    pub max_price_impact_bps: Option<u16>,
+    /// Whether `swap-with-price-impact` would accept the price impact
+    pub within_max_price_impact: Option<bool>,
+    pub slot: u64,
+}
+
+impl Report for QuoteReport {
+    const COMMAND: &'static str = "quote";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        print_key_values(&[
+            ("Pair", self.lb_pair.clone()),
+            ("Mode", self.mode.clone()),
+            ("Swap for Y", self.swap_for_y.to_string()),
+            ("Active bin", self.active_id.to_string()),
+            ("Amount in", self.amount_in.to_string()),
+            ("Amount out", self.amount_out.to_string()),
+            ("Fee", self.fee.to_string()),
+            ("Price impact", format!("{:.4}%", self.price_impact_pct)),
+            (
+                "Max price impact",
+                format_optional(self.max_price_impact_bps.map(|bps| format!("{} bps", bps))),
+            ),
+            (
+                "Within max price impact",
+                format_optional(self.within_max_price_impact),
+            ),
+            ("Slot", self.slot.to_string()),
+        ]);
+
+        println!("\nBin arrays:");
+        for bin_array in self.bin_arrays.iter() {
+            println!("  {bin_array}");
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct AccountFile {
+    pubkey: String,
+    account: UiAccount,
+}
+
+fn read_account_files(paths: &[String]) -> Result<HashMap<Pubkey, Account>> {
+    let mut accounts = HashMap::new();
+    for path in paths {
+        let file = File::open(path).with_context(|| format!("Failed to open {}", path))?;
+        let account_file: AccountFile = serde_json::from_reader(BufReader::new(file))
+            .with_context(|| format!("{} is not a solana account json file", path))?;
+        let pubkey = account_file.pubkey.parse::<Pubkey>()?;
+        let account = account_file
+            .account
+            .decode::<Account>()
+            .with_context(|| format!("Failed to decode the account data of {}", path))?;
+        accounts.insert(pubkey, account);
+    }
+    Ok(accounts)
+}
+
+/// Accounts of the account files, then the cluster for the ones missing.
+async fn get_accounts<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    snapshot: &HashMap<Pubkey, Account>,
+    keys: &[Pubkey],
+) -> Result<Vec<Option<Account>>> {
+    let missing_keys = keys
+        .iter()
+        .filter(|key| !snapshot.contains_key(key))
+        .copied()
+        .collect::<Vec<_>>();
+
+    let mut fetched = HashMap::new();
+    if !missing_keys.is_empty() {
+        let accounts = program
+            .async_rpc()
+            .get_multiple_accounts(&missing_keys)
+            .await?;
+        fetched.extend(missing_keys.into_iter().zip(accounts));
+    }
+
+    Ok(keys
+        .iter()
+        .map(|key| {
+            snapshot
+                .get(key)
+                .cloned()
+                .or_else(|| fetched.get(key).cloned().flatten())
+        })
+        .collect())
+}
+
+pub async fn quote<C: Deref<Target = impl Signer> + Clone>(
+    params: QuoteParameters,
+    program: &Program<C>,
+    output: OutputFormat,
+) -> Result<()> {
+    let QuoteParameters {
+        lb_pair,
+        swap_for_y,
+        mode,
+        account_files,
+    } = params;
+
+    let snapshot = read_account_files(&account_files)?;
+
+    let (bitmap_extension_key, _bump) = derive_bin_array_bitmap_extension(lb_pair);
+    let mut accounts = get_accounts(
+        program,
+        &snapshot,
+        &[lb_pair, bitmap_extension_key, Clock::id()],
+    )
+    .await?;
+
+    let lb_pair_account = accounts[0].take().context("Pair not found")?;
+    let lb_pair_state = LbPair::try_deserialize(&mut lb_pair_account.data.as_ref())?;
+    let bitmap_extension = accounts[1].take().and_then(|account| {
+        BinArrayBitmapExtension::try_deserialize(&mut account.data.as_ref()).ok()
+    });
+    let clock_account = accounts[2].take().context("Clock sysvar not found")?;
+    let clock: Clock = bincode::deserialize(clock_account.data.as_ref())?;
+
+    let bin_array_keys = get_bin_array_pubkeys_for_swap(
+        lb_pair,
+        &lb_pair_state,
+        bitmap_extension.as_ref(),
+        swap_for_y,
+        QUOTE_BIN_ARRAY_COUNT,
+    )?;
+    let bin_arrays = get_accounts(program, &snapshot, &bin_array_keys)
+        .await?
+        .into_iter()
+        .zip(bin_array_keys.iter())
+        .filter_map(|(account, &key)| {
+            let bin_array = BinArray::try_deserialize(&mut account?.data.as_ref()).ok()?;
+            Some((key, bin_array))
+        })
+        .collect::<HashMap<_, _>>();
+
+    let (amount_in, amount_out, fee, crossed_bin_arrays, price_impact_pct) = match mode {
+        QuoteMode::ExactIn { amount_in } | QuoteMode::PriceImpact { amount_in, .. } => {
+            let quote = quote_exact_in(
+                lb_pair,
+                &lb_pair_state,
+                amount_in,
+                swap_for_y,
+                bin_arrays,
+                bitmap_extension.as_ref(),
+                clock.unix_timestamp as u64,
+                clock.slot,
+            )?;
+            let price_impact_pct = get_price_impact_pct(
+                &lb_pair_state,
+                amount_in.saturating_sub(quote.fee),
+                quote.amount_out,
+                swap_for_y,
+            )?;
+            (
+                amount_in,
+                quote.amount_out,
+                quote.fee,
+                quote.bin_arrays,
+                price_impact_pct,
+            )
+        }
+        QuoteMode::ExactOut { amount_out } => {
+            let quote = quote_exact_out(
+                lb_pair,
+                &lb_pair_state,
+                amount_out,
+                swap_for_y,
+                bin_arrays,
+                bitmap_extension.as_ref(),
+                clock.unix_timestamp as u64,
+                clock.slot,
+            )?;
+            let price_impact_pct =
+                get_price_impact_pct(&lb_pair_state, quote.amount_in, amount_out, swap_for_y)?;
+            let amount_in = quote
+                .amount_in
+                .checked_add(quote.fee)
+                .context("MathOverflow")?;
+            (
+                amount_in,
+                amount_out,
+                quote.fee,
+                quote.bin_arrays,
+                price_impact_pct,
+            )
+        }
+    };
+
+    let (mode_name, max_price_impact_bps) = match mode {
+        QuoteMode::ExactIn { .. } => ("exact-in", None),
+        QuoteMode::ExactOut { .. } => ("exact-out", None),
+        QuoteMode::PriceImpact {
+            max_price_impact_bps,
+            ..
+        } => ("price-impact", Some(max_price_impact_bps)),
+    };
+
+    let report = QuoteReport {
+        lb_pair: lb_pair.to_string(),
+        mode: mode_name.to_string(),
+        swap_for_y,
+        active_id: lb_pair_state.active_id,
+        amount_in,
+        amount_out,
+        fee,
+        price_impact_pct,
+        bin_arrays: crossed_bin_arrays.iter().map(Pubkey::to_string).collect(),
+        max_price_impact_bps,
+        within_max_price_impact: max_price_impact_bps
+            .map(|bps| price_impact_pct * 100.0 <= f64::from(bps)),
+        slot: clock.slot,
+    };
+
+    print_report(output, &report)
+}
@@ -0,0 +1,824 @@
+use std::collections::HashMap;
+use std::ops::Deref;
+
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::signature::Keypair;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::solana_program::instruction::AccountMeta;
+use anchor_lang::{AccountDeserialize, InstructionData, ToAccountMetas};
+use anchor_spl::token::TokenAccount;
+use anyhow::*;
+use commons::position::get_position_amounts;
+use commons::quote::{get_bin_array_pubkeys_for_swap, quote_exact_in};
+use lb_clmm::accounts;
+use lb_clmm::constants::MAX_BIN_PER_POSITION;
+use lb_clmm::instruction;
+use lb_clmm::instructions::deposit::{
+    LiquidityParameterByStrategy, StrategyParameters, StrategyType,
+};
+use lb_clmm::math::price_math::get_price_from_id;
+use lb_clmm::math::u64x64_math::SCALE_OFFSET;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::state::position::PositionV2;
+use lb_clmm::utils::pda::*;
+use serde::{Deserialize, Serialize};
+
+use crate::instructions::utils::{
+    compute_budget_instructions, derive_position_bin_arrays, get_bin_array_bitmap_extension,
+    get_clock, get_min_amount_out, get_or_create_ata, get_swap_bin_array_count,
+};
+use crate::journal::Journal;
+use crate::transaction::{TransactionConfig, TransactionMode};
+
+#[derive(Serialize, Deserialize, Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum RebalanceStep {
+    /// Remove the liquidity, claim the fees and rewards and close the position
+    #[default]
+    Withdraw,
+    /// Swap the withdrawn amounts to the target ratio
+    Swap,
+    /// Open the new position and deposit the withdrawn amounts
+    Deposit,
+    Done,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+#[serde(default)]
+pub struct RebalanceState {
+    pub position: Pubkey,
+    pub lb_pair: Pubkey,
+    pub step: RebalanceStep,
+    /// Wallet token balances before the transaction of the current step
+    pub balance_x_before: u64,
+    pub balance_y_before: u64,
+    /// Amounts to deposit to the new position
+    pub amount_x: u64,
+    pub amount_y: u64,
+    /// Swap of the swap step, fixed before it is sent so a resumed run can find it on chain
+    pub swap_amount_in: u64,
+    pub swap_for_y: bool,
+    pub new_position: Pubkey,
+}
+
+#[derive(Debug)]
+pub struct RebalancePositionParameters {
+    pub position: Pubkey,
+    pub strategy_type: StrategyType,
+    /// Number of bins of the new position, centered on the active bin
+    pub width: i32,
+    /// Share of the value, in token Y at the active price, held in token X before the deposit.
+    /// The withdrawn amounts are swapped through the pair to reach it. No swap when None.
+    pub target_x_ratio: Option<f64>,
+    /// Slippage tolerance on the quoted amount out of the swap
+    pub slippage_bps: u16,
+    /// Max number of bins the active bin can move by before the deposit fails
+    pub max_active_bin_slippage: i32,
+}
+
+/// Swap bringing the value share of token X to `target_x_ratio`, as (amount in, swap for y).
+/// `price` is the active bin price of a token X lamport in token Y lamports. Fees are not accounted for.
+pub fn get_rebalance_swap(
+    amount_x: u64,
+    amount_y: u64,
+    price: f64,
+    target_x_ratio: f64,
+) -> Result<Option<(u64, bool)>> {
+    ensure!(
+        (0.0..=1.0).contains(&target_x_ratio),
+        "Target X ratio must be between 0 and 1"
+    );
+    ensure!(price > 0.0, "Invalid price");
+
+    let value_x = amount_x as f64 * price;
+    let total_value = value_x + amount_y as f64;
+    let target_value_x = total_value * target_x_ratio;
+
+    let swap = if value_x > target_value_x {
+        (((value_x - target_value_x) / price) as u64, true)
+    } else {
+        ((target_value_x - value_x) as u64, false)
+    };
+
+    Ok((swap.0 > 0).then_some(swap))
+}
+
+async fn get_token_balances<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    user_token_x: Pubkey,
+    user_token_y: Pubkey,
+) -> Result<(u64, u64)> {
+    let accounts = program
+        .async_rpc()
+        .get_multiple_accounts(&[user_token_x, user_token_y])
+        .await?;
+
+    let mut balances = accounts.into_iter().map(|account| {
+        let account = account.context("Token account not found")?;
+        Ok(TokenAccount::try_deserialize(&mut account.data.as_ref())?.amount)
+    });
+    let balance_x = balances.next().context("Missing token X account")??;
+    let balance_y = balances.next().context("Missing token Y account")??;
+
+    Ok((balance_x, balance_y))
+}
+
+/// Remove all the liquidity, claim the fees and rewards and close the position, in one transaction.
+async fn withdraw<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    journal: &mut Journal,
+    position: Pubkey,
+    lb_pair_state: &LbPair,
+    user_token_x: Pubkey,
+    user_token_y: Pubkey,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let position_state: PositionV2 = program.account(position).await?;
+    let lb_pair = position_state.lb_pair;
+
+    ensure!(
+        position_state.owner == program.payer(),
+        "The wallet is not the owner of the position"
+    );
+    // remove_all_liquidity has no slippage guard. Bins out of range hold a single token, so the withdrawn
+    // amounts only change when the active bin moves into the position.
+    ensure!(
+        position_state
+            .id_within_position(lb_pair_state.active_id)
+            .is_err(),
+        "Position is in range. Active bin {}, position bins {} - {}",
+        lb_pair_state.active_id,
+        position_state.lower_bin_id,
+        position_state.upper_bin_id
+    );
+
+    let [bin_array_lower, bin_array_upper] =
+        derive_position_bin_arrays(lb_pair, position_state.lower_bin_id)?;
+    let bin_arrays = program
+        .async_rpc()
+        .get_multiple_accounts(&[bin_array_lower, bin_array_upper])
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|account| Ok(BinArray::try_deserialize(&mut account.data.as_ref())?))
+        .collect::<Result<Vec<_>>>()?;
+    let (amount_x, amount_y) = get_position_amounts(&position_state, &bin_arrays)?;
+    println!(
+        "Withdraw {} X and {} Y from position {} (bins {} - {})",
+        amount_x, amount_y, position, position_state.lower_bin_id, position_state.upper_bin_id
+    );
+
+    let (event_authority, _bump) = derive_event_authority_pda();
+    let bin_array_bitmap_extension = get_bin_array_bitmap_extension(program, lb_pair)
+        .await
+        .map(|(key, _)| key);
+
+    let mut instructions = compute_budget_instructions(1_400_000, compute_unit_price.clone());
+    instructions.push(Instruction {
+        program_id: lb_clmm::ID,
+        accounts: accounts::ModifyLiquidity {
+            bin_array_lower,
+            bin_array_upper,
+            lb_pair,
+            bin_array_bitmap_extension,
+            position,
+            reserve_x: lb_pair_state.reserve_x,
+            reserve_y: lb_pair_state.reserve_y,
+            token_x_mint: lb_pair_state.token_x_mint,
+            token_y_mint: lb_pair_state.token_y_mint,
+            sender: program.payer(),
+            user_token_x,
+            user_token_y,
+            token_x_program: anchor_spl::token::ID,
+            token_y_program: anchor_spl::token::ID,
+            event_authority,
+            program: lb_clmm::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::RemoveAllLiquidity {}.data(),
+    });
+    instructions.push(Instruction {
+        program_id: lb_clmm::ID,
+        accounts: accounts::ClaimFee {
+            bin_array_lower,
+            bin_array_upper,
+            lb_pair,
+            sender: program.payer(),
+            position,
+            reserve_x: lb_pair_state.reserve_x,
+            reserve_y: lb_pair_state.reserve_y,
+            token_program: anchor_spl::token::ID,
+            token_x_mint: lb_pair_state.token_x_mint,
+            token_y_mint: lb_pair_state.token_y_mint,
+            user_token_x,
+            user_token_y,
+            event_authority,
+            program: lb_clmm::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClaimFee {}.data(),
+    });
+
+    // Positions with pending rewards can't be closed
+    for (reward_index, reward_info) in lb_pair_state.reward_infos.iter().enumerate() {
+        if !reward_info.initialized() {
+            continue;
+        }
+        let user_token_account = get_or_create_ata(
+            program,
+            transaction_config,
+            reward_info.mint,
+            program.payer(),
+            compute_unit_price.clone(),
+        )
+        .await?;
+        let reward_index = reward_index as u64;
+        let (reward_vault, _bump) = derive_reward_vault_pda(lb_pair, reward_index);
+
+        instructions.push(Instruction {
+            program_id: lb_clmm::ID,
+            accounts: accounts::ClaimReward {
+                bin_array_lower,
+                bin_array_upper,
+                lb_pair,
+                reward_vault,
+                reward_mint: reward_info.mint,
+                token_program: anchor_spl::token::ID,
+                position,
+                user_token_account,
+                sender: program.payer(),
+                event_authority,
+                program: lb_clmm::ID,
+            }
+            .to_account_metas(None),
+            data: instruction::ClaimReward { reward_index }.data(),
+        });
+    }
+
+    instructions.push(Instruction {
+        program_id: lb_clmm::ID,
+        accounts: accounts::ClosePosition {
+            bin_array_lower,
+            bin_array_upper,
+            lb_pair,
+            sender: program.payer(),
+            rent_receiver: program.payer(),
+            position,
+            event_authority,
+            program: lb_clmm::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::ClosePosition {}.data(),
+    });
+
+    let builder = program.request();
+    let builder = instructions
+        .into_iter()
+        .fold(builder, |bld, ix| bld.instruction(ix));
+
+    if let Some(signature) = journal
+        .send_step(program, "withdraw", builder, &[], transaction_config)
+        .await?
+    {
+        println!("Withdraw and close position. Signature: {}", signature);
+    }
+
+    Ok(())
+}
+
+async fn swap<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    journal: &mut Journal,
+    lb_pair: Pubkey,
+    lb_pair_state: &LbPair,
+    amount_in: u64,
+    swap_for_y: bool,
+    slippage_bps: u16,
+    user_token_x: Pubkey,
+    user_token_y: Pubkey,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let (user_token_in, user_token_out) = if swap_for_y {
+        (user_token_x, user_token_y)
+    } else {
+        (user_token_y, user_token_x)
+    };
+
+    let bitmap_extension = get_bin_array_bitmap_extension(program, lb_pair).await;
+
+    let bin_arrays_for_swap = get_bin_array_pubkeys_for_swap(
+        lb_pair,
+        lb_pair_state,
+        bitmap_extension.as_ref().map(|(_, extension)| extension),
+        swap_for_y,
+        get_swap_bin_array_count(transaction_config),
+    )?;
+    let bin_arrays = program
+        .async_rpc()
+        .get_multiple_accounts(&bin_arrays_for_swap)
+        .await?
+        .into_iter()
+        .zip(bin_arrays_for_swap.iter())
+        .map(|(account, &key)| {
+            let account = account?;
+            Some((
+                key,
+                BinArray::try_deserialize(&mut account.data.as_ref()).ok()?,
+            ))
+        })
+        .collect::<Option<HashMap<Pubkey, BinArray>>>()
+        .context("Failed to fetch bin arrays")?;
+
+    let clock = get_clock(program).await?;
+    let quote = quote_exact_in(
+        lb_pair,
+        lb_pair_state,
+        amount_in,
+        swap_for_y,
+        bin_arrays,
+        bitmap_extension.as_ref().map(|(_, extension)| extension),
+        clock.unix_timestamp as u64,
+        clock.slot,
+    )?;
+    let min_amount_out = get_min_amount_out(quote.amount_out, slippage_bps)?;
+    println!(
+        "Swap {} {} for at least {} {}",
+        amount_in,
+        if swap_for_y { "X" } else { "Y" },
+        min_amount_out,
+        if swap_for_y { "Y" } else { "X" },
+    );
+
+    let (event_authority, _bump) = derive_event_authority_pda();
+
+    let accounts = accounts::Swap {
+        lb_pair,
+        bin_array_bitmap_extension: bitmap_extension.map(|(key, _)| key).or(Some(lb_clmm::ID)),
+        reserve_x: lb_pair_state.reserve_x,
+        reserve_y: lb_pair_state.reserve_y,
+        token_x_mint: lb_pair_state.token_x_mint,
+        token_y_mint: lb_pair_state.token_y_mint,
+        token_x_program: anchor_spl::token::ID,
+        token_y_program: anchor_spl::token::ID,
+        user: program.payer(),
+        user_token_in,
+        user_token_out,
+        oracle: lb_pair_state.oracle,
+        host_fee_in: Some(lb_clmm::ID),
+        event_authority,
+        program: lb_clmm::ID,
+    };
+
+    let ix = instruction::Swap {
+        amount_in,
+        min_amount_out,
+    };
+
+    let remaining_accounts = bin_arrays_for_swap
+        .into_iter()
+        .map(|key| AccountMeta::new(key, false))
+        .collect::<Vec<_>>();
+
+    let mut request_builder = program.request();
+    for compute_budget_ix in compute_budget_instructions(1_400_000, compute_unit_price) {
+        request_builder = request_builder.instruction(compute_budget_ix);
+    }
+    let request_builder = request_builder
+        .accounts(accounts)
+        .accounts(remaining_accounts)
+        .args(ix);
+
+    if let Some(signature) = journal
+        .send_step(program, "swap", request_builder, &[], transaction_config)
+        .await?
+    {
+        println!("Swap. Signature: {}", signature);
+    }
+
+    Ok(())
+}
+
+/// Open a position of `width` bins centered on the active bin and deposit with the strategy, in one transaction.
+async fn deposit<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    journal: &mut Journal,
+    lb_pair: Pubkey,
+    lb_pair_state: &LbPair,
+    position_kp: &Keypair,
+    amount_x: u64,
+    amount_y: u64,
+    width: i32,
+    strategy_type: StrategyType,
+    max_active_bin_slippage: i32,
+    user_token_x: Pubkey,
+    user_token_y: Pubkey,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let lower_bin_id = lb_pair_state.active_id - width / 2;
+    let upper_bin_id = lower_bin_id + width - 1;
+    let position = position_kp.pubkey();
+
+    let mut instructions = compute_budget_instructions(1_400_000, compute_unit_price);
+
+    let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(lower_bin_id)?;
+    let upper_bin_array_idx = lower_bin_array_idx.checked_add(1).context("MathOverflow")?;
+    for idx in lower_bin_array_idx..=upper_bin_array_idx {
+        // Initialize bin array if not exists
+        let (bin_array, _bump) = derive_bin_array_pda(lb_pair, idx.into());
+
+        if program
+            .async_rpc()
+            .get_account_data(&bin_array)
+            .await
+            .is_err()
+        {
+            instructions.push(Instruction {
+                program_id: lb_clmm::ID,
+                accounts: accounts::InitializeBinArray {
+                    bin_array,
+                    funder: program.payer(),
+                    lb_pair,
+                    system_program: anchor_client::solana_sdk::system_program::ID,
+                }
+                .to_account_metas(None),
+                data: instruction::InitializeBinArray { index: idx.into() }.data(),
+            });
+        }
+    }
+
+    let (event_authority, _bump) = derive_event_authority_pda();
+    instructions.push(Instruction {
+        program_id: lb_clmm::ID,
+        accounts: accounts::InitializePosition {
+            lb_pair,
+            payer: program.payer(),
+            position,
+            owner: program.payer(),
+            rent: anchor_client::solana_sdk::sysvar::rent::ID,
+            system_program: anchor_client::solana_sdk::system_program::ID,
+            event_authority,
+            program: lb_clmm::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::InitializePosition {
+            lower_bin_id,
+            width,
+        }
+        .data(),
+    });
+
+    let [bin_array_lower, bin_array_upper] = derive_position_bin_arrays(lb_pair, lower_bin_id)?;
+    let bin_array_bitmap_extension = get_bin_array_bitmap_extension(program, lb_pair)
+        .await
+        .map(|(key, _)| key);
+
+    instructions.push(Instruction {
+        program_id: lb_clmm::ID,
+        accounts: accounts::ModifyLiquidity {
+            lb_pair,
+            position,
+            bin_array_bitmap_extension,
+            bin_array_lower,
+            bin_array_upper,
+            sender: program.payer(),
+            event_authority,
+            program: lb_clmm::ID,
+            reserve_x: lb_pair_state.reserve_x,
+            reserve_y: lb_pair_state.reserve_y,
+            token_x_mint: lb_pair_state.token_x_mint,
+            token_y_mint: lb_pair_state.token_y_mint,
+            user_token_x,
+            user_token_y,
+            token_x_program: anchor_spl::token::ID,
+            token_y_program: anchor_spl::token::ID,
+        }
+        .to_account_metas(None),
+        data: instruction::AddLiquidityByStrategy {
+            liquidity_parameter: LiquidityParameterByStrategy {
+                amount_x,
+                amount_y,
+                active_id: lb_pair_state.active_id,
+                max_active_bin_slippage,
+                strategy_parameters: StrategyParameters {
+                    min_bin_id: lower_bin_id,
+                    max_bin_id: upper_bin_id,
+                    strategy_type,
+                    parameteres: [0u8; 64],
+                },
+            },
+        }
+        .data(),
+    });
+
+    println!(
+        "Deposit {} X and {} Y to new position {} (bins {} - {})",
+        amount_x, amount_y, position, lower_bin_id, upper_bin_id
+    );
+
+    let builder = program.request();
+    let builder = instructions
+        .into_iter()
+        .fold(builder, |bld, ix| bld.instruction(ix));
+
+    if let Some(signature) = journal
+        .send_step(
+            program,
+            "deposit",
+            builder,
+            &[position_kp],
+            transaction_config,
+        )
+        .await?
+    {
+        println!("Open position and deposit. Signature: {}", signature);
+    }
+
+    Ok(())
+}
+
+pub async fn rebalance_position<C: Deref<Target = impl Signer> + Clone>(
+    params: RebalancePositionParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let RebalancePositionParameters {
+        position,
+        strategy_type,
+        width,
+        target_x_ratio,
+        slippage_bps,
+        max_active_bin_slippage,
+    } = params;
+
+    ensure!(
+        width > 0 && width <= MAX_BIN_PER_POSITION as i32,
+        "Width must be between 1 and {}",
+        MAX_BIN_PER_POSITION
+    );
+
+    let mut journal = Journal::open("rebalance-position", position, transaction_config)?;
+    let sent = matches!(transaction_config.mode, TransactionMode::Send);
+
+    let mut rebalance_state: RebalanceState = journal.data()?;
+    if rebalance_state.position == Pubkey::default() {
+        let position_state: PositionV2 = program.account(position).await?;
+        rebalance_state.position = position;
+        rebalance_state.lb_pair = position_state.lb_pair;
+    } else {
+        ensure!(
+            rebalance_state.position == position,
+            "Invalid rebalance journal {}",
+            journal.path()
+        );
+        println!(
+            "Resume rebalance of position {} from step {:?}",
+            position, rebalance_state.step
+        );
+    }
+
+    if rebalance_state.step == RebalanceStep::Done {
+        println!(
+            "Position already rebalanced into {}. Remove {} to rebalance it again.",
+            rebalance_state.new_position,
+            journal.path()
+        );
+        return Ok(());
+    }
+
+    let lb_pair = rebalance_state.lb_pair;
+    let lb_pair_state: LbPair = program.account(lb_pair).await?;
+
+    let user_token_x = get_or_create_ata(
+        program,
+        transaction_config,
+        lb_pair_state.token_x_mint,
+        program.payer(),
+        compute_unit_price.clone(),
+    )
+    .await?;
+
+    let user_token_y = get_or_create_ata(
+        program,
+        transaction_config,
+        lb_pair_state.token_y_mint,
+        program.payer(),
+        compute_unit_price.clone(),
+    )
+    .await?;
+
+    if rebalance_state.step == RebalanceStep::Withdraw {
+        let position_closed = move || async move {
+            Ok(program
+                .async_rpc()
+                .get_account_data(&position)
+                .await
+                .is_err())
+        };
+
+        // The balances of a started withdrawal were taken before it was sent
+        if journal.step("withdraw").is_none() && !position_closed().await? {
+            let (balance_x, balance_y) =
+                get_token_balances(program, user_token_x, user_token_y).await?;
+            rebalance_state.balance_x_before = balance_x;
+            rebalance_state.balance_y_before = balance_y;
+            journal.set_data(&rebalance_state)?;
+        }
+
+        if !journal
+            .wait_step(program, "withdraw", position_closed)
+            .await?
+        {
+            withdraw(
+                program,
+                &mut journal,
+                position,
+                &lb_pair_state,
+                user_token_x,
+                user_token_y,
+                transaction_config,
+                compute_unit_price.clone(),
+            )
+            .await?;
+            if !sent {
+                println!("Stop. The next steps depend on the withdrawn amounts.");
+                return Ok(());
+            }
+        }
+
+        let (balance_x, balance_y) =
+            get_token_balances(program, user_token_x, user_token_y).await?;
+        rebalance_state.amount_x = balance_x.saturating_sub(rebalance_state.balance_x_before);
+        rebalance_state.amount_y = balance_y.saturating_sub(rebalance_state.balance_y_before);
+        rebalance_state.step = RebalanceStep::Swap;
+        journal.set_data(&rebalance_state)?;
+    }
+
+    if rebalance_state.step == RebalanceStep::Swap {
+        let lb_pair_state: LbPair = program.account(lb_pair).await?;
+
+        if journal.step("swap").is_none() {
+            let price = get_price_from_id(lb_pair_state.active_id, lb_pair_state.bin_step)? as f64
+                / (1u128 << SCALE_OFFSET) as f64;
+
+            let swap_params = match target_x_ratio {
+                Some(target_x_ratio) => get_rebalance_swap(
+                    rebalance_state.amount_x,
+                    rebalance_state.amount_y,
+                    price,
+                    target_x_ratio,
+                )?,
+                None => None,
+            };
+            let (amount_in, swap_for_y) = swap_params.unwrap_or_default();
+
+            let (balance_x, balance_y) =
+                get_token_balances(program, user_token_x, user_token_y).await?;
+            rebalance_state.balance_x_before = balance_x;
+            rebalance_state.balance_y_before = balance_y;
+            rebalance_state.swap_amount_in = amount_in;
+            rebalance_state.swap_for_y = swap_for_y;
+            journal.set_data(&rebalance_state)?;
+        }
+
+        let amount_in = rebalance_state.swap_amount_in;
+        let swap_for_y = rebalance_state.swap_for_y;
+        if amount_in > 0 {
+            let balance_in_before = if swap_for_y {
+                rebalance_state.balance_x_before
+            } else {
+                rebalance_state.balance_y_before
+            };
+            let swapped = move || async move {
+                let (balance_x, balance_y) =
+                    get_token_balances(program, user_token_x, user_token_y).await?;
+                let balance_in = if swap_for_y { balance_x } else { balance_y };
+                Ok(balance_in.saturating_add(amount_in) <= balance_in_before)
+            };
+
+            if !journal.wait_step(program, "swap", swapped).await? {
+                swap(
+                    program,
+                    &mut journal,
+                    lb_pair,
+                    &lb_pair_state,
+                    amount_in,
+                    swap_for_y,
+                    slippage_bps,
+                    user_token_x,
+                    user_token_y,
+                    transaction_config,
+                    compute_unit_price.clone(),
+                )
+                .await?;
+                if !sent {
+                    println!("Stop. The deposit depends on the swapped amounts.");
+                    return Ok(());
+                }
+            }
+
+            let (balance_x, balance_y) =
+                get_token_balances(program, user_token_x, user_token_y).await?;
+            rebalance_state.amount_x = (i128::from(rebalance_state.amount_x)
+                + i128::from(balance_x)
+                - i128::from(rebalance_state.balance_x_before))
+            .max(0)
+            .try_into()?;
+            rebalance_state.amount_y = (i128::from(rebalance_state.amount_y)
+                + i128::from(balance_y)
+                - i128::from(rebalance_state.balance_y_before))
+            .max(0)
+            .try_into()?;
+        }
+
+        rebalance_state.step = RebalanceStep::Deposit;
+        journal.set_data(&rebalance_state)?;
+    }
+
+    if rebalance_state.step == RebalanceStep::Deposit {
+        // The position of an interrupted attempt, whose keypair is gone
+        let previous_position = rebalance_state.new_position;
+        let deposited = move || async move {
+            Ok(previous_position != Pubkey::default()
+                && program
+                    .async_rpc()
+                    .get_account_data(&previous_position)
+                    .await
+                    .is_ok())
+        };
+
+        if !journal.wait_step(program, "deposit", deposited).await? {
+            let lb_pair_state: LbPair = program.account(lb_pair).await?;
+            let (balance_x, balance_y) =
+                get_token_balances(program, user_token_x, user_token_y).await?;
+            let amount_x = rebalance_state.amount_x.min(balance_x);
+            let amount_y = rebalance_state.amount_y.min(balance_y);
+            ensure!(amount_x > 0 || amount_y > 0, "Nothing to deposit");
+
+            let position_kp = Keypair::new();
+            rebalance_state.new_position = position_kp.pubkey();
+            journal.set_data(&rebalance_state)?;
+
+            deposit(
+                program,
+                &mut journal,
+                lb_pair,
+                &lb_pair_state,
+                &position_kp,
+                amount_x,
+                amount_y,
+                width,
+                strategy_type,
+                max_active_bin_slippage,
+                user_token_x,
+                user_token_y,
+                transaction_config,
+                compute_unit_price,
+            )
+            .await?;
+            if !sent {
+                return Ok(());
+            }
+        }
+
+        rebalance_state.step = RebalanceStep::Done;
+        journal.set_data(&rebalance_state)?;
+    }
+
+    println!(
+        "Rebalanced position {} into {}",
+        position, rebalance_state.new_position
+    );
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_rebalance_swap() {
+        // 100 X worth 200 Y, 0 Y. Half of the X value is swapped.
+        assert_eq!(
+            get_rebalance_swap(100, 0, 2.0, 0.5).unwrap(),
+            Some((50, true))
+        );
+        // 0 X, 200 Y. Half of the Y is swapped.
+        assert_eq!(
+            get_rebalance_swap(0, 200, 2.0, 0.5).unwrap(),
+            Some((100, false))
+        );
+        // Already at the target
+        assert_eq!(get_rebalance_swap(50, 100, 2.0, 0.5).unwrap(), None);
+        // All in Y
+        assert_eq!(
+            get_rebalance_swap(50, 100, 2.0, 0.0).unwrap(),
+            Some((50, true))
+        );
+        assert!(get_rebalance_swap(50, 100, 2.0, 1.5).is_err());
+    }
+}
@@ -0,0 +1,32 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::{compute_budget_instructions, LiquidityTarget};
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::instruction;
+
+pub async fn remove_all_liquidity<C: Deref<Target = impl Signer> + Clone>(
+    position: Pubkey,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let target = LiquidityTarget::fetch(program, position).await?;
+    let accounts = target
+        .get_modify_liquidity_accounts(program, transaction_config, compute_unit_price.clone())
+        .await?;
+
+    let request_builder = compute_budget_instructions(1_400_000, compute_unit_price)
+        .into_iter()
+        .fold(program.request(), |bld, ix| bld.instruction(ix))
+        .accounts(accounts)
+        .args(instruction::RemoveAllLiquidity {});
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Remove all liquidity. Signature: {}", signature);
+    }
+
+    Ok(())
+}
@@ -1,6 +1,6 @@
 use crate::instructions::utils::get_or_create_ata;
 use crate::math::{get_id_from_price, price_per_token_to_per_lamport};
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
 use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
@@ -30,7 +30,7 @@ pub struct RemoveLiquidityByPriceRangeParameters {
 pub async fn remove_liquidity_by_price_range<C: Deref<Target = impl Signer> + Clone>(
     params: RemoveLiquidityByPriceRangeParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let RemoveLiquidityByPriceRangeParameters {
@@ -174,10 +174,11 @@ pub async fn remove_liquidity_by_price_range<C: Deref<Target = impl Signer> + Cl
                 let builder = instructions
                     .into_iter()
                     .fold(builder, |bld, ix| bld.instruction(ix));
-                let signature = builder
-                    .send_with_spinner_and_config(transaction_config)
-                    .await?;
-                println!("close popsition min_bin_id {i} {signature}");
+                if let Some(signature) =
+                    send_or_simulate(program, builder, transaction_config).await?
+                {
+                    println!("close popsition min_bin_id {i} {signature}");
+                }
             }
             Err(_err) => continue,
         }
@@ -1,11 +1,17 @@
 use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufReader, BufWriter, Write};
+use std::future::Future;
+use std::io::BufReader;
 use std::ops::Deref;
 
-use crate::instructions::utils::get_or_create_ata;
+use crate::instructions::seed_plan::{print_seed_plan, SeedPlanOptions, SeedPlanParameters};
+use crate::instructions::utils::{get_or_create_ata, pack_instructions, BudgetedInstruction};
+use crate::journal::Journal;
 use crate::math::{get_id_from_price, price_per_token_to_per_lamport};
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::seed_curve::{
+    distribute_amount, plot_bin_amounts, BinDistribution, BinPrice, SeedCurve,
+};
+use crate::transaction::TransactionConfig;
 use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
 use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::solana_sdk::signature::Keypair;
@@ -14,6 +20,7 @@ use anchor_lang::InstructionData;
 use anchor_lang::ToAccountMetas;
 use anchor_spl::token::Mint;
 use anyhow::*;
+use commons::compute_budget::is_compute_budget_instruction;
 use lb_clmm::accounts;
 use lb_clmm::constants::{BASIS_POINT_MAX, MAX_BIN_PER_POSITION};
 use lb_clmm::instruction;
@@ -23,7 +30,6 @@ use lb_clmm::state::bin::BinArray;
 use lb_clmm::state::lb_pair::LbPair;
 use lb_clmm::state::position::PositionV2;
 use lb_clmm::utils::pda::*;
-use rust_decimal::prelude::ToPrimitive;
 use serde::{Deserialize, Serialize};
 use serde_json_any_key::*;
 
@@ -111,7 +117,8 @@ async fn get_or_create_position<C: Deref<Target = impl Signer> + Clone>(
     upper_bin_id: i32,
     width: i32,
     owner: &Keypair,
-    transaction_config: RpcSendTransactionConfig,
+    journal: &mut Journal,
+    transaction_config: TransactionConfig,
     compute_unit_price_ix: Option<Instruction>,
 ) -> Result<PositionV2> {
     let (event_authority, _bump) = derive_event_authority_pda();
@@ -146,15 +153,25 @@ async fn get_or_create_position<C: Deref<Target = impl Signer> + Clone>(
             builder = builder.instruction(compute_unit_price_ix);
         }
 
-        builder = builder.instruction(ix).signer(base_keypair).signer(owner);
-        let signature = builder
-            .send_with_spinner_and_config(transaction_config)
-            .await;
-        println!(
-            "Create position: lower bin id {lower_bin_id} upper bin id {upper_bin_id} position {position}. signature {:#?}",
-            signature
-        );
-        signature?;
+        builder = builder.instruction(ix);
+        let landed =
+            move || async move { Ok(program.async_rpc().get_account(&position).await.is_ok()) };
+        if let Some(signature) = journal
+            .run_step(
+                program,
+                &format!("initialize_position:{}", position),
+                landed,
+                builder,
+                &[base_keypair, owner],
+                transaction_config,
+            )
+            .await?
+        {
+            println!(
+                "Create position: lower bin id {lower_bin_id} upper bin id {upper_bin_id} position {position}. signature {}",
+                signature
+            );
+        }
     }
 
     let position_state: PositionV2 = program.account(position).await?;
@@ -162,7 +179,14 @@ async fn get_or_create_position<C: Deref<Target = impl Signer> + Clone>(
     Ok(position_state)
 }
 
-pub async fn deposit<C: Deref<Target = impl Signer> + Clone>(
+/// Compute units budgeted for a deposit, on top of the compute units per bin it deposits to
+const DEPOSIT_BASE_COMPUTE_UNITS: u32 = 100_000;
+const DEPOSIT_COMPUTE_UNITS_PER_BIN: u32 = 10_000;
+/// Compute unit limit of the deposit to a full position
+const DEPOSIT_COMPUTE_UNIT_LIMIT: u32 = 800_000;
+
+/// `add_liquidity` instruction depositing `deposit_amount_x` to the position, with the compute units budgeted for it
+pub fn get_deposit_instruction<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
     position: Pubkey,
     position_state: &PositionV2,
@@ -171,18 +195,8 @@ pub async fn deposit<C: Deref<Target = impl Signer> + Clone>(
     user_token_y: Pubkey,
     deposit_amount_x: u64,
     position_liquidity_distribution: Vec<BinLiquidityDistribution>,
-    transaction_config: RpcSendTransactionConfig,
-    compute_unit_price_ix: Option<Instruction>,
-) -> Result<String> {
+) -> Result<BudgetedInstruction> {
     let (event_authority, _bump) = derive_event_authority_pda();
-    let mut instructions = if let Some(compute_unit_price_ix) = compute_unit_price_ix {
-        vec![
-            compute_unit_price_ix,
-            ComputeBudgetInstruction::set_compute_unit_limit(800_000),
-        ]
-    } else {
-        vec![ComputeBudgetInstruction::set_compute_unit_limit(800_000)]
-    };
 
     let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(position_state.lower_bin_id)?;
 
@@ -191,7 +205,13 @@ pub async fn deposit<C: Deref<Target = impl Signer> + Clone>(
     let (bin_array_upper, _bump) =
         derive_bin_array_pda(position_state.lb_pair, (lower_bin_array_idx + 1).into());
 
-    instructions.push(Instruction {
+    let bin_count = position_liquidity_distribution.len() as u32;
+    let compute_units = DEPOSIT_COMPUTE_UNITS_PER_BIN
+        .saturating_mul(bin_count)
+        .saturating_add(DEPOSIT_BASE_COMPUTE_UNITS)
+        .min(DEPOSIT_COMPUTE_UNIT_LIMIT);
+
+    let instruction = Instruction {
         program_id: lb_clmm::ID,
         accounts: accounts::ModifyLiquidity {
             lb_pair: position_state.lb_pair,
@@ -220,30 +240,156 @@ pub async fn deposit<C: Deref<Target = impl Signer> + Clone>(
             },
         }
         .data(),
-    });
+    };
+
+    Ok(BudgetedInstruction {
+        instruction,
+        compute_units,
+    })
+}
+
+/// Deposit to the position as the journal step `step`, which `landed` finds done on chain
+pub async fn deposit<C: Deref<Target = impl Signer> + Clone, F, Fut>(
+    program: &Program<C>,
+    journal: &mut Journal,
+    step: &str,
+    landed: F,
+    position: Pubkey,
+    position_state: &PositionV2,
+    lb_pair_state: &LbPair,
+    user_token_x: Pubkey,
+    user_token_y: Pubkey,
+    deposit_amount_x: u64,
+    position_liquidity_distribution: Vec<BinLiquidityDistribution>,
+    transaction_config: TransactionConfig,
+    compute_unit_price_ix: Option<Instruction>,
+) -> Result<()>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<bool>>,
+{
+    let mut instructions = if let Some(compute_unit_price_ix) = compute_unit_price_ix {
+        vec![
+            compute_unit_price_ix,
+            ComputeBudgetInstruction::set_compute_unit_limit(DEPOSIT_COMPUTE_UNIT_LIMIT),
+        ]
+    } else {
+        vec![ComputeBudgetInstruction::set_compute_unit_limit(
+            DEPOSIT_COMPUTE_UNIT_LIMIT,
+        )]
+    };
+
+    let deposit_ix = get_deposit_instruction(
+        program,
+        position,
+        position_state,
+        lb_pair_state,
+        user_token_x,
+        user_token_y,
+        deposit_amount_x,
+        position_liquidity_distribution,
+    )?;
+    instructions.push(deposit_ix.instruction);
 
     let builder = program.request();
     let builder = instructions
         .into_iter()
         .fold(builder, |bld, ix| bld.instruction(ix));
 
-    let signature = builder
-        .send_with_spinner_and_config(transaction_config)
-        .await;
+    if let Some(signature) = journal
+        .run_step(program, step, landed, builder, &[], transaction_config)
+        .await?
+    {
+        println!(
+            "Seed liquidity min_bin_id {} max_bin_id {} Position {position}. Sig: {}",
+            position_state.lower_bin_id, position_state.upper_bin_id, signature
+        );
+    }
 
-    println!(
-        "Seed liquidity min_bin_id {} max_bin_id {} Position {position}. Sig: {:#?}",
-        position_state.lower_bin_id, position_state.upper_bin_id, signature
-    );
+    Ok(())
+}
 
-    Ok(signature?.to_string())
+/// Deposit sent as the journal step `key`
+pub struct PendingDeposit {
+    pub key: String,
+    pub instruction: BudgetedInstruction,
+}
+
+/// Queue the deposit of the journal step `key` unless `landed` finds it done on chain. Waits until a previous
+/// attempt of the step can no longer land, so it can be sent again with the other queued deposits.
+pub async fn queue_deposit<C: Deref<Target = impl Signer> + Clone, F, Fut>(
+    program: &Program<C>,
+    journal: &mut Journal,
+    deposits: &mut Vec<PendingDeposit>,
+    key: String,
+    landed: F,
+    instruction: BudgetedInstruction,
+) -> Result<()>
+where
+    F: Fn() -> Fut,
+    Fut: Future<Output = Result<bool>>,
+{
+    if !journal.wait_step(program, &key, landed).await? {
+        deposits.push(PendingDeposit { key, instruction });
+    }
+    Ok(())
+}
+
+/// Send the queued deposits, packed into as few transactions as the compute unit limit and the transaction size
+/// allow. More of them fit in a transaction compiled against the lookup tables.
+pub async fn send_deposits<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    journal: &mut Journal,
+    deposits: Vec<PendingDeposit>,
+    transaction_config: TransactionConfig,
+    compute_unit_price_ix: Option<Instruction>,
+) -> Result<()> {
+    let (keys, instructions): (Vec<_>, Vec<_>) = deposits
+        .into_iter()
+        .map(|deposit| (deposit.key, deposit.instruction))
+        .unzip();
+
+    let transactions = pack_instructions(
+        instructions,
+        &program.payer(),
+        transaction_config.lookup_tables,
+        compute_unit_price_ix,
+    )?;
+
+    let mut keys = keys.as_slice();
+    for instructions in transactions {
+        let deposit_count = instructions
+            .iter()
+            .filter(|ix| !is_compute_budget_instruction(ix))
+            .count();
+        let (transaction_keys, remaining_keys) = keys.split_at(deposit_count);
+        keys = remaining_keys;
+
+        let builder = instructions
+            .into_iter()
+            .fold(program.request(), |bld, ix| bld.instruction(ix));
+
+        if let Some(signature) = journal
+            .send_steps(program, transaction_keys, builder, &[], transaction_config)
+            .await?
+        {
+            println!(
+                "Seed liquidity {}. Sig: {}",
+                transaction_keys.join(", "),
+                signature
+            );
+        }
+    }
+
+    Ok(())
 }
 
 pub async fn create_position_bin_array_if_not_exists<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
     lb_pair: Pubkey,
     lower_bin_id: i32,
-    transaction_config: RpcSendTransactionConfig,
+    journal: &mut Journal,
+    transaction_config: TransactionConfig,
     compute_unit_price_ix: Option<Instruction>,
 ) -> Result<(i32, i32)> {
     let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(lower_bin_id)?;
@@ -290,12 +436,29 @@ pub async fn create_position_bin_array_if_not_exists<C: Deref<Target = impl Sign
             request_builder = request_builder.instruction(ix);
         }
 
-        let sig = request_builder
-            .send_with_spinner_and_config(transaction_config)
-            .await;
-        println!("Initialize {} bin arrays. Signature {:#?}", ixs_length, sig);
+        let bin_arrays = [lower_bin_array_idx, upper_bin_array_idx]
+            .map(|idx| derive_bin_array_pda(lb_pair, idx.into()).0);
+        let landed = move || async move {
+            let accounts = program
+                .async_rpc()
+                .get_multiple_accounts(&bin_arrays)
+                .await?;
+            Ok(accounts.iter().all(Option::is_some))
+        };
 
-        sig?;
+        if let Some(sig) = journal
+            .run_step(
+                program,
+                &format!("initialize_bin_arrays:{}", lower_bin_array_idx),
+                landed,
+                request_builder,
+                &[],
+                transaction_config,
+            )
+            .await?
+        {
+            println!("Initialize {} bin arrays. Signature {}", ixs_length, sig);
+        }
     }
 
     Ok((lower_bin_array_idx, upper_bin_array_idx))
@@ -316,6 +479,10 @@ pub fn deposit_amount_to_deposit_parameter(
         total_amount += amount;
     }
 
+    if total_amount == 0 {
+        return Ok((vec![], 0));
+    }
+
     let mut bin_liquidity_dist = vec![];
 
     for bin_id in lower_bin_id..=upper_bin_id {
@@ -384,10 +551,12 @@ pub fn generate_redistribute_amount_to_position_based_on_ratio(
     let mut position_redistributed_amount_x = HashMap::new();
 
     for bin_id in lower_bin_id..=upper_bin_id {
-        let bin_amount_x: u128 = (*on_chain_bins_amount_x
+        // Bins the curve put nothing in are empty on chain
+        let bin_amount_x: u128 = on_chain_bins_amount_x
             .get(&bin_id)
-            .context("on chain bin amount x not found")?)
-        .into();
+            .copied()
+            .unwrap_or_default()
+            .into();
 
         let redistribute_amount: u64 = leftover_amount
             .checked_mul(bin_amount_x)
@@ -422,14 +591,6 @@ pub fn read_dust_deposit_state(path: &str) -> Result<DustDepositState> {
     }
 }
 
-pub fn write_dust_deposit_state(path: &str, dust_deposit_state: &DustDepositState) -> Result<()> {
-    let file = File::create(path)?;
-    let mut writer = BufWriter::new(file);
-    serde_json::to_writer(&mut writer, dust_deposit_state)?;
-    writer.flush()?;
-    Ok(())
-}
-
 #[derive(Debug)]
 pub struct SeedLiquidityParameters {
     pub lb_pair: Pubkey,
@@ -439,13 +600,17 @@ pub struct SeedLiquidityParameters {
     pub max_price: f64,
     pub base_pubkey: Pubkey,
     pub position_owner_kp: Keypair,
-    pub curvature: f64,
+    pub curve: SeedCurve,
+    /// Render the curve without seeding
+    pub plot: bool,
+    /// Print the seeding plan without seeding
+    pub plan: Option<SeedPlanOptions>,
 }
 
 pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
     params: SeedLiquidityParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let SeedLiquidityParameters {
@@ -456,21 +621,11 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
         max_price,
         position_owner_kp,
         base_pubkey,
-        curvature,
+        curve,
+        plot,
+        plan,
     } = params;
 
-    let progress_file_path = format!("{}_progress.json", lb_pair);
-
-    let mut dust_deposit_state = read_dust_deposit_state(&progress_file_path)?;
-    if dust_deposit_state.lb_pair != Pubkey::default() {
-        assert_eq!(
-            dust_deposit_state.lb_pair, lb_pair,
-            "Invalid dust deposit tracking file"
-        );
-    }
-
-    let k = 1.0 / curvature;
-
     // For easier validation during jup launch through .env
     assert_eq!(
         position_base_kp.pubkey(),
@@ -513,6 +668,72 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
 
     assert!(min_bin_id < max_bin_id, "Invalid price range");
 
+    let bins_amount = generate_amount_for_bins(
+        bin_step,
+        min_bin_id,
+        max_bin_id,
+        actual_min_price,
+        actual_max_price,
+        token_mint_base.decimals,
+        token_mint_quote.decimals,
+        fund_amount,
+        &curve,
+    )?;
+
+    if plot {
+        let bin_prices = get_bin_prices(
+            bin_step,
+            min_bin_id,
+            max_bin_id,
+            token_mint_base.decimals,
+            token_mint_quote.decimals,
+        );
+        let amounts = bins_amount
+            .iter()
+            .map(|(_, amount)| *amount)
+            .collect::<Vec<_>>();
+        plot_bin_amounts(&bin_prices, &amounts);
+    }
+
+    if let Some(plan) = plan {
+        let params = SeedPlanParameters {
+            lb_pair,
+            lb_pair_state: &lb_pair_state,
+            token_x_decimals: token_mint_base.decimals,
+            min_price,
+            max_price,
+            actual_min_price,
+            actual_max_price,
+            min_bin_id,
+            max_bin_id,
+            position_number,
+            position_base: position_base_kp.pubkey(),
+            position_owner: position_owner_kp.pubkey(),
+            by_operator: false,
+            bins_amount: &bins_amount,
+            fund_amount,
+        };
+        print_seed_plan(program, params, plan).await?;
+    }
+
+    if plot || plan.is_some() {
+        return Ok(());
+    }
+
+    let mut journal = Journal::open("seed-liquidity", lb_pair, transaction_config)?;
+
+    let mut dust_deposit_state: DustDepositState = journal.data()?;
+    if dust_deposit_state.lb_pair == Pubkey::default() {
+        // Progress file of the previous versions
+        dust_deposit_state = read_dust_deposit_state(&format!("{}_progress.json", lb_pair))?;
+    }
+    if dust_deposit_state.lb_pair != Pubkey::default() {
+        assert_eq!(
+            dust_deposit_state.lb_pair, lb_pair,
+            "Invalid dust deposit tracking file"
+        );
+    }
+
     let user_token_x = get_or_create_ata(
         program,
         transaction_config,
@@ -531,18 +752,6 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
     )
     .await?;
 
-    let bins_amount = generate_amount_for_bins(
-        bin_step,
-        min_bin_id,
-        max_bin_id,
-        actual_min_price,
-        actual_max_price,
-        token_mint_base.decimals,
-        token_mint_quote.decimals,
-        fund_amount,
-        k,
-    );
-
     let bins_amount_map: HashMap<i32, u64> = bins_amount
         .iter()
         .map(|(bin_id, amount_x)| (*bin_id, *amount_x))
@@ -550,6 +759,7 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
 
     let width = MAX_BIN_PER_POSITION as i32;
 
+    let mut deposits = vec![];
     for i in 0..position_number {
         let lower_bin_id = min_bin_id + (MAX_BIN_PER_POSITION as i32 * i);
         let upper_bin_id = lower_bin_id + MAX_BIN_PER_POSITION as i32 - 1;
@@ -558,6 +768,7 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
             program,
             lb_pair,
             lower_bin_id,
+            &mut journal,
             transaction_config,
             compute_unit_price.clone(),
         )
@@ -574,6 +785,7 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
             upper_bin_id,
             width,
             &position_owner_kp,
+            &mut journal,
             transaction_config,
             compute_unit_price.clone(),
         )
@@ -604,7 +816,14 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
         let (position_liquidity_distribution, deposit_amount_x) =
             deposit_amount_to_deposit_parameter(&bins_amount_map, lower_bin_id, upper_bin_id)?;
 
-        deposit(
+        // The curve put nothing in the bins of the position
+        if deposit_amount_x == 0 {
+            continue;
+        }
+
+        let landed =
+            move || async move { Ok(!program.account::<PositionV2>(position).await?.is_empty()) };
+        let deposit_ix = get_deposit_instruction(
             program,
             position,
             &position_state,
@@ -613,12 +832,27 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
             user_token_y,
             deposit_amount_x,
             position_liquidity_distribution,
-            transaction_config,
-            compute_unit_price.clone(),
+        )?;
+        queue_deposit(
+            program,
+            &mut journal,
+            &mut deposits,
+            format!("deposit:{}", position),
+            landed,
+            deposit_ix,
         )
         .await?;
     }
 
+    send_deposits(
+        program,
+        &mut journal,
+        deposits,
+        transaction_config,
+        compute_unit_price.clone(),
+    )
+    .await?;
+
     // States after principal deposit
     let (leftover, bins_amount_x, total_amount_in_bins_onchain, position_share) =
         if dust_deposit_state.lb_pair.eq(&Pubkey::default()) {
@@ -647,7 +881,7 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
                     .insert(position, position_liquidity_shares);
             }
 
-            write_dust_deposit_state(&progress_file_path, &dust_deposit_state)?;
+            journal.set_data(&dust_deposit_state)?;
 
             (
                 leftover,
@@ -671,6 +905,7 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
             leftover
         );
 
+        let mut deposits = vec![];
         for i in 0..position_number {
             let lower_bin_id = min_bin_id + (MAX_BIN_PER_POSITION as i32 * i);
 
@@ -682,18 +917,15 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
             let position_share_snapshot =
                 position_share.get(&position).context("Missing snapshot")?;
 
-            let mut dust_deposited = false;
-            for (i, share) in position_state.liquidity_shares.iter().enumerate() {
-                let snapshot_share = position_share_snapshot[i];
-                if snapshot_share != *share {
-                    dust_deposited = true;
-                    break;
-                }
-            }
-
-            if dust_deposited {
-                continue;
-            }
+            // The shares changed since the snapshot once the dust is deposited
+            let landed = move || async move {
+                let position_state: PositionV2 = program.account(position).await?;
+                Ok(position_state
+                    .liquidity_shares
+                    .iter()
+                    .zip(position_share_snapshot.iter())
+                    .any(|(share, snapshot_share)| share != snapshot_share))
+            };
 
             // Don't deposit to the last bin because c(last_bin + 1) - c(last_bin) will > amount
             let upper_bin_id = std::cmp::min(position_state.upper_bin_id, max_bin_id - 1);
@@ -712,7 +944,11 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
                     upper_bin_id,
                 )?;
 
-            deposit(
+            if position_redistributed_amount == 0 {
+                continue;
+            }
+
+            let deposit_ix = get_deposit_instruction(
                 program,
                 position,
                 &position_state,
@@ -721,11 +957,26 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
                 user_token_y,
                 position_redistributed_amount,
                 position_liquidity_distribution,
-                transaction_config,
-                compute_unit_price.clone(),
+            )?;
+            queue_deposit(
+                program,
+                &mut journal,
+                &mut deposits,
+                format!("redistribute:{}", position),
+                landed,
+                deposit_ix,
             )
             .await?;
         }
+
+        send_deposits(
+            program,
+            &mut journal,
+            deposits,
+            transaction_config,
+            compute_unit_price.clone(),
+        )
+        .await?;
     }
 
     let (_, total_amount_in_bin_onchain) =
@@ -749,8 +1000,16 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
 
         assert!(upper_bin_id < max_bin_id, "Funding to last bin id");
 
+        let landed = move || async move {
+            let (_, total_amount_in_bins_onchain) =
+                get_on_chain_bins_amount_x(lb_pair, min_bin_id, max_bin_id, program).await?;
+            Ok(total_amount_in_bins_onchain >= fund_amount)
+        };
         deposit(
             program,
+            &mut journal,
+            "deposit_dust",
+            landed,
             position,
             &position_state,
             &lb_pair_state,
@@ -787,68 +1046,33 @@ pub async fn seed_liquidity<C: Deref<Target = impl Signer> + Clone>(
     Ok(())
 }
 
-fn get_bin_deposit_amount(
-    amount: u64,
+/// Bins from `min_bin_id` to `max_bin_id`, excluded, with their UI prices
+pub fn get_bin_prices(
     bin_step: u16,
-    bin_id: i32,
-    base_token_decimal: u8,
-    quote_token_decimal: u8,
-    min_price: f64,
-    max_price: f64,
-    k: f64,
-) -> u64 {
-    let c1 = get_c(
-        amount,
-        bin_step,
-        bin_id + 1,
-        base_token_decimal,
-        quote_token_decimal,
-        min_price,
-        max_price,
-        k,
-    );
-
-    let c0 = get_c(
-        amount,
-        bin_step,
-        bin_id,
-        base_token_decimal,
-        quote_token_decimal,
-        min_price,
-        max_price,
-        k,
-    );
-
-    assert!(c1 > c0);
-
-    let amount_into_bin = c1 - c0;
-    amount_into_bin.to_u64().unwrap()
-}
-
-// c(p) = 5 * 10^8 ((p - 0.1)/0.7) ^ 1.25, where P = ui price
-// c(p) = 5 * 10^8 ((p - min_price)/(max_price - min_price)) ^ 1.25
-fn get_c(
-    amount: u64,
-    bin_step: u16,
-    bin_id: i32,
+    min_bin_id: i32,
+    max_bin_id: i32,
     base_token_decimal: u8,
     quote_token_decimal: u8,
-    min_price: f64,
-    max_price: f64,
-    k: f64,
-) -> u64 {
-    let price_per_lamport = (1.0 + bin_step as f64 / 10000.0).powi(bin_id);
-
-    let current_price =
-        price_per_lamport * 10.0f64.powi(base_token_decimal as i32 - quote_token_decimal as i32);
-
-    let price_range = max_price - min_price;
-    let current_price_delta_from_min = current_price - min_price;
+) -> Vec<BinPrice> {
+    let price_of = |bin_id| {
+        get_ui_price_from_id(
+            bin_step,
+            bin_id,
+            base_token_decimal.into(),
+            quote_token_decimal.into(),
+        )
+    };
 
-    let c = amount as f64 * ((current_price_delta_from_min / price_range).powf(k));
-    c as u64
+    (min_bin_id..max_bin_id)
+        .map(|bin_id| BinPrice {
+            bin_id,
+            price: price_of(bin_id),
+            next_price: price_of(bin_id + 1),
+        })
+        .collect()
 }
 
+/// Split `amount` over the bins from `min_bin_id` to `max_bin_id` following `curve`. The amounts sum to `amount`.
 pub fn generate_amount_for_bins(
     bin_step: u16,
     min_bin_id: i32,
@@ -858,33 +1082,29 @@ pub fn generate_amount_for_bins(
     base_token_decimal: u8,
     quote_token_decimal: u8,
     amount: u64,
-    k: f64,
-) -> Vec<(i32, u64)> {
-    let mut total_amount = 0;
-    let mut bin_amounts = vec![];
-
-    // Last bin is purposely no included because for the last bin, c(last_bin +1) - c(last_bin) will > fund amount
-    for bin_id in min_bin_id..max_bin_id {
-        let bin_amount = get_bin_deposit_amount(
-            amount,
-            bin_step,
-            bin_id,
-            base_token_decimal,
-            quote_token_decimal,
-            min_price,
-            max_price,
-            k,
-        );
-
-        bin_amounts.push((bin_id, bin_amount));
-
-        total_amount += bin_amount;
-    }
+    curve: &impl BinDistribution,
+) -> Result<Vec<(i32, u64)>> {
+    // Last bin is purposely not included because its upper edge is beyond max price
+    let bin_prices = get_bin_prices(
+        bin_step,
+        min_bin_id,
+        max_bin_id,
+        base_token_decimal,
+        quote_token_decimal,
+    );
 
-    assert_eq!(
-        total_amount, amount,
-        "Amount distributed to bins not equals to funding amount"
+    let weights = curve.weights(&bin_prices, min_price, max_price)?;
+    ensure!(
+        weights.len() == bin_prices.len(),
+        "Curve returned {} weights for {} bins",
+        weights.len(),
+        bin_prices.len()
     );
+    let amounts = distribute_amount(amount, &weights)?;
 
-    bin_amounts
+    Ok(bin_prices
+        .iter()
+        .zip(amounts)
+        .map(|(bin, amount)| (bin.bin_id, amount))
+        .collect())
 }
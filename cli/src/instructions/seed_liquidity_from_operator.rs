@@ -4,12 +4,16 @@ use std::ops::Deref;
 use crate::instructions::seed_liquidity::{
     convert_min_max_ui_price_to_min_max_bin_id, create_position_bin_array_if_not_exists, deposit,
     deposit_amount_to_deposit_parameter, generate_amount_for_bins,
-    generate_redistribute_amount_to_position_based_on_ratio,
-    get_number_of_position_required_to_cover_range, get_on_chain_bins_amount_x,
-    get_ui_price_from_id, read_dust_deposit_state, to_wei_amount, write_dust_deposit_state,
+    generate_redistribute_amount_to_position_based_on_ratio, get_bin_prices,
+    get_deposit_instruction, get_number_of_position_required_to_cover_range,
+    get_on_chain_bins_amount_x, get_ui_price_from_id, queue_deposit, read_dust_deposit_state,
+    send_deposits, to_wei_amount, DustDepositState,
 };
+use crate::instructions::seed_plan::{print_seed_plan, SeedPlanOptions, SeedPlanParameters};
 use crate::instructions::utils::get_or_create_ata;
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::journal::Journal;
+use crate::seed_curve::{plot_bin_amounts, SeedCurve};
+use crate::transaction::TransactionConfig;
 use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::solana_sdk::signature::Keypair;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
@@ -37,7 +41,8 @@ async fn get_or_create_position<C: Deref<Target = impl Signer> + Clone>(
     owner: Pubkey,
     fee_owner: Pubkey,
     lock_release_point: u64,
-    transaction_config: RpcSendTransactionConfig,
+    journal: &mut Journal,
+    transaction_config: TransactionConfig,
     compute_unit_price_ix: Option<Instruction>,
 ) -> Result<PositionV2> {
     let (event_authority, _bump) = derive_event_authority_pda();
@@ -119,15 +124,25 @@ async fn get_or_create_position<C: Deref<Target = impl Signer> + Clone>(
             builder = builder.instruction(compute_unit_price_ix);
         }
 
-        builder = builder.instruction(ix).signer(base_keypair);
-        let signature = builder
-            .send_with_spinner_and_config(transaction_config)
-            .await;
-        println!(
-            "Create position: lower bin id {lower_bin_id} upper bin id {upper_bin_id} position {position}. signature {:#?}",
-            signature
-        );
-        signature?;
+        builder = builder.instruction(ix);
+        let landed =
+            move || async move { Ok(program.async_rpc().get_account(&position).await.is_ok()) };
+        if let Some(signature) = journal
+            .run_step(
+                program,
+                &format!("initialize_position:{}", position),
+                landed,
+                builder,
+                &[base_keypair],
+                transaction_config,
+            )
+            .await?
+        {
+            println!(
+                "Create position: lower bin id {lower_bin_id} upper bin id {upper_bin_id} position {position}. signature {}",
+                signature
+            );
+        }
     }
 
     let position_state: PositionV2 = program.account(position).await?;
@@ -146,13 +161,17 @@ pub struct SeedLiquidityByOperatorParameters {
     pub position_owner: Pubkey,
     pub fee_owner: Pubkey,
     pub lock_release_point: u64,
-    pub curvature: f64,
+    pub curve: SeedCurve,
+    /// Render the curve without seeding
+    pub plot: bool,
+    /// Print the seeding plan without seeding
+    pub plan: Option<SeedPlanOptions>,
 }
 
 pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
     params: SeedLiquidityByOperatorParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let SeedLiquidityByOperatorParameters {
@@ -165,21 +184,11 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
         fee_owner,
         lock_release_point,
         base_pubkey,
-        curvature,
+        curve,
+        plot,
+        plan,
     } = params;
 
-    let progress_file_path = format!("{}_progress.json", lb_pair);
-
-    let mut dust_deposit_state = read_dust_deposit_state(&progress_file_path)?;
-    if dust_deposit_state.lb_pair != Pubkey::default() {
-        assert_eq!(
-            dust_deposit_state.lb_pair, lb_pair,
-            "Invalid dust deposit tracking file"
-        );
-    }
-
-    let k = 1.0 / curvature;
-
     // For easier validation during jup launch through .env
     assert_eq!(
         position_base_kp.pubkey(),
@@ -222,6 +231,72 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
 
     assert!(min_bin_id < max_bin_id, "Invalid price range");
 
+    let bins_amount = generate_amount_for_bins(
+        bin_step,
+        min_bin_id,
+        max_bin_id,
+        actual_min_price,
+        actual_max_price,
+        token_mint_base.decimals,
+        token_mint_quote.decimals,
+        fund_amount,
+        &curve,
+    )?;
+
+    if plot {
+        let bin_prices = get_bin_prices(
+            bin_step,
+            min_bin_id,
+            max_bin_id,
+            token_mint_base.decimals,
+            token_mint_quote.decimals,
+        );
+        let amounts = bins_amount
+            .iter()
+            .map(|(_, amount)| *amount)
+            .collect::<Vec<_>>();
+        plot_bin_amounts(&bin_prices, &amounts);
+    }
+
+    if let Some(plan) = plan {
+        let params = SeedPlanParameters {
+            lb_pair,
+            lb_pair_state: &lb_pair_state,
+            token_x_decimals: token_mint_base.decimals,
+            min_price,
+            max_price,
+            actual_min_price,
+            actual_max_price,
+            min_bin_id,
+            max_bin_id,
+            position_number,
+            position_base: position_base_kp.pubkey(),
+            position_owner: position_owner,
+            by_operator: true,
+            bins_amount: &bins_amount,
+            fund_amount,
+        };
+        print_seed_plan(program, params, plan).await?;
+    }
+
+    if plot || plan.is_some() {
+        return Ok(());
+    }
+
+    let mut journal = Journal::open("seed-liquidity-by-operator", lb_pair, transaction_config)?;
+
+    let mut dust_deposit_state: DustDepositState = journal.data()?;
+    if dust_deposit_state.lb_pair == Pubkey::default() {
+        // Progress file of the previous versions
+        dust_deposit_state = read_dust_deposit_state(&format!("{}_progress.json", lb_pair))?;
+    }
+    if dust_deposit_state.lb_pair != Pubkey::default() {
+        assert_eq!(
+            dust_deposit_state.lb_pair, lb_pair,
+            "Invalid dust deposit tracking file"
+        );
+    }
+
     let user_token_x = get_or_create_ata(
         program,
         transaction_config,
@@ -240,18 +315,6 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
     )
     .await?;
 
-    let bins_amount = generate_amount_for_bins(
-        bin_step,
-        min_bin_id,
-        max_bin_id,
-        actual_min_price,
-        actual_max_price,
-        token_mint_base.decimals,
-        token_mint_quote.decimals,
-        fund_amount,
-        k,
-    );
-
     let bins_amount_map: HashMap<i32, u64> = bins_amount
         .iter()
         .map(|(bin_id, amount_x)| (*bin_id, *amount_x))
@@ -259,6 +322,7 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
 
     let width = MAX_BIN_PER_POSITION as i32;
 
+    let mut deposits = vec![];
     for i in 0..position_number {
         let lower_bin_id = min_bin_id + (MAX_BIN_PER_POSITION as i32 * i);
         let upper_bin_id = lower_bin_id + MAX_BIN_PER_POSITION as i32 - 1;
@@ -267,6 +331,7 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
             program,
             lb_pair,
             lower_bin_id,
+            &mut journal,
             transaction_config,
             compute_unit_price.clone(),
         )
@@ -285,6 +350,7 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
             position_owner,
             fee_owner,
             lock_release_point,
+            &mut journal,
             transaction_config,
             compute_unit_price.clone(),
         )
@@ -315,7 +381,14 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
         let (position_liquidity_distribution, deposit_amount_x) =
             deposit_amount_to_deposit_parameter(&bins_amount_map, lower_bin_id, upper_bin_id)?;
 
-        deposit(
+        // The curve put nothing in the bins of the position
+        if deposit_amount_x == 0 {
+            continue;
+        }
+
+        let landed =
+            move || async move { Ok(!program.account::<PositionV2>(position).await?.is_empty()) };
+        let deposit_ix = get_deposit_instruction(
             program,
             position,
             &position_state,
@@ -324,12 +397,27 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
             user_token_y,
             deposit_amount_x,
             position_liquidity_distribution,
-            transaction_config,
-            compute_unit_price.clone(),
+        )?;
+        queue_deposit(
+            program,
+            &mut journal,
+            &mut deposits,
+            format!("deposit:{}", position),
+            landed,
+            deposit_ix,
         )
         .await?;
     }
 
+    send_deposits(
+        program,
+        &mut journal,
+        deposits,
+        transaction_config,
+        compute_unit_price.clone(),
+    )
+    .await?;
+
     // States after principal deposit
     let (leftover, bins_amount_x, total_amount_in_bins_onchain, position_share) =
         if dust_deposit_state.lb_pair.eq(&Pubkey::default()) {
@@ -358,7 +446,7 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
                     .insert(position, position_liquidity_shares);
             }
 
-            write_dust_deposit_state(&progress_file_path, &dust_deposit_state)?;
+            journal.set_data(&dust_deposit_state)?;
 
             (
                 leftover,
@@ -382,6 +470,7 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
             leftover
         );
 
+        let mut deposits = vec![];
         for i in 0..position_number {
             let lower_bin_id = min_bin_id + (MAX_BIN_PER_POSITION as i32 * i);
 
@@ -393,18 +482,15 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
             let position_share_snapshot =
                 position_share.get(&position).context("Missing snapshot")?;
 
-            let mut dust_deposited = false;
-            for (i, share) in position_state.liquidity_shares.iter().enumerate() {
-                let snapshot_share = position_share_snapshot[i];
-                if snapshot_share != *share {
-                    dust_deposited = true;
-                    break;
-                }
-            }
-
-            if dust_deposited {
-                continue;
-            }
+            // The shares changed since the snapshot once the dust is deposited
+            let landed = move || async move {
+                let position_state: PositionV2 = program.account(position).await?;
+                Ok(position_state
+                    .liquidity_shares
+                    .iter()
+                    .zip(position_share_snapshot.iter())
+                    .any(|(share, snapshot_share)| share != snapshot_share))
+            };
 
             // Don't deposit to the last bin because c(last_bin + 1) - c(last_bin) will > amount
             let upper_bin_id = std::cmp::min(position_state.upper_bin_id, max_bin_id - 1);
@@ -423,7 +509,11 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
                     upper_bin_id,
                 )?;
 
-            deposit(
+            if position_redistributed_amount == 0 {
+                continue;
+            }
+
+            let deposit_ix = get_deposit_instruction(
                 program,
                 position,
                 &position_state,
@@ -432,11 +522,26 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
                 user_token_y,
                 position_redistributed_amount,
                 position_liquidity_distribution,
-                transaction_config,
-                compute_unit_price.clone(),
+            )?;
+            queue_deposit(
+                program,
+                &mut journal,
+                &mut deposits,
+                format!("redistribute:{}", position),
+                landed,
+                deposit_ix,
             )
             .await?;
         }
+
+        send_deposits(
+            program,
+            &mut journal,
+            deposits,
+            transaction_config,
+            compute_unit_price.clone(),
+        )
+        .await?;
     }
 
     let (_, total_amount_in_bin_onchain) =
@@ -460,8 +565,16 @@ pub async fn seed_liquidity_by_operator<C: Deref<Target = impl Signer> + Clone>(
 
         assert!(upper_bin_id < max_bin_id, "Funding to last bin id");
 
+        let landed = move || async move {
+            let (_, total_amount_in_bins_onchain) =
+                get_on_chain_bins_amount_x(lb_pair, min_bin_id, max_bin_id, program).await?;
+            Ok(total_amount_in_bins_onchain >= fund_amount)
+        };
         deposit(
             program,
+            &mut journal,
+            "deposit_dust",
+            landed,
             position,
             &position_state,
             &lb_pair_state,
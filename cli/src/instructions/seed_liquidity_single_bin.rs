@@ -1,7 +1,8 @@
 use std::ops::Deref;
 
+use crate::journal::Journal;
+use crate::transaction::TransactionConfig;
 use anchor_client::{
-    solana_client::rpc_config::RpcSendTransactionConfig,
     solana_sdk::{
         compute_budget::ComputeBudgetInstruction, instruction::Instruction, pubkey::Pubkey,
         signature::Keypair, signer::Signer,
@@ -42,7 +43,7 @@ pub struct SeedLiquiditySingleBinParameters {
 pub async fn seed_liquidity_single_bin<C: Deref<Target = impl Signer> + Clone>(
     params: SeedLiquiditySingleBinParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let SeedLiquiditySingleBinParameters {
@@ -238,18 +239,27 @@ pub async fn seed_liquidity_single_bin<C: Deref<Target = impl Signer> + Clone>(
     instructions.push(deposit_ix);
 
     let mut builder = program.request();
-    builder = builder.signer(&position_base_kp);
     builder = instructions
         .into_iter()
         .fold(builder, |builder, ix| builder.instruction(ix));
 
-    let signature = builder
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("{:#?}", signature);
-
-    signature?;
+    // The position is initialized in the same transaction as the deposit
+    let mut journal = Journal::open("seed-liquidity-single-bin", lb_pair, transaction_config)?;
+    let landed =
+        move || async move { Ok(program.async_rpc().get_account(&position).await.is_ok()) };
+    if let Some(signature) = journal
+        .run_step(
+            program,
+            &format!("seed_single_bin:{}", position),
+            landed,
+            builder,
+            &[&position_base_kp],
+            transaction_config,
+        )
+        .await?
+    {
+        println!("{}", signature);
+    }
 
     Ok(())
 }
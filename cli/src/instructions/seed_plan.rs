@@ -0,0 +1,429 @@
+use std::collections::BTreeSet;
+use std::ops::Deref;
+
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::{AccountDeserialize, Space};
+use anchor_spl::associated_token::get_associated_token_address;
+use anchor_spl::token::TokenAccount;
+use anyhow::*;
+use lb_clmm::constants::MAX_BIN_PER_POSITION;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::state::position::PositionV2;
+use lb_clmm::utils::pda::{derive_bin_array_pda, derive_position_pda};
+use serde::Serialize;
+
+use crate::instructions::utils::get_multiple_accounts_chunked;
+use crate::math::to_ui_amount;
+use crate::output::{print_key_values, print_report, print_table, OutputFormat, Report};
+
+/// Base fee of a transaction signature
+const LAMPORTS_PER_SIGNATURE: u64 = 5_000;
+/// Compute unit limit of an instruction of a transaction without a compute unit limit instruction
+const DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT: u64 = 200_000;
+/// Compute unit limit of the seeding deposits
+const DEPOSIT_COMPUTE_UNIT_LIMIT: u64 = 800_000;
+/// Compute unit limit of the bin array initializations
+const INITIALIZE_BIN_ARRAY_COMPUTE_UNIT_LIMIT: u64 = 400_000;
+
+/// Print the seeding plan instead of seeding.
+#[derive(Debug, Clone, Copy)]
+pub struct SeedPlanOptions {
+    /// `--priority-fee`, in micro lamports per compute unit
+    pub compute_unit_price: u64,
+    pub output: OutputFormat,
+}
+
+/// Seeding resolved by a `seed-liquidity*` command, before any transaction is sent
+pub struct SeedPlanParameters<'a> {
+    pub lb_pair: Pubkey,
+    pub lb_pair_state: &'a LbPair,
+    pub token_x_decimals: u8,
+    pub min_price: f64,
+    pub max_price: f64,
+    pub actual_min_price: f64,
+    pub actual_max_price: f64,
+    pub min_bin_id: i32,
+    pub max_bin_id: i32,
+    pub position_number: i32,
+    pub position_base: Pubkey,
+    pub position_owner: Pubkey,
+    /// Positions are initialized by the wallet as operator, which funds the token X account of the owner
+    pub by_operator: bool,
+    pub bins_amount: &'a [(i32, u64)],
+    pub fund_amount: u64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeedPlanPosition {
+    pub address: String,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub amount_x: u64,
+    pub ui_amount_x: f64,
+    /// Already initialized
+    pub exists: bool,
+    /// Already holds liquidity. Filled positions are not deposited to again.
+    pub filled: bool,
+}
+
+#[derive(Debug, Serialize)]
+pub struct SeedPlanReport {
+    pub lb_pair: String,
+    pub token_x_mint: String,
+    pub requested_min_price: f64,
+    pub requested_max_price: f64,
+    /// UI price of the bins the requested prices resolve to
+    pub actual_min_price: f64,
+    pub actual_max_price: f64,
+    pub min_bin_id: i32,
+    pub max_bin_id: i32,
+    pub amount_x: u64,
+    pub ui_amount_x: f64,
+    pub positions: Vec<SeedPlanPosition>,
+    pub positions_to_create: Vec<String>,
+    pub bin_arrays_to_create: Vec<String>,
+    pub token_accounts_to_create: Vec<String>,
+    pub position_rent_lamports: u64,
+    pub bin_array_rent_lamports: u64,
+    pub token_account_rent_lamports: u64,
+    /// Transactions of the principal deposit, counting a transaction per position deposit. Deposits to partly
+    /// filled positions may be packed into fewer transactions, especially with `--lookup-table`.
+    pub transaction_count: u64,
+    /// Upper bound of the transactions redistributing the rounding dust after the principal deposit
+    pub max_dust_transaction_count: u64,
+    /// `--priority-fee`, in micro lamports per compute unit
+    pub compute_unit_price: u64,
+    /// Signature and priority fees of the principal deposit
+    pub fee_lamports: u64,
+    /// Upper bound of the fees of the dust redistribution
+    pub max_dust_fee_lamports: u64,
+    /// Rent, fees and dust fees
+    pub max_total_cost_lamports: u64,
+}
+
+impl Report for SeedPlanReport {
+    const COMMAND: &'static str = "seed-plan";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        let sol = |lamports: u64| format!("{} SOL", to_ui_amount(lamports, 9));
+
+        print_key_values(&[
+            ("Pair", self.lb_pair.clone()),
+            (
+                "Requested price range",
+                format!(
+                    "{} - {}",
+                    self.requested_min_price, self.requested_max_price
+                ),
+            ),
+            (
+                "Actual price range",
+                format!("{} - {}", self.actual_min_price, self.actual_max_price),
+            ),
+            (
+                "Bin range",
+                format!("{} - {}", self.min_bin_id, self.max_bin_id),
+            ),
+            ("Amount X", self.ui_amount_x.to_string()),
+            (
+                "Positions",
+                format!(
+                    "{} ({} to create)",
+                    self.positions.len(),
+                    self.positions_to_create.len()
+                ),
+            ),
+            (
+                "Bin arrays to create",
+                self.bin_arrays_to_create.len().to_string(),
+            ),
+            (
+                "Token accounts to create",
+                self.token_accounts_to_create.len().to_string(),
+            ),
+            ("Position rent", sol(self.position_rent_lamports)),
+            ("Bin array rent", sol(self.bin_array_rent_lamports)),
+            ("Token account rent", sol(self.token_account_rent_lamports)),
+            (
+                "Transactions",
+                format!(
+                    "{} (up to {} more for the dust)",
+                    self.transaction_count, self.max_dust_transaction_count
+                ),
+            ),
+            (
+                "Fees",
+                format!(
+                    "{} (up to {} more for the dust)",
+                    sol(self.fee_lamports),
+                    sol(self.max_dust_fee_lamports)
+                ),
+            ),
+            ("Max total cost", sol(self.max_total_cost_lamports)),
+        ]);
+
+        let rows = self
+            .positions
+            .iter()
+            .map(|position| {
+                let status = match (position.exists, position.filled) {
+                    (_, true) => "Filled",
+                    (true, false) => "Created",
+                    (false, false) => "To create",
+                };
+                vec![
+                    position.address.clone(),
+                    format!("{} - {}", position.lower_bin_id, position.upper_bin_id),
+                    position.ui_amount_x.to_string(),
+                    status.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        println!();
+        print_table(&["Position", "Bins", "Amount X", "Status"], &rows);
+    }
+}
+
+/// Lamports paid for a transaction of `signature_count` signatures and `compute_unit_limit` compute units.
+fn get_transaction_fee(
+    signature_count: u64,
+    compute_unit_limit: u64,
+    compute_unit_price: u64,
+) -> u64 {
+    let priority_fee = u128::from(compute_unit_limit) * u128::from(compute_unit_price);
+    signature_count * LAMPORTS_PER_SIGNATURE + priority_fee.div_ceil(1_000_000) as u64
+}
+
+pub async fn get_seed_plan<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    params: SeedPlanParameters<'_>,
+    compute_unit_price: u64,
+) -> Result<SeedPlanReport> {
+    let SeedPlanParameters {
+        lb_pair,
+        lb_pair_state,
+        token_x_decimals,
+        min_price,
+        max_price,
+        actual_min_price,
+        actual_max_price,
+        min_bin_id,
+        max_bin_id,
+        position_number,
+        position_base,
+        position_owner,
+        by_operator,
+        bins_amount,
+        fund_amount,
+    } = params;
+
+    let payer = program.payer();
+    let width = MAX_BIN_PER_POSITION as i32;
+
+    let position_ranges = (0..position_number)
+        .map(|i| {
+            let lower_bin_id = min_bin_id + width * i;
+            (lower_bin_id, lower_bin_id + width - 1)
+        })
+        .collect::<Vec<_>>();
+    let position_keys = position_ranges
+        .iter()
+        .map(|(lower_bin_id, _)| {
+            derive_position_pda(lb_pair, position_base, *lower_bin_id, width).0
+        })
+        .collect::<Vec<_>>();
+    let position_accounts = get_multiple_accounts_chunked(program, &position_keys).await?;
+
+    // Bin arrays are initialized per position, the lower bin array of the position and the next one
+    let mut bin_array_indexes = BTreeSet::new();
+    for (lower_bin_id, _) in position_ranges.iter() {
+        let idx = BinArray::bin_id_to_bin_array_index(*lower_bin_id)?;
+        bin_array_indexes.insert(idx);
+        bin_array_indexes.insert(idx + 1);
+    }
+    let bin_array_keys = bin_array_indexes
+        .iter()
+        .map(|idx| derive_bin_array_pda(lb_pair, (*idx).into()).0)
+        .collect::<Vec<_>>();
+    let missing_bin_arrays = bin_array_keys
+        .iter()
+        .zip(get_multiple_accounts_chunked(program, &bin_array_keys).await?)
+        .filter(|(_, account)| account.is_none())
+        .map(|(key, _)| *key)
+        .collect::<BTreeSet<_>>();
+
+    let mut token_account_keys = vec![
+        get_associated_token_address(&payer, &lb_pair_state.token_x_mint),
+        get_associated_token_address(&payer, &lb_pair_state.token_y_mint),
+    ];
+    if by_operator && position_owner != payer {
+        token_account_keys.push(get_associated_token_address(
+            &position_owner,
+            &lb_pair_state.token_x_mint,
+        ));
+    }
+    let missing_token_accounts = token_account_keys
+        .iter()
+        .zip(get_multiple_accounts_chunked(program, &token_account_keys).await?)
+        .filter(|(_, account)| account.is_none())
+        .map(|(key, _)| *key)
+        .collect::<Vec<_>>();
+
+    let rpc_client = program.async_rpc();
+    let position_rent = rpc_client
+        .get_minimum_balance_for_rent_exemption(8 + PositionV2::INIT_SPACE)
+        .await?;
+    let bin_array_rent = rpc_client
+        .get_minimum_balance_for_rent_exemption(8 + std::mem::size_of::<BinArray>())
+        .await?;
+    let token_account_rent = rpc_client
+        .get_minimum_balance_for_rent_exemption(TokenAccount::LEN)
+        .await?;
+
+    // (signature count, compute unit limit) of the transactions sent in order by the seeding
+    let mut transactions = vec![];
+
+    // The wallet token accounts are created one per transaction. The owner token account of operator
+    // seeding is created along the first position.
+    for key in missing_token_accounts.iter() {
+        if token_account_keys[..2].contains(key) {
+            transactions.push((1, DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT));
+        }
+    }
+
+    let mut positions = vec![];
+    let mut positions_to_create = vec![];
+    let mut created_bin_arrays = BTreeSet::new();
+    for (((lower_bin_id, upper_bin_id), key), account) in position_ranges
+        .iter()
+        .zip(position_keys.iter())
+        .zip(position_accounts)
+    {
+        let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(*lower_bin_id)?;
+        let position_missing_bin_arrays = [lower_bin_array_idx, lower_bin_array_idx + 1]
+            .iter()
+            .map(|idx| derive_bin_array_pda(lb_pair, (*idx).into()).0)
+            .filter(|key| missing_bin_arrays.contains(key) && created_bin_arrays.insert(*key))
+            .count();
+        if position_missing_bin_arrays > 0 {
+            transactions.push((1, INITIALIZE_BIN_ARRAY_COMPUTE_UNIT_LIMIT));
+        }
+
+        let exists = account.is_some();
+        let filled = match account {
+            Some(account) => !PositionV2::try_deserialize(&mut account.data.as_ref())?.is_empty(),
+            None => false,
+        };
+        if !exists {
+            positions_to_create.push(key.to_string());
+            if by_operator {
+                // Owner token account creation, proof of ownership transfer and the initialization
+                transactions.push((2, 3 * DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT));
+            } else {
+                let signers = [payer, position_base, position_owner]
+                    .into_iter()
+                    .collect::<BTreeSet<_>>();
+                transactions.push((signers.len() as u64, DEFAULT_INSTRUCTION_COMPUTE_UNIT_LIMIT));
+            }
+        }
+
+        // The last bin of the range is not deposited to
+        let deposit_upper_bin_id = std::cmp::min(*upper_bin_id, max_bin_id - 1);
+        let amount_x = bins_amount
+            .iter()
+            .filter(|(bin_id, _)| (*lower_bin_id..=deposit_upper_bin_id).contains(bin_id))
+            .map(|(_, amount)| *amount)
+            .sum::<u64>();
+        if !filled && amount_x > 0 {
+            transactions.push((1, DEPOSIT_COMPUTE_UNIT_LIMIT));
+        }
+
+        positions.push(SeedPlanPosition {
+            address: key.to_string(),
+            lower_bin_id: *lower_bin_id,
+            upper_bin_id: *upper_bin_id,
+            amount_x,
+            ui_amount_x: to_ui_amount(amount_x, token_x_decimals),
+            exists,
+            filled,
+        });
+    }
+
+    // One redistribution deposit per position, then the deposit of the remaining dust to the last bin
+    let max_dust_transaction_count = position_number as u64 + 1;
+
+    let fee_lamports = transactions
+        .iter()
+        .map(|(signature_count, compute_unit_limit)| {
+            get_transaction_fee(*signature_count, *compute_unit_limit, compute_unit_price)
+        })
+        .sum::<u64>();
+    let max_dust_fee_lamports = max_dust_transaction_count
+        * get_transaction_fee(1, DEPOSIT_COMPUTE_UNIT_LIMIT, compute_unit_price);
+
+    let position_rent_lamports = positions_to_create.len() as u64 * position_rent;
+    let bin_array_rent_lamports = missing_bin_arrays.len() as u64 * bin_array_rent;
+    let token_account_rent_lamports = missing_token_accounts.len() as u64 * token_account_rent;
+
+    Ok(SeedPlanReport {
+        lb_pair: lb_pair.to_string(),
+        token_x_mint: lb_pair_state.token_x_mint.to_string(),
+        requested_min_price: min_price,
+        requested_max_price: max_price,
+        actual_min_price,
+        actual_max_price,
+        min_bin_id,
+        max_bin_id,
+        amount_x: fund_amount,
+        ui_amount_x: to_ui_amount(fund_amount, token_x_decimals),
+        positions,
+        positions_to_create,
+        bin_arrays_to_create: missing_bin_arrays
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+        token_accounts_to_create: missing_token_accounts
+            .iter()
+            .map(|key| key.to_string())
+            .collect(),
+        position_rent_lamports,
+        bin_array_rent_lamports,
+        token_account_rent_lamports,
+        transaction_count: transactions.len() as u64,
+        max_dust_transaction_count,
+        compute_unit_price,
+        fee_lamports,
+        max_dust_fee_lamports,
+        max_total_cost_lamports: position_rent_lamports
+            + bin_array_rent_lamports
+            + token_account_rent_lamports
+            + fee_lamports
+            + max_dust_fee_lamports,
+    })
+}
+
+/// Print the plan of a `seed-liquidity*` command.
+pub async fn print_seed_plan<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    params: SeedPlanParameters<'_>,
+    options: SeedPlanOptions,
+) -> Result<()> {
+    let report = get_seed_plan(program, params, options.compute_unit_price).await?;
+    print_report(options.output, &report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_transaction_fee() {
+        assert_eq!(get_transaction_fee(2, 200_000, 0), 10_000);
+        // 800_000 CU at 1 micro lamport
+        assert_eq!(get_transaction_fee(1, 800_000, 1), 5_001);
+        assert_eq!(get_transaction_fee(1, 800_000, 1_000_000), 5_000 + 800_000);
+    }
+}
@@ -1,6 +1,6 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 
 use anchor_lang::solana_program::instruction::Instruction;
@@ -16,7 +16,7 @@ pub struct SetActivationPointParam {
 pub async fn set_activation_point<C: Deref<Target = impl Signer> + Clone>(
     params: SetActivationPointParam,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<()> {
     let SetActivationPointParam {
         lb_pair,
@@ -37,15 +37,10 @@ pub async fn set_activation_point<C: Deref<Target = impl Signer> + Clone>(
         program_id: lb_clmm::ID,
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .instruction(set_activation_point_ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Set activation point. Signature: {:#?}", signature);
-
-    signature?;
+    let request_builder = program.request().instruction(set_activation_point_ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Set activation point. Signature: {}", signature);
+    }
 
     Ok(())
 }
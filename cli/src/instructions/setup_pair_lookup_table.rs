@@ -0,0 +1,114 @@
+use std::ops::Deref;
+
+use crate::transaction::{send_or_simulate, TransactionConfig, TransactionMode};
+use anchor_client::solana_sdk::address_lookup_table::instruction::{
+    create_lookup_table, extend_lookup_table,
+};
+use anchor_client::solana_sdk::address_lookup_table::state::{
+    AddressLookupTable, LOOKUP_TABLE_MAX_ADDRESSES,
+};
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use commons::lookup_table::{get_missing_addresses, get_pair_lookup_table_addresses};
+use lb_clmm::state::lb_pair::LbPair;
+
+/// Addresses added by an extend instruction, so it fits in a legacy transaction
+const EXTEND_LOOKUP_TABLE_CHUNK_SIZE: usize = 20;
+
+#[derive(Debug)]
+pub struct SetupPairLookupTableParameters {
+    pub lb_pair: Pubkey,
+    /// Lookup table to extend. A new one is created when `None`.
+    pub table: Option<Pubkey>,
+    pub bin_array_radius: i64,
+}
+
+pub async fn setup_pair_lookup_table<C: Deref<Target = impl Signer> + Clone>(
+    params: SetupPairLookupTableParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+) -> Result<()> {
+    let SetupPairLookupTableParameters {
+        lb_pair,
+        table,
+        bin_array_radius,
+    } = params;
+
+    let lb_pair_state: LbPair = program.account(lb_pair).await?;
+    let addresses = get_pair_lookup_table_addresses(lb_pair, &lb_pair_state, bin_array_radius)?;
+
+    let rpc_client = program.async_rpc();
+    let authority = program.payer();
+
+    let (table, table_addresses, mut create_ix) = match table {
+        Some(table) => {
+            let account = rpc_client.get_account(&table).await?;
+            let lookup_table = AddressLookupTable::deserialize(&account.data)
+                .map_err(|err| anyhow!("{} is not an address lookup table: {}", table, err))?;
+            ensure!(
+                lookup_table.meta.authority == Some(authority),
+                "Lookup table {} is not extendable by {}",
+                table,
+                authority
+            );
+            ensure!(
+                lookup_table.meta.deactivation_slot == u64::MAX,
+                "Lookup table {} is deactivated",
+                table
+            );
+            (table, lookup_table.addresses.to_vec(), None)
+        }
+        None => {
+            let recent_slot = rpc_client
+                .get_slot_with_commitment(CommitmentConfig::finalized())
+                .await?;
+            let (create_ix, table) = create_lookup_table(authority, authority, recent_slot);
+            (table, vec![], Some(create_ix))
+        }
+    };
+
+    let missing_addresses = get_missing_addresses(&table_addresses, &addresses);
+    if missing_addresses.is_empty() {
+        println!("Lookup table {table} already has the addresses of pair {lb_pair}");
+        return Ok(());
+    }
+    ensure!(
+        table_addresses.len() + missing_addresses.len() <= LOOKUP_TABLE_MAX_ADDRESSES,
+        "Lookup table {} has {} addresses and can't take {} more",
+        table,
+        table_addresses.len(),
+        missing_addresses.len()
+    );
+
+    for chunk in missing_addresses.chunks(EXTEND_LOOKUP_TABLE_CHUNK_SIZE) {
+        let creates_table = create_ix.is_some();
+        let mut request_builder = program.request();
+        if let Some(create_ix) = create_ix.take() {
+            request_builder = request_builder.instruction(create_ix);
+        }
+        let request_builder = request_builder.instruction(extend_lookup_table(
+            table,
+            authority,
+            Some(authority),
+            chunk.to_vec(),
+        ));
+
+        if let Some(signature) =
+            send_or_simulate(program, request_builder, transaction_config).await?
+        {
+            println!(
+                "Extend lookup table {table} with {} addresses. Signature: {signature}",
+                chunk.len()
+            );
+        } else if creates_table && matches!(transaction_config.mode, TransactionMode::Simulate) {
+            println!("The next transactions extend the lookup table created by this one, and can't be simulated before it exists");
+            return Ok(());
+        }
+    }
+
+    println!("Lookup table {table} of pair {lb_pair}. The addresses added are usable from the next slot, with --lookup-table {table}");
+
+    Ok(())
+}
@@ -7,23 +7,106 @@ use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Prog
 use anchor_spl::token::Mint;
 use anyhow::*;
 
-use lb_clmm::constants::FEE_PRECISION;
-use lb_clmm::math::price_math::get_price_from_id;
 use lb_clmm::state::bin::BinArray;
 use lb_clmm::state::lb_pair::LbPair;
-use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
-use rust_decimal::Decimal;
+use serde::Serialize;
+
+use crate::instructions::utils::{
+    get_clock, get_reward_mint_decimals, get_reward_prices_in_token_y, print_reward_reports,
+    RewardReport,
+};
+use crate::math::{fee_rate_to_fee_pct, get_ui_price_from_id, to_ui_amount};
+use crate::output::{print_key_values, print_report, print_table, OutputFormat, Report};
+use commons::rewards::project_pair_rewards;
+
+#[derive(Debug, Serialize)]
+pub struct BinReport {
+    pub bin_id: i32,
+    /// UI price of token X in token Y
+    pub price: f64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub ui_amount_x: f64,
+    pub ui_amount_y: f64,
+}
 
-use crate::math::{price_per_lamport_to_price_per_token, q64x64_price_to_decimal};
+#[derive(Debug, Serialize)]
+pub struct PairReport {
+    pub address: String,
+    pub token_x_mint: String,
+    pub token_y_mint: String,
+    pub token_x_decimals: u8,
+    pub token_y_decimals: u8,
+    pub reserve_x: String,
+    pub reserve_y: String,
+    pub bin_step: u16,
+    pub active_id: i32,
+    /// UI price of token X in token Y at the active bin
+    pub current_price: f64,
+    pub base_fee_pct: f64,
+    pub variable_fee_pct: f64,
+    pub current_fee_pct: f64,
+    pub protocol_share_bps: u16,
+    pub ui_protocol_fee_x: f64,
+    pub ui_protocol_fee_y: f64,
+    pub status: u8,
+    pub pair_type: u8,
+    pub activation_type: u8,
+    pub activation_point: u64,
+    /// Bins holding liquidity, ordered by bin id
+    pub bins: Vec<BinReport>,
+    pub rewards: Vec<RewardReport>,
+}
 
-fn fee_rate_to_fee_pct(fee_rate: u128) -> Option<Decimal> {
-    let fee_rate = Decimal::from_u128(fee_rate)?.checked_div(Decimal::from(FEE_PRECISION))?;
-    fee_rate.checked_mul(Decimal::ONE_HUNDRED)
+impl Report for PairReport {
+    const COMMAND: &'static str = "show-pair";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        print_key_values(&[
+            ("Pair", self.address.clone()),
+            ("Token X", self.token_x_mint.clone()),
+            ("Token Y", self.token_y_mint.clone()),
+            ("Bin step", self.bin_step.to_string()),
+            ("Active bin", self.active_id.to_string()),
+            ("Current price", self.current_price.to_string()),
+            ("Base fee rate", format!("{}%", self.base_fee_pct)),
+            ("Volatile fee rate", format!("{}%", self.variable_fee_pct)),
+            ("Current fee rate", format!("{}%", self.current_fee_pct)),
+            (
+                "Protocol fee",
+                format!("{} X, {} Y", self.ui_protocol_fee_x, self.ui_protocol_fee_y),
+            ),
+            ("Status", self.status.to_string()),
+            ("Pair type", self.pair_type.to_string()),
+            ("Activation type", self.activation_type.to_string()),
+            ("Activation point", self.activation_point.to_string()),
+        ]);
+
+        let rows = self
+            .bins
+            .iter()
+            .map(|bin| {
+                vec![
+                    bin.bin_id.to_string(),
+                    bin.price.to_string(),
+                    bin.ui_amount_x.to_string(),
+                    bin.ui_amount_y.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+
+        println!();
+        print_table(&["Bin", "Price", "Amount X", "Amount Y"], &rows);
+        print_reward_reports(&self.rewards);
+    }
 }
 
 pub async fn show_pair<C: Deref<Target = impl Signer> + Clone>(
     lb_pair: Pubkey,
+    reward_prices: Vec<f64>,
     program: &Program<C>,
+    output: OutputFormat,
 ) -> Result<()> {
     let lb_pair_state: LbPair = program.account(lb_pair).await?;
 
@@ -31,50 +114,98 @@ pub async fn show_pair<C: Deref<Target = impl Signer> + Clone>(
     let mut bin_arrays: Vec<(Pubkey, BinArray)> = program.accounts(vec![lb_pair_filter]).await?;
     bin_arrays.sort_by(|a, b| a.1.index.cmp(&b.1.index));
 
-    println!("{:#?}", lb_pair_state);
+    let x_mint: Mint = program.account(lb_pair_state.token_x_mint).await?;
+    let y_mint: Mint = program.account(lb_pair_state.token_y_mint).await?;
 
-    for (_, bin_array) in bin_arrays {
+    let mut bins = vec![];
+    for (_, bin_array) in bin_arrays.iter() {
         let (mut lower_bin_id, _) =
             BinArray::get_bin_array_lower_upper_bin_id(bin_array.index as i32)?;
         for bin in bin_array.bins.iter() {
             let total_amount = bin.amount_x + bin.amount_y;
             if total_amount > 0 {
-                println!(
-                    "Bin: {}, X: {}, Y: {}",
-                    lower_bin_id, bin.amount_x, bin.amount_y
-                );
+                bins.push(BinReport {
+                    bin_id: lower_bin_id,
+                    price: get_ui_price_from_id(
+                        lower_bin_id,
+                        lb_pair_state.bin_step,
+                        x_mint.decimals,
+                        y_mint.decimals,
+                    )?,
+                    amount_x: bin.amount_x,
+                    amount_y: bin.amount_y,
+                    ui_amount_x: to_ui_amount(bin.amount_x, x_mint.decimals),
+                    ui_amount_y: to_ui_amount(bin.amount_y, y_mint.decimals),
+                });
             }
             lower_bin_id += 1;
         }
     }
 
-    let x_mint: Mint = program.account(lb_pair_state.token_x_mint).await?;
-    let y_mint: Mint = program.account(lb_pair_state.token_y_mint).await?;
-
-    let q64x64_price = get_price_from_id(lb_pair_state.active_id, lb_pair_state.bin_step)?;
-    let decimal_price_per_lamport =
-        q64x64_price_to_decimal(q64x64_price).context("q64x64 price to decimal overflow")?;
-
-    let token_price = price_per_lamport_to_price_per_token(
-        decimal_price_per_lamport
-            .to_f64()
-            .context("Decimal conversion to f64 fail")?,
+    let current_price = get_ui_price_from_id(
+        lb_pair_state.active_id,
+        lb_pair_state.bin_step,
         x_mint.decimals,
         y_mint.decimals,
-    )
-    .context("price_per_lamport_to_price_per_token overflow")?;
+    )?;
 
-    let base_fee_rate = fee_rate_to_fee_pct(lb_pair_state.get_total_fee()?)
-        .context("get_total_fee convert to percentage overflow")?;
-    let variable_fee_rate = fee_rate_to_fee_pct(lb_pair_state.get_variable_fee()?)
-        .context("get_total_fee convert to percentage overflow")?;
-    let current_fee_rate = fee_rate_to_fee_pct(lb_pair_state.get_total_fee()?)
+    let base_fee_pct = fee_rate_to_fee_pct(lb_pair_state.get_base_fee()?)
+        .context("get_base_fee convert to percentage overflow")?;
+    let variable_fee_pct = fee_rate_to_fee_pct(lb_pair_state.get_variable_fee()?)
+        .context("get_variable_fee convert to percentage overflow")?;
+    let current_fee_pct = fee_rate_to_fee_pct(lb_pair_state.get_total_fee()?)
         .context("get_total_fee convert to percentage overflow")?;
 
-    println!("Current price {}", token_price);
-    println!("Base fee rate {}%", base_fee_rate);
-    println!("Volatile fee rate {}%", variable_fee_rate);
-    println!("Current fee rate {}%", current_fee_rate);
+    let active_bin_array_idx = BinArray::bin_id_to_bin_array_index(lb_pair_state.active_id)?;
+    let active_bin = bin_arrays
+        .iter()
+        .find(|(_, bin_array)| bin_array.index == active_bin_array_idx as i64)
+        .map(|(_, bin_array)| bin_array.get_bin(lb_pair_state.active_id))
+        .transpose()?;
+
+    let mut rewards = vec![];
+    if let Some(active_bin) = active_bin {
+        let reward_prices =
+            get_reward_prices_in_token_y(program, &lb_pair_state, &reward_prices).await?;
+        let clock = get_clock(program).await?;
+
+        let reward_projections = project_pair_rewards(
+            &lb_pair_state,
+            active_bin,
+            Some(&reward_prices),
+            clock.unix_timestamp as u64,
+        )?;
+        let reward_decimals = get_reward_mint_decimals(program, &lb_pair_state).await?;
+        rewards = reward_projections
+            .iter()
+            .map(|projection| RewardReport::new(projection, &reward_decimals))
+            .collect();
+    }
 
-    Ok(())
+    let report = PairReport {
+        address: lb_pair.to_string(),
+        token_x_mint: lb_pair_state.token_x_mint.to_string(),
+        token_y_mint: lb_pair_state.token_y_mint.to_string(),
+        token_x_decimals: x_mint.decimals,
+        token_y_decimals: y_mint.decimals,
+        reserve_x: lb_pair_state.reserve_x.to_string(),
+        reserve_y: lb_pair_state.reserve_y.to_string(),
+        bin_step: lb_pair_state.bin_step,
+        active_id: lb_pair_state.active_id,
+        current_price,
+        base_fee_pct,
+        variable_fee_pct,
+        current_fee_pct,
+        protocol_share_bps: lb_pair_state.parameters.protocol_share,
+        ui_protocol_fee_x: to_ui_amount(lb_pair_state.protocol_fee.amount_x, x_mint.decimals),
+        ui_protocol_fee_y: to_ui_amount(lb_pair_state.protocol_fee.amount_y, y_mint.decimals),
+        status: lb_pair_state.status,
+        pair_type: lb_pair_state.pair_type,
+        activation_type: lb_pair_state.activation_type,
+        activation_point: lb_pair_state.activation_point,
+        bins,
+        rewards,
+    };
+
+    print_report(output, &report)
 }
@@ -0,0 +1,359 @@
+use std::ops::Deref;
+
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_lang::{AccountDeserialize, Discriminator};
+use anchor_spl::token::Mint;
+use anyhow::*;
+use commons::position::{
+    deserialize_bin_array, get_position_amounts, get_position_pending_fees,
+    get_position_pending_rewards,
+};
+use commons::rewards::project_position_rewards;
+use lb_clmm::pair_action_access::ActivationType;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::state::position::{Position, PositionV2};
+use serde::Serialize;
+
+use crate::instructions::utils::{
+    derive_position_bin_arrays, get_clock, get_reward_mint_decimals, get_reward_prices_in_token_y,
+    migrate_legacy_position, print_reward_reports, RewardReport,
+};
+use crate::math::{get_ui_price_from_id, to_ui_amount};
+use crate::output::{
+    format_optional, print_key_values, print_report, print_table, OutputFormat, Report,
+};
+
+#[derive(Debug, Serialize)]
+pub struct PositionBinReport {
+    pub bin_id: i32,
+    /// UI price of token X in token Y
+    pub price: f64,
+    pub liquidity_share: u128,
+    /// Share of the bin liquidity owned by the position
+    pub liquidity_share_pct: f64,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub ui_amount_x: f64,
+    pub ui_amount_y: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PositionRewardReport {
+    pub reward_index: usize,
+    pub mint: String,
+    pub pending: u64,
+    pub ui_pending: f64,
+    pub claimed: u64,
+    pub ui_claimed: f64,
+}
+
+#[derive(Debug, Serialize)]
+pub struct PositionReport {
+    pub address: String,
+    /// 1 for the legacy `Position` account, 2 for `PositionV2`
+    pub version: u8,
+    pub lb_pair: String,
+    pub owner: String,
+    /// None when the position has no operator, or for legacy positions
+    pub operator: Option<String>,
+    /// Receiver of the fees when it isn't the owner. None for legacy positions.
+    pub fee_owner: Option<String>,
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    /// UI price of token X in token Y of the lower bin
+    pub lower_price: f64,
+    /// UI price of token X in token Y of the upper bin
+    pub upper_price: f64,
+    pub active_id: i32,
+    /// Whether the active bin is within the position range
+    pub in_range: bool,
+    /// Slot or timestamp, depending on the pair activation type, from which the liquidity can be withdrawn
+    pub lock_release_point: u64,
+    pub liquidity_locked: bool,
+    pub amount_x: u64,
+    pub amount_y: u64,
+    pub ui_amount_x: f64,
+    pub ui_amount_y: f64,
+    pub fee_x_pending: u64,
+    pub fee_y_pending: u64,
+    pub ui_fee_x_pending: f64,
+    pub ui_fee_y_pending: f64,
+    pub total_claimed_fee_x: u64,
+    pub total_claimed_fee_y: u64,
+    pub ui_total_claimed_fee_x: f64,
+    pub ui_total_claimed_fee_y: f64,
+    /// Bins of the position holding liquidity
+    pub bins: Vec<PositionBinReport>,
+    /// Pending and claimed amount of the initialized rewards
+    pub reward_balances: Vec<PositionRewardReport>,
+    pub rewards: Vec<RewardReport>,
+}
+
+impl Report for PositionReport {
+    const COMMAND: &'static str = "show-position";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        print_key_values(&[
+            ("Position", self.address.clone()),
+            ("Version", self.version.to_string()),
+            ("Pair", self.lb_pair.clone()),
+            ("Owner", self.owner.clone()),
+            ("Operator", format_optional(self.operator.as_ref())),
+            ("Fee owner", format_optional(self.fee_owner.as_ref())),
+            (
+                "Bin range",
+                format!("{} - {}", self.lower_bin_id, self.upper_bin_id),
+            ),
+            (
+                "Price range",
+                format!("{} - {}", self.lower_price, self.upper_price),
+            ),
+            (
+                "In range",
+                format!("{} (active bin {})", self.in_range, self.active_id),
+            ),
+            (
+                "Liquidity locked",
+                format!(
+                    "{} (release point {})",
+                    self.liquidity_locked, self.lock_release_point
+                ),
+            ),
+            (
+                "Amount",
+                format!("{} X, {} Y", self.ui_amount_x, self.ui_amount_y),
+            ),
+            (
+                "Pending fee",
+                format!("{} X, {} Y", self.ui_fee_x_pending, self.ui_fee_y_pending),
+            ),
+            (
+                "Claimed fee",
+                format!(
+                    "{} X, {} Y",
+                    self.ui_total_claimed_fee_x, self.ui_total_claimed_fee_y
+                ),
+            ),
+        ]);
+
+        let rows = self
+            .bins
+            .iter()
+            .map(|bin| {
+                vec![
+                    bin.bin_id.to_string(),
+                    bin.price.to_string(),
+                    format!("{:.4}%", bin.liquidity_share_pct),
+                    bin.ui_amount_x.to_string(),
+                    bin.ui_amount_y.to_string(),
+                ]
+            })
+            .collect::<Vec<_>>();
+        println!();
+        print_table(&["Bin", "Price", "Share", "Amount X", "Amount Y"], &rows);
+
+        if !self.reward_balances.is_empty() {
+            let rows = self
+                .reward_balances
+                .iter()
+                .map(|reward| {
+                    vec![
+                        reward.reward_index.to_string(),
+                        reward.mint.clone(),
+                        reward.ui_pending.to_string(),
+                        reward.ui_claimed.to_string(),
+                    ]
+                })
+                .collect::<Vec<_>>();
+            println!();
+            print_table(&["Reward", "Mint", "Pending", "Claimed"], &rows);
+        }
+
+        print_reward_reports(&self.rewards);
+    }
+}
+
+/// Deserialize a position account of any version. Legacy positions are converted to `PositionV2`
+/// the same way the program migrates them.
+fn deserialize_position(data: &[u8]) -> Result<(u8, PositionV2)> {
+    if data.starts_with(&PositionV2::discriminator()) {
+        let position = PositionV2::try_deserialize(&mut &data[..])?;
+        return Ok((2, position));
+    }
+
+    if data.starts_with(&Position::discriminator()) {
+        let position = Position::try_deserialize(&mut &data[..])?;
+        return Ok((1, migrate_legacy_position(position)?));
+    }
+
+    Err(anyhow!("Not a position account"))
+}
+
+pub async fn show_position<C: Deref<Target = impl Signer> + Clone>(
+    position: Pubkey,
+    reward_prices: Vec<f64>,
+    program: &Program<C>,
+    output: OutputFormat,
+) -> Result<()> {
+    let position_account = program.async_rpc().get_account(&position).await?;
+    let (version, position_state) = deserialize_position(&position_account.data)
+        .with_context(|| format!("Failed to deserialize position {}", position))?;
+    let lb_pair_state: LbPair = program.account(position_state.lb_pair).await?;
+
+    let x_mint: Mint = program.account(lb_pair_state.token_x_mint).await?;
+    let y_mint: Mint = program.account(lb_pair_state.token_y_mint).await?;
+
+    let bin_array_pubkeys =
+        derive_position_bin_arrays(position_state.lb_pair, position_state.lower_bin_id)?;
+    let bin_arrays = program
+        .async_rpc()
+        .get_multiple_accounts(&bin_array_pubkeys)
+        .await?
+        .into_iter()
+        .flatten()
+        .map(|account| deserialize_bin_array(&account.data))
+        .collect::<Result<Vec<_>>>()?;
+
+    let clock = get_clock(program).await?;
+    let current_point = match ActivationType::try_from(lb_pair_state.activation_type)? {
+        ActivationType::Slot => clock.slot,
+        ActivationType::Timestamp => clock.unix_timestamp as u64,
+    };
+
+    let (amount_x, amount_y) = get_position_amounts(&position_state, &bin_arrays)?;
+    let (fee_x_pending, fee_y_pending) = get_position_pending_fees(&position_state, &bin_arrays)?;
+    let pending_rewards = get_position_pending_rewards(
+        &lb_pair_state,
+        &position_state,
+        &bin_arrays,
+        clock.unix_timestamp as u64,
+    )?;
+
+    let mut bins = vec![];
+    for bin_id in position_state.lower_bin_id..=position_state.upper_bin_id {
+        let liquidity_share = position_state.get_liquidity_share_in_bin(bin_id)?;
+        if liquidity_share == 0 {
+            continue;
+        }
+        let bin_array_idx = BinArray::bin_id_to_bin_array_index(bin_id)?;
+        let bin = bin_arrays
+            .iter()
+            .find(|bin_array| bin_array.index == i64::from(bin_array_idx))
+            .with_context(|| format!("Bin array of bin {} not found", bin_id))?
+            .get_bin(bin_id)?;
+        let (bin_amount_x, bin_amount_y) = bin.calculate_out_amount(liquidity_share)?;
+
+        bins.push(PositionBinReport {
+            bin_id,
+            price: get_ui_price_from_id(
+                bin_id,
+                lb_pair_state.bin_step,
+                x_mint.decimals,
+                y_mint.decimals,
+            )?,
+            liquidity_share,
+            liquidity_share_pct: liquidity_share as f64 / bin.liquidity_supply as f64 * 100.0,
+            amount_x: bin_amount_x,
+            amount_y: bin_amount_y,
+            ui_amount_x: to_ui_amount(bin_amount_x, x_mint.decimals),
+            ui_amount_y: to_ui_amount(bin_amount_y, y_mint.decimals),
+        });
+    }
+
+    let reward_decimals = get_reward_mint_decimals(program, &lb_pair_state).await?;
+    let mut reward_balances = vec![];
+    for (reward_index, reward_info) in lb_pair_state.reward_infos.iter().enumerate() {
+        if !reward_info.initialized() {
+            continue;
+        }
+        let pending = pending_rewards[reward_index];
+        let claimed = position_state.total_claimed_rewards[reward_index];
+        reward_balances.push(PositionRewardReport {
+            reward_index,
+            mint: reward_info.mint.to_string(),
+            pending,
+            ui_pending: to_ui_amount(pending, reward_decimals[reward_index]),
+            claimed,
+            ui_claimed: to_ui_amount(claimed, reward_decimals[reward_index]),
+        });
+    }
+
+    let reward_prices =
+        get_reward_prices_in_token_y(program, &lb_pair_state, &reward_prices).await?;
+
+    let reward_projections = project_position_rewards(
+        &lb_pair_state,
+        &position_state,
+        &bin_arrays,
+        Some(&reward_prices),
+        clock.unix_timestamp as u64,
+    )?;
+
+    let (operator, fee_owner) = if version == 2 {
+        let optional_pubkey =
+            |pubkey: Pubkey| (pubkey != Pubkey::default()).then(|| pubkey.to_string());
+        (
+            optional_pubkey(position_state.operator),
+            optional_pubkey(position_state.fee_owner),
+        )
+    } else {
+        (None, None)
+    };
+
+    let report = PositionReport {
+        address: position.to_string(),
+        version,
+        lb_pair: position_state.lb_pair.to_string(),
+        owner: position_state.owner.to_string(),
+        operator,
+        fee_owner,
+        lower_bin_id: position_state.lower_bin_id,
+        upper_bin_id: position_state.upper_bin_id,
+        lower_price: get_ui_price_from_id(
+            position_state.lower_bin_id,
+            lb_pair_state.bin_step,
+            x_mint.decimals,
+            y_mint.decimals,
+        )?,
+        upper_price: get_ui_price_from_id(
+            position_state.upper_bin_id,
+            lb_pair_state.bin_step,
+            x_mint.decimals,
+            y_mint.decimals,
+        )?,
+        active_id: lb_pair_state.active_id,
+        in_range: position_state
+            .id_within_position(lb_pair_state.active_id)
+            .is_ok(),
+        lock_release_point: position_state.lock_release_point,
+        liquidity_locked: position_state.is_liquidity_locked(current_point),
+        amount_x,
+        amount_y,
+        ui_amount_x: to_ui_amount(amount_x, x_mint.decimals),
+        ui_amount_y: to_ui_amount(amount_y, y_mint.decimals),
+        fee_x_pending,
+        fee_y_pending,
+        ui_fee_x_pending: to_ui_amount(fee_x_pending, x_mint.decimals),
+        ui_fee_y_pending: to_ui_amount(fee_y_pending, y_mint.decimals),
+        total_claimed_fee_x: position_state.total_claimed_fee_x_amount,
+        total_claimed_fee_y: position_state.total_claimed_fee_y_amount,
+        ui_total_claimed_fee_x: to_ui_amount(
+            position_state.total_claimed_fee_x_amount,
+            x_mint.decimals,
+        ),
+        ui_total_claimed_fee_y: to_ui_amount(
+            position_state.total_claimed_fee_y_amount,
+            y_mint.decimals,
+        ),
+        bins,
+        reward_balances,
+        rewards: reward_projections
+            .iter()
+            .map(|projection| RewardReport::new(projection, &reward_decimals))
+            .collect(),
+    };
+
+    print_report(output, &report)
+}
@@ -0,0 +1,79 @@
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anyhow::*;
+use lb_clmm::state::preset_parameters::PresetParameter;
+use serde::Serialize;
+use std::ops::Deref;
+
+use crate::instructions::list_all_binstep::get_base_fee_pct;
+use crate::output::{print_key_values, print_report, OutputFormat, Report};
+
+#[derive(Debug, Serialize)]
+pub struct PresetParameterReport {
+    pub preset_parameter: String,
+    pub bin_step: u16,
+    pub base_factor: u16,
+    pub base_fee_pct: f64,
+    pub filter_period: u16,
+    pub decay_period: u16,
+    pub reduction_factor: u16,
+    pub variable_fee_control: u32,
+    pub max_volatility_accumulator: u32,
+    pub min_bin_id: i32,
+    pub max_bin_id: i32,
+    pub protocol_share_bps: u16,
+}
+
+impl Report for PresetParameterReport {
+    const COMMAND: &'static str = "show-preset-parameter";
+    const SCHEMA_VERSION: u32 = 1;
+
+    fn print_table(&self) {
+        print_key_values(&[
+            ("Preset parameter", self.preset_parameter.clone()),
+            ("Bin step", self.bin_step.to_string()),
+            ("Base factor", self.base_factor.to_string()),
+            ("Base fee", format!("{}%", self.base_fee_pct)),
+            ("Filter period", self.filter_period.to_string()),
+            ("Decay period", self.decay_period.to_string()),
+            ("Reduction factor", self.reduction_factor.to_string()),
+            (
+                "Variable fee control",
+                self.variable_fee_control.to_string(),
+            ),
+            (
+                "Max volatility accumulator",
+                self.max_volatility_accumulator.to_string(),
+            ),
+            (
+                "Bin id range",
+                format!("{} - {}", self.min_bin_id, self.max_bin_id),
+            ),
+            ("Protocol share", format!("{} bps", self.protocol_share_bps)),
+        ]);
+    }
+}
+
+pub async fn show_preset_parameter<C: Deref<Target = impl Signer> + Clone>(
+    preset_parameter: Pubkey,
+    program: &Program<C>,
+    output: OutputFormat,
+) -> Result<()> {
+    let param: PresetParameter = program.account(preset_parameter).await?;
+
+    let report = PresetParameterReport {
+        preset_parameter: preset_parameter.to_string(),
+        bin_step: param.bin_step,
+        base_factor: param.base_factor,
+        base_fee_pct: get_base_fee_pct(&param),
+        filter_period: param.filter_period,
+        decay_period: param.decay_period,
+        reduction_factor: param.reduction_factor,
+        variable_fee_control: param.variable_fee_control,
+        max_volatility_accumulator: param.max_volatility_accumulator,
+        min_bin_id: param.min_bin_id,
+        max_bin_id: param.max_bin_id,
+        protocol_share_bps: param.protocol_share,
+    };
+
+    print_report(output, &report)
+}
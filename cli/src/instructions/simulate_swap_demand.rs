@@ -1,7 +1,7 @@
 use crate::instructions::utils::get_or_create_ata;
 use crate::swap;
+use crate::transaction::TransactionConfig;
 use crate::SwapExactInParameters;
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
 use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anchor_spl::token::Mint;
@@ -21,7 +21,7 @@ pub struct SimulateSwapDemandParameters {
 pub async fn simulate_swap_demand<C: Deref<Target = impl Signer> + Clone>(
     params: SimulateSwapDemandParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let SimulateSwapDemandParameters {
@@ -64,8 +64,18 @@ pub async fn simulate_swap_demand<C: Deref<Target = impl Signer> + Clone>(
                 amount_in: amount_x.round() as u64,
                 lb_pair,
                 swap_for_y: true,
+                slippage_bps: 100,
+                min_amount_out: None,
+                host_fee_account: None,
             };
-            match swap(params, program, transaction_config).await {
+            match swap(
+                params,
+                program,
+                transaction_config,
+                compute_unit_price.clone(),
+            )
+            .await
+            {
                 Ok(_) => {}
                 Err(err) => {
                     println!("{err}");
@@ -80,8 +90,18 @@ pub async fn simulate_swap_demand<C: Deref<Target = impl Signer> + Clone>(
                 amount_in: amount_y.round() as u64,
                 lb_pair,
                 swap_for_y: false,
+                slippage_bps: 100,
+                min_amount_out: None,
+                host_fee_account: None,
             };
-            match swap(params, program, transaction_config).await {
+            match swap(
+                params,
+                program,
+                transaction_config,
+                compute_unit_price.clone(),
+            )
+            .await
+            {
                 Ok(_) => {}
                 Err(err) => {
                     println!("{err}");
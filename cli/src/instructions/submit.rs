@@ -0,0 +1,108 @@
+use std::ops::Deref;
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::message::VersionedMessage;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::transaction::VersionedTransaction;
+use anchor_client::Program;
+use anyhow::*;
+
+use crate::export::ExportFile;
+use crate::transaction::{send_or_simulate_transaction, TransactionConfig};
+
+#[derive(Debug)]
+pub struct SubmitParameters {
+    /// Export files of the same transactions. Their signatures are combined.
+    pub files: Vec<String>,
+    pub signatures: Vec<(Pubkey, Signature)>,
+}
+
+pub async fn submit<C: Deref<Target = impl Signer> + Clone>(
+    params: SubmitParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+) -> Result<()> {
+    let SubmitParameters { files, signatures } = params;
+
+    let mut export_files = files
+        .iter()
+        .map(|path| ExportFile::read(path))
+        .collect::<Result<Vec<_>>>()?;
+    ensure!(!export_files.is_empty(), "No export file given");
+    let mut export_file = export_files.remove(0);
+
+    for (other_file, path) in export_files.iter().zip(files.iter().skip(1)) {
+        ensure!(
+            other_file.transactions.len() == export_file.transactions.len(),
+            "{} does not contain the same transactions as {}",
+            path,
+            files[0]
+        );
+        for (transaction, other_transaction) in export_file
+            .transactions
+            .iter_mut()
+            .zip(other_file.transactions.iter())
+        {
+            ensure!(
+                transaction.message == other_transaction.message,
+                "{} does not contain the same transactions as {}",
+                path,
+                files[0]
+            );
+            transaction
+                .signatures
+                .extend(other_transaction.signatures.clone());
+        }
+    }
+
+    // Check every signature before sending anything
+    let mut transactions = vec![];
+    for (i, exported_transaction) in export_file.transactions.iter().enumerate() {
+        let message_bytes = exported_transaction.message_bytes()?;
+        let message: VersionedMessage = bincode::deserialize(&message_bytes)?;
+        let signers =
+            &message.static_account_keys()[..usize::from(message.header().num_required_signatures)];
+
+        let mut transaction_signatures = vec![];
+        for signer in signers {
+            let signature = signatures
+                .iter()
+                .find(|(pubkey, signature)| {
+                    pubkey == signer && signature.verify(signer.as_ref(), &message_bytes)
+                })
+                .map(|(_, signature)| *signature)
+                .or_else(|| {
+                    exported_transaction
+                        .signatures
+                        .get(&signer.to_string())
+                        .and_then(|signature| Signature::from_str(signature).ok())
+                })
+                .with_context(|| format!("Transaction {}: missing signature of {}", i, signer))?;
+
+            ensure!(
+                signature.verify(signer.as_ref(), &message_bytes),
+                "Transaction {}: invalid signature of {}",
+                i,
+                signer
+            );
+            transaction_signatures.push(signature);
+        }
+
+        transactions.push(VersionedTransaction {
+            signatures: transaction_signatures,
+            message,
+        });
+    }
+
+    for (i, transaction) in transactions.iter().enumerate() {
+        if let Some(signature) =
+            send_or_simulate_transaction(program, transaction, transaction_config).await?
+        {
+            println!("Submit transaction {i}. Signature: {signature}");
+        }
+    }
+
+    Ok(())
+}
@@ -1,10 +1,12 @@
 use std::collections::HashMap;
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use super::utils::{compute_budget_instructions, get_max_amount_in, get_swap_bin_array_count};
+use crate::output::print_key_values;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 
 use anchor_client::solana_sdk::clock::Clock;
-use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::solana_sdk::sysvar::SysvarId;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anchor_lang::solana_program::instruction::AccountMeta;
@@ -12,9 +14,8 @@ use anchor_lang::AccountDeserialize;
 use anchor_spl::associated_token::get_associated_token_address;
 
 use anyhow::*;
-use commons::quote::{get_bin_array_pubkeys_for_swap, quote_exact_out};
+use commons::quote::{get_bin_array_pubkeys_for_swap, get_price_impact_pct, quote_exact_out};
 use lb_clmm::accounts;
-use lb_clmm::constants::BASIS_POINT_MAX;
 use lb_clmm::instruction;
 
 use lb_clmm::state::bin::BinArray;
@@ -27,17 +28,27 @@ pub struct SwapExactOutParameters {
     pub lb_pair: Pubkey,
     pub amount_out: u64,
     pub swap_for_y: bool,
+    /// Slippage tolerance on the quoted amount in
+    pub slippage_bps: u16,
+    /// Maximum amount in, fee included. Overrides the one derived from the quote and `slippage_bps`.
+    pub max_amount_in: Option<u64>,
+    /// Token account of the host receiving a share of the swap fee, in the input token
+    pub host_fee_account: Option<Pubkey>,
 }
 
 pub async fn swap_exact_out<C: Deref<Target = impl Signer> + Clone>(
     params: SwapExactOutParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let SwapExactOutParameters {
         amount_out,
         lb_pair,
         swap_for_y,
+        slippage_bps,
+        max_amount_in,
+        host_fee_account,
     } = params;
 
     let lb_pair_state: LbPair = program.account(lb_pair).await?;
@@ -66,7 +77,7 @@ pub async fn swap_exact_out<C: Deref<Target = impl Signer> + Clone>(
         &lb_pair_state,
         bitmap_extension.as_ref(),
         swap_for_y,
-        3,
+        get_swap_bin_array_count(transaction_config),
     )?;
 
     let bin_arrays = program
@@ -123,14 +134,29 @@ pub async fn swap_exact_out<C: Deref<Target = impl Signer> + Clone>(
         user_token_in,
         user_token_out,
         oracle: lb_pair_state.oracle,
-        host_fee_in: Some(lb_clmm::ID),
+        host_fee_in: host_fee_account.or(Some(lb_clmm::ID)),
         event_authority,
         program: lb_clmm::ID,
     };
 
-    let in_amount = quote.amount_in + quote.fee;
-    // 100 bps slippage
-    let max_in_amount = in_amount * 10100 / BASIS_POINT_MAX as u64;
+    let in_amount = quote
+        .amount_in
+        .checked_add(quote.fee)
+        .context("MathOverflow")?;
+    let max_in_amount = match max_amount_in {
+        Some(max_amount_in) => max_amount_in,
+        None => get_max_amount_in(in_amount, slippage_bps)?,
+    };
+    let price_impact_pct =
+        get_price_impact_pct(&lb_pair_state, quote.amount_in, amount_out, swap_for_y)?;
+
+    print_key_values(&[
+        ("Amount out", amount_out.to_string()),
+        ("Expected amount in", in_amount.to_string()),
+        ("Fee", quote.fee.to_string()),
+        ("Price impact", format!("{:.4}%", price_impact_pct)),
+        ("Max amount in", max_in_amount.to_string()),
+    ]);
 
     let ix = instruction::SwapExactOut {
         out_amount: amount_out,
@@ -142,20 +168,17 @@ pub async fn swap_exact_out<C: Deref<Target = impl Signer> + Clone>(
         .map(|key| AccountMeta::new(key, false))
         .collect::<Vec<_>>();
 
-    let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(1_400_000);
-
-    let request_builder = program.request();
-    let signature = request_builder
-        .instruction(compute_budget_ix)
+    let mut request_builder = program.request();
+    for compute_budget_ix in compute_budget_instructions(1_400_000, compute_unit_price) {
+        request_builder = request_builder.instruction(compute_budget_ix);
+    }
+    let request_builder = request_builder
         .accounts(accounts)
         .accounts(remaining_accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Swap. Signature: {:#?}", signature);
-
-    signature?;
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Swap. Signature: {}", signature);
+    }
 
     Ok(())
 }
@@ -1,8 +1,9 @@
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use super::utils::{compute_budget_instructions, get_swap_bin_array_count};
+use crate::transaction::{send_or_simulate, TransactionConfig};
 
-use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+use anchor_client::solana_sdk::instruction::Instruction;
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anchor_lang::solana_program::instruction::AccountMeta;
 use anchor_spl::associated_token::get_associated_token_address;
@@ -22,18 +23,22 @@ pub struct SwapWithPriceImpactParameters {
     pub amount_in: u64,
     pub swap_for_y: bool,
     pub price_impact_bps: u16,
+    /// Token account of the host receiving a share of the swap fee, in the input token
+    pub host_fee_account: Option<Pubkey>,
 }
 
 pub async fn swap_with_price_impact<C: Deref<Target = impl Signer> + Clone>(
     params: SwapWithPriceImpactParameters,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
 ) -> Result<()> {
     let SwapWithPriceImpactParameters {
         amount_in,
         lb_pair,
         swap_for_y,
         price_impact_bps,
+        host_fee_account,
     } = params;
 
     let lb_pair_state: LbPair = program.account(lb_pair).await?;
@@ -62,7 +67,7 @@ pub async fn swap_with_price_impact<C: Deref<Target = impl Signer> + Clone>(
         &lb_pair_state,
         bitmap_extension.as_ref(),
         swap_for_y,
-        3,
+        get_swap_bin_array_count(transaction_config),
     )?;
 
     let (event_authority, _bump) =
@@ -83,7 +88,7 @@ pub async fn swap_with_price_impact<C: Deref<Target = impl Signer> + Clone>(
         user_token_in,
         user_token_out,
         oracle: lb_pair_state.oracle,
-        host_fee_in: Some(lb_clmm::ID),
+        host_fee_in: host_fee_account.or(Some(lb_clmm::ID)),
         event_authority,
         program: lb_clmm::ID,
     };
@@ -99,20 +104,17 @@ pub async fn swap_with_price_impact<C: Deref<Target = impl Signer> + Clone>(
         .map(|key| AccountMeta::new(key, false))
         .collect::<Vec<_>>();
 
-    let compute_budget_ix = ComputeBudgetInstruction::set_compute_unit_limit(1_400_000);
-
-    let request_builder = program.request();
-    let signature = request_builder
-        .instruction(compute_budget_ix)
+    let mut request_builder = program.request();
+    for compute_budget_ix in compute_budget_instructions(1_400_000, compute_unit_price) {
+        request_builder = request_builder.instruction(compute_budget_ix);
+    }
+    let request_builder = request_builder
         .accounts(accounts)
         .accounts(remaining_accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Swap. Signature: {:#?}", signature);
-
-    signature?;
+        .args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Swap. Signature: {}", signature);
+    }
 
     Ok(())
 }
@@ -1,4 +1,4 @@
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::Program;
 use anchor_lang::prelude::Pubkey;
@@ -10,7 +10,7 @@ use std::ops::Deref;
 pub async fn toggle_pool_status<C: Deref<Target = impl Signer> + Clone>(
     lb_pair: Pubkey,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<()> {
     let accounts = accounts::TogglePairStatus {
         admin: program.payer(),
@@ -19,16 +19,10 @@ pub async fn toggle_pool_status<C: Deref<Target = impl Signer> + Clone>(
 
     let ix = instruction::TogglePairStatus {};
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Toggle pool status. Signature: {:#?}", signature);
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Toggle pool status. Signature: {}", signature);
+    }
 
     Ok(())
 }
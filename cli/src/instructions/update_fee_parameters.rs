@@ -0,0 +1,225 @@
+use std::ops::Deref;
+
+use crate::math::{compute_base_factor_from_fee_bps, fee_rate_to_fee_pct};
+use crate::output::print_table;
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::accounts;
+use lb_clmm::constants::{MAX_BASE_FACTOR_STEP, MAX_BASE_FEE, MAX_PROTOCOL_SHARE, MIN_BASE_FEE};
+use lb_clmm::instruction;
+use lb_clmm::instructions::admin::update_fee_parameters::FeeParameter;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::state::parameters::StaticParameters;
+use lb_clmm::utils::pda::derive_event_authority_pda;
+
+#[derive(Debug)]
+pub struct UpdateFeeParametersParams {
+    pub lb_pair: Pubkey,
+    /// New protocol share in bps. Unchanged when `None`.
+    pub protocol_share: Option<u16>,
+    /// New base factor. Unchanged when `None`.
+    pub base_factor: Option<u16>,
+    /// New base fee in bps, converted to a base factor with the bin step of the pair.
+    pub base_fee_bps: Option<u16>,
+}
+
+fn format_base_fee(bin_step: u16, base_factor: u16) -> String {
+    // Same as LbPair::get_base_fee, in FEE_PRECISION
+    let base_fee = u128::from(base_factor) * u128::from(bin_step) * 10;
+    match fee_rate_to_fee_pct(base_fee) {
+        Some(base_fee_pct) => format!("{}%", base_fee_pct),
+        None => "-".to_string(),
+    }
+}
+
+/// Check the new fee parameters against the limits of the program.
+fn validate_fee_parameter(
+    bin_step: u16,
+    current: &StaticParameters,
+    fee_parameter: &FeeParameter,
+) -> Result<()> {
+    ensure!(
+        fee_parameter.protocol_share <= MAX_PROTOCOL_SHARE,
+        "Protocol share {} bps is above the maximum {} bps",
+        fee_parameter.protocol_share,
+        MAX_PROTOCOL_SHARE
+    );
+
+    let base_fee = u128::from(fee_parameter.base_factor) * u128::from(bin_step) * 10;
+    ensure!(
+        (MIN_BASE_FEE..=MAX_BASE_FEE).contains(&base_fee),
+        "Base fee {} is out of the range {}% - {}%",
+        format_base_fee(bin_step, fee_parameter.base_factor),
+        fee_rate_to_fee_pct(MIN_BASE_FEE).unwrap_or_default(),
+        fee_rate_to_fee_pct(MAX_BASE_FEE).unwrap_or_default()
+    );
+
+    let base_factor_delta = fee_parameter.base_factor.abs_diff(current.base_factor);
+    ensure!(
+        base_factor_delta <= current.base_factor && base_factor_delta <= MAX_BASE_FACTOR_STEP,
+        "Base factor can change by at most {} and by at most the current base factor {} per update",
+        MAX_BASE_FACTOR_STEP,
+        current.base_factor
+    );
+
+    Ok(())
+}
+
+fn print_static_parameters_diff(bin_step: u16, current: &StaticParameters, new: &StaticParameters) {
+    let row = |name: &str, current: String, new: String| {
+        let changed = if current != new { "*" } else { "" };
+        vec![name.to_string(), current, new, changed.to_string()]
+    };
+
+    let rows = vec![
+        row(
+            "base_factor",
+            current.base_factor.to_string(),
+            new.base_factor.to_string(),
+        ),
+        row(
+            "base fee",
+            format_base_fee(bin_step, current.base_factor),
+            format_base_fee(bin_step, new.base_factor),
+        ),
+        row(
+            "protocol_share",
+            current.protocol_share.to_string(),
+            new.protocol_share.to_string(),
+        ),
+        row(
+            "filter_period",
+            current.filter_period.to_string(),
+            new.filter_period.to_string(),
+        ),
+        row(
+            "decay_period",
+            current.decay_period.to_string(),
+            new.decay_period.to_string(),
+        ),
+        row(
+            "reduction_factor",
+            current.reduction_factor.to_string(),
+            new.reduction_factor.to_string(),
+        ),
+        row(
+            "variable_fee_control",
+            current.variable_fee_control.to_string(),
+            new.variable_fee_control.to_string(),
+        ),
+        row(
+            "max_volatility_accumulator",
+            current.max_volatility_accumulator.to_string(),
+            new.max_volatility_accumulator.to_string(),
+        ),
+        row(
+            "min_bin_id",
+            current.min_bin_id.to_string(),
+            new.min_bin_id.to_string(),
+        ),
+        row(
+            "max_bin_id",
+            current.max_bin_id.to_string(),
+            new.max_bin_id.to_string(),
+        ),
+    ];
+
+    print_table(&["Parameter", "Current", "New", "Changed"], &rows);
+}
+
+pub async fn update_fee_parameters<C: Deref<Target = impl Signer> + Clone>(
+    params: UpdateFeeParametersParams,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+) -> Result<()> {
+    let UpdateFeeParametersParams {
+        lb_pair,
+        protocol_share,
+        base_factor,
+        base_fee_bps,
+    } = params;
+
+    let lb_pair_state: LbPair = program.account(lb_pair).await?;
+    let bin_step = lb_pair_state.bin_step;
+    let current = lb_pair_state.parameters;
+
+    let base_factor = match (base_factor, base_fee_bps) {
+        (Some(base_factor), _) => base_factor,
+        (None, Some(base_fee_bps)) => compute_base_factor_from_fee_bps(bin_step, base_fee_bps)
+            .with_context(|| {
+                format!(
+                    "Base fee {} bps is not a base factor of bin step {}",
+                    base_fee_bps, bin_step
+                )
+            })?,
+        (None, None) => current.base_factor,
+    };
+    let fee_parameter = FeeParameter {
+        protocol_share: protocol_share.unwrap_or(current.protocol_share),
+        base_factor,
+    };
+
+    let mut new = current;
+    new.protocol_share = fee_parameter.protocol_share;
+    new.base_factor = fee_parameter.base_factor;
+    print_static_parameters_diff(bin_step, &current, &new);
+
+    if new.protocol_share == current.protocol_share && new.base_factor == current.base_factor {
+        println!("Fee parameters of pair {} are unchanged", lb_pair);
+        return Ok(());
+    }
+    validate_fee_parameter(bin_step, &current, &fee_parameter)?;
+
+    let (event_authority, _bump) = derive_event_authority_pda();
+
+    let accounts = accounts::UpdateFeeParameters {
+        lb_pair,
+        admin: program.payer(),
+        event_authority,
+        program: lb_clmm::ID,
+    };
+
+    let ix = instruction::UpdateFeeParameters { fee_parameter };
+
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Update fee parameters. Signature: {}", signature);
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_validate_fee_parameter() {
+        let current = StaticParameters {
+            base_factor: 10_000,
+            protocol_share: 1_000,
+            ..StaticParameters::default()
+        };
+        // 0.1% base fee with bin step 10
+        let bin_step = 10;
+        let fee_parameter = |protocol_share: u16, base_factor: u16| FeeParameter {
+            protocol_share,
+            base_factor,
+        };
+
+        assert!(validate_fee_parameter(bin_step, &current, &fee_parameter(2_000, 10_100)).is_ok());
+        // Protocol share above MAX_PROTOCOL_SHARE
+        assert!(validate_fee_parameter(bin_step, &current, &fee_parameter(2_501, 10_000)).is_err());
+        // Base factor step above MAX_BASE_FACTOR_STEP
+        assert!(validate_fee_parameter(bin_step, &current, &fee_parameter(1_000, 10_101)).is_err());
+
+        // Base fee below MIN_BASE_FEE
+        let current = StaticParameters {
+            base_factor: 10,
+            ..StaticParameters::default()
+        };
+        assert!(validate_fee_parameter(bin_step, &current, &fee_parameter(0, 9)).is_err());
+    }
+}
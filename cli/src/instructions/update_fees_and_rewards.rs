@@ -0,0 +1,44 @@
+use std::ops::Deref;
+
+use crate::instructions::utils::derive_position_bin_arrays;
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::accounts;
+use lb_clmm::instruction;
+use lb_clmm::state::position::PositionV2;
+
+/// Checkpoint the pending fees and rewards of a position, without claiming them.
+pub async fn update_fees_and_rewards<C: Deref<Target = impl Signer> + Clone>(
+    position: Pubkey,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let position_state: PositionV2 = program.account(position).await?;
+    let [bin_array_lower, bin_array_upper] =
+        derive_position_bin_arrays(position_state.lb_pair, position_state.lower_bin_id)?;
+
+    let accounts = accounts::UpdateFeesAndRewards {
+        position,
+        lb_pair: position_state.lb_pair,
+        bin_array_lower,
+        bin_array_upper,
+        owner: program.payer(),
+    };
+
+    let ix = instruction::UpdateFeesAndRewards {};
+
+    let mut request_builder = program.request();
+    if let Some(compute_unit_price) = compute_unit_price {
+        request_builder = request_builder.instruction(compute_unit_price);
+    }
+    let request_builder = request_builder.accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Update fees and rewards. Signature: {}", signature);
+    }
+
+    Ok(())
+}
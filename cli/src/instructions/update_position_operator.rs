@@ -0,0 +1,59 @@
+use std::ops::Deref;
+
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+
+use anyhow::*;
+use lb_clmm::accounts;
+use lb_clmm::instruction;
+use lb_clmm::state::position::PositionV2;
+use lb_clmm::utils::pda::derive_event_authority_pda;
+
+#[derive(Debug)]
+pub struct UpdatePositionOperatorParameters {
+    pub position: Pubkey,
+    pub operator: Pubkey,
+}
+
+pub async fn update_position_operator<C: Deref<Target = impl Signer> + Clone>(
+    params: UpdatePositionOperatorParameters,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let UpdatePositionOperatorParameters { position, operator } = params;
+
+    let position_state: PositionV2 = program.account(position).await?;
+    ensure!(
+        position_state.owner == program.payer(),
+        "Position {} is owned by {}",
+        position,
+        position_state.owner
+    );
+
+    let (event_authority, _bump) = derive_event_authority_pda();
+
+    let accounts = accounts::UpdatePositionOperator {
+        position,
+        owner: program.payer(),
+        event_authority,
+        program: lb_clmm::ID,
+    };
+
+    let ix = instruction::UpdatePositionOperator { operator };
+
+    let mut request_builder = program.request();
+    if let Some(compute_unit_price) = compute_unit_price {
+        request_builder = request_builder.instruction(compute_unit_price);
+    }
+    let request_builder = request_builder.accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!(
+            "Update operator of position {} from {} to {}. Signature: {}",
+            position, position_state.operator, operator, signature
+        );
+    }
+
+    Ok(())
+}
@@ -1,4 +1,4 @@
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
 use anyhow::*;
 use lb_clmm::accounts;
@@ -17,7 +17,7 @@ pub struct UpdateRewardFunderParams {
 pub async fn update_reward_funder<C: Deref<Target = impl Signer> + Clone>(
     params: UpdateRewardFunderParams,
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
 ) -> Result<()> {
     let UpdateRewardFunderParams {
         lb_pair,
@@ -39,16 +39,10 @@ pub async fn update_reward_funder<C: Deref<Target = impl Signer> + Clone>(
         new_funder: funder,
     };
 
-    let request_builder = program.request();
-    let signature = request_builder
-        .accounts(accounts)
-        .args(ix)
-        .send_with_spinner_and_config(transaction_config)
-        .await;
-
-    println!("Fund reward. Signature: {:#?}", signature);
-
-    signature?;
+    let request_builder = program.request().accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Fund reward. Signature: {}", signature);
+    }
 
     Ok(())
 }
@@ -1,13 +1,42 @@
+use crate::instructions::seed_liquidity::convert_min_max_ui_price_to_min_max_bin_id;
+use crate::math::{price_per_lamport_to_price_per_token, q64x64_price_to_decimal, to_amount};
+use crate::output::{format_optional, print_table};
+use anchor_client::solana_client::rpc_filter::{Memcmp, RpcFilterType};
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::clock::Clock;
+use anchor_client::solana_sdk::compute_budget::ComputeBudgetInstruction;
+use anchor_client::solana_sdk::hash::Hash;
 use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::{Message, VersionedMessage};
+use anchor_client::solana_sdk::packet::PACKET_DATA_SIZE;
 use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::sysvar::SysvarId;
 use anchor_client::Program;
+use anchor_lang::Space;
+use anchor_spl::token::Mint;
+use commons::compute_budget::MAX_COMPUTE_UNIT_LIMIT;
+use commons::rewards::{RewardPrices, RewardProjection, TokenPrice};
+use lb_clmm::accounts;
+use lb_clmm::constants::{BASIS_POINT_MAX, NUM_REWARDS};
+use lb_clmm::instructions::deposit::{
+    BinLiquidityDistributionByWeight, CompressedBinDepositAmount,
+};
+use lb_clmm::math::price_math::get_price_from_id;
 use lb_clmm::state::bin::BinArray;
-use lb_clmm::state::position::PositionV2;
-use lb_clmm::utils::pda::derive_bin_array_pda;
+use lb_clmm::state::bin_array_bitmap_extension::BinArrayBitmapExtension;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::state::position::{Position, PositionV2};
+use lb_clmm::utils::pda::{
+    derive_bin_array_bitmap_extension, derive_bin_array_pda, derive_event_authority_pda,
+};
+use rust_decimal::prelude::ToPrimitive;
+use serde::Serialize;
 use spl_associated_token_account::instruction::create_associated_token_account;
+use std::cell::RefCell;
 use std::ops::Deref;
 
-use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use crate::lookup_table::LookupTables;
+use crate::transaction::{send_or_simulate, TransactionConfig};
 use anchor_client::solana_sdk::pubkey::Pubkey;
 use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::spl_token;
@@ -15,7 +44,7 @@ use anyhow::*;
 
 pub async fn get_or_create_ata<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
-    transaction_config: RpcSendTransactionConfig,
+    transaction_config: TransactionConfig,
     token_mint: Pubkey,
     wallet_address: Pubkey,
     compute_unit_price: Option<Instruction>,
@@ -40,27 +69,805 @@ pub async fn get_or_create_ata<C: Deref<Target = impl Signer> + Clone>(
                 builder = builder.instruction(compute_unit_price);
             }
 
-            builder
-                .send_with_spinner_and_config(transaction_config)
-                .await?;
+            send_or_simulate(program, builder, transaction_config).await?;
             Ok(user_ata)
         }
     }
 }
 
+/// Compute budget instructions of a transaction: the compute unit limit, followed by the compute unit
+/// price of `--priority-fee` when there is one.
+pub fn compute_budget_instructions(
+    compute_unit_limit: u32,
+    compute_unit_price: Option<Instruction>,
+) -> Vec<Instruction> {
+    let mut instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        compute_unit_limit,
+    )];
+    instructions.extend(compute_unit_price);
+    instructions
+}
+
+/// Instruction with the compute units budgeted for it, to pack instructions into transactions.
+pub struct BudgetedInstruction {
+    pub instruction: Instruction,
+    pub compute_units: u32,
+}
+
+/// Serialized size of a transaction made of `instructions`, compiled against `lookup_tables` when given.
+fn get_transaction_size(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: Option<&LookupTables>,
+) -> Result<usize> {
+    let message = match lookup_tables {
+        Some(lookup_tables) => lookup_tables.compile(instructions, Hash::default())?,
+        None => VersionedMessage::Legacy(Message::new(instructions, Some(payer))),
+    };
+    let signature_count = usize::from(message.header().num_required_signatures);
+    // Compact array length of the signatures, the signatures, then the message
+    Ok(1 + signature_count * 64 + message.serialize().len())
+}
+
+/// Split the instructions, in order, into as few transactions as the packet size and the compute unit limit allow.
+/// More of them fit in a transaction compiled against `lookup_tables`.
+/// Each transaction is returned with its compute budget instructions first.
+pub fn pack_instructions(
+    instructions: Vec<BudgetedInstruction>,
+    payer: &Pubkey,
+    lookup_tables: Option<&LookupTables>,
+    compute_unit_price: Option<Instruction>,
+) -> Result<Vec<Vec<Instruction>>> {
+    let build = |compute_units: u32, instructions: &[Instruction]| {
+        let mut transaction =
+            compute_budget_instructions(compute_units, compute_unit_price.clone());
+        transaction.extend_from_slice(instructions);
+        transaction
+    };
+
+    let mut transactions = vec![];
+    let mut current: Vec<Instruction> = vec![];
+    let mut current_compute_units = 0u32;
+
+    for BudgetedInstruction {
+        instruction,
+        compute_units,
+    } in instructions
+    {
+        let compute_units_with_instruction = current_compute_units.saturating_add(compute_units);
+        let mut candidate = current.clone();
+        candidate.push(instruction.clone());
+
+        let fits = compute_units_with_instruction <= MAX_COMPUTE_UNIT_LIMIT
+            && get_transaction_size(
+                &build(compute_units_with_instruction, &candidate),
+                payer,
+                lookup_tables,
+            )? <= PACKET_DATA_SIZE;
+
+        if fits {
+            current = candidate;
+            current_compute_units = compute_units_with_instruction;
+            continue;
+        }
+
+        ensure!(
+            !current.is_empty(),
+            "Instruction of program {} does not fit in a transaction",
+            instruction.program_id
+        );
+        transactions.push(build(current_compute_units, &current));
+        current = vec![instruction];
+        current_compute_units = compute_units;
+
+        ensure!(
+            current_compute_units <= MAX_COMPUTE_UNIT_LIMIT
+                && get_transaction_size(
+                    &build(current_compute_units, &current),
+                    payer,
+                    lookup_tables
+                )? <= PACKET_DATA_SIZE,
+            "Instruction of program {} does not fit in a transaction",
+            current[0].program_id
+        );
+    }
+
+    if !current.is_empty() {
+        transactions.push(build(current_compute_units, &current));
+    }
+
+    Ok(transactions)
+}
+
+/// Bin arrays given to a swap, from the active bin array in the swap direction
+const SWAP_BIN_ARRAY_COUNT: u8 = 3;
+/// Bin arrays given to a swap compiled against lookup tables, which take an index instead of a key in the transaction
+const SWAP_BIN_ARRAY_COUNT_WITH_LOOKUP_TABLES: u8 = 8;
+
+pub fn get_swap_bin_array_count(transaction_config: TransactionConfig) -> u8 {
+    if transaction_config.lookup_tables.is_some() {
+        SWAP_BIN_ARRAY_COUNT_WITH_LOOKUP_TABLES
+    } else {
+        SWAP_BIN_ARRAY_COUNT
+    }
+}
+
+/// Minimum amount out of a swap quoted to return `amount_out`, with `slippage_bps` tolerance.
+pub fn get_min_amount_out(amount_out: u64, slippage_bps: u16) -> Result<u64> {
+    let slippage_bps = validate_slippage_bps(slippage_bps)?;
+    let min_amount_out =
+        u128::from(amount_out) * (BASIS_POINT_MAX as u128 - slippage_bps) / BASIS_POINT_MAX as u128;
+    Ok(min_amount_out.try_into()?)
+}
+
+/// Maximum amount in of a swap quoted to take `amount_in`, with `slippage_bps` tolerance.
+pub fn get_max_amount_in(amount_in: u64, slippage_bps: u16) -> Result<u64> {
+    let slippage_bps = validate_slippage_bps(slippage_bps)?;
+    let max_amount_in =
+        u128::from(amount_in) * (BASIS_POINT_MAX as u128 + slippage_bps) / BASIS_POINT_MAX as u128;
+    Ok(max_amount_in.try_into().unwrap_or(u64::MAX))
+}
+
+fn validate_slippage_bps(slippage_bps: u16) -> Result<u128> {
+    ensure!(
+        i32::from(slippage_bps) <= BASIS_POINT_MAX,
+        "Slippage must be at most {} bps",
+        BASIS_POINT_MAX
+    );
+    Ok(slippage_bps.into())
+}
+
 pub async fn get_bin_arrays_for_position<C: Deref<Target = impl Signer> + Clone>(
     program: &Program<C>,
     position_address: Pubkey,
 ) -> Result<[Pubkey; 2]> {
     let position: PositionV2 = program.account(position_address).await?;
 
-    let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(position.lower_bin_id)?;
+    derive_position_bin_arrays(position.lb_pair, position.lower_bin_id)
+}
+
+/// The two bin arrays covering a position starting at `lower_bin_id`.
+pub fn derive_position_bin_arrays(lb_pair: Pubkey, lower_bin_id: i32) -> Result<[Pubkey; 2]> {
+    let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(lower_bin_id)?;
     let upper_bin_array_idx = lower_bin_array_idx.checked_add(1).context("MathOverflow")?;
 
-    let (lower_bin_array, _bump) =
-        derive_bin_array_pda(position.lb_pair, lower_bin_array_idx.into());
-    let (upper_bin_array, _bump) =
-        derive_bin_array_pda(position.lb_pair, upper_bin_array_idx.into());
+    let (lower_bin_array, _bump) = derive_bin_array_pda(lb_pair, lower_bin_array_idx.into());
+    let (upper_bin_array, _bump) = derive_bin_array_pda(lb_pair, upper_bin_array_idx.into());
 
     Ok([lower_bin_array, upper_bin_array])
 }
+
+/// Bitmap extension of the pair, when it is initialized.
+pub async fn get_bin_array_bitmap_extension<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    lb_pair: Pubkey,
+) -> Option<(Pubkey, BinArrayBitmapExtension)> {
+    let (bin_array_bitmap_extension, _bump) = derive_bin_array_bitmap_extension(lb_pair);
+    program
+        .account::<BinArrayBitmapExtension>(bin_array_bitmap_extension)
+        .await
+        .ok()
+        .map(|extension| (bin_array_bitmap_extension, extension))
+}
+
+/// Bins a deposit goes to, by UI price or by bin id. Both ends are included.
+#[derive(Debug, Clone, Copy)]
+pub enum BinRange {
+    Price { min_price: f64, max_price: f64 },
+    BinId { min_bin_id: i32, max_bin_id: i32 },
+}
+
+/// Position a liquidity command deposits to or withdraws from, with its pair and the pair mints.
+pub struct LiquidityTarget {
+    pub position: Pubkey,
+    pub position_state: PositionV2,
+    pub lb_pair_state: LbPair,
+    pub token_x_mint: Mint,
+    pub token_y_mint: Mint,
+}
+
+impl LiquidityTarget {
+    pub async fn fetch<C: Deref<Target = impl Signer> + Clone>(
+        program: &Program<C>,
+        position: Pubkey,
+    ) -> Result<Self> {
+        let position_state: PositionV2 = program.account(position).await?;
+        let lb_pair_state: LbPair = program.account(position_state.lb_pair).await?;
+        let token_x_mint: Mint = program.account(lb_pair_state.token_x_mint).await?;
+        let token_y_mint: Mint = program.account(lb_pair_state.token_y_mint).await?;
+
+        Ok(Self {
+            position,
+            position_state,
+            lb_pair_state,
+            token_x_mint,
+            token_y_mint,
+        })
+    }
+
+    pub fn lb_pair(&self) -> Pubkey {
+        self.position_state.lb_pair
+    }
+
+    /// Amount of token Y when `token_y`, else of token X, from a UI amount.
+    pub fn to_amount(&self, ui_amount: f64, token_y: bool) -> Result<u64> {
+        let decimals = if token_y {
+            self.token_y_mint.decimals
+        } else {
+            self.token_x_mint.decimals
+        };
+        to_amount(ui_amount, decimals)
+    }
+
+    /// Min and max bin id of `bin_range`, the whole position when `None`. The bins must be in the position.
+    pub fn get_bin_range(&self, bin_range: Option<BinRange>) -> Result<(i32, i32)> {
+        let (min_bin_id, max_bin_id) = match bin_range {
+            None => (
+                self.position_state.lower_bin_id,
+                self.position_state.upper_bin_id,
+            ),
+            Some(BinRange::Price {
+                min_price,
+                max_price,
+            }) => convert_min_max_ui_price_to_min_max_bin_id(
+                self.lb_pair_state.bin_step,
+                min_price,
+                max_price,
+                self.token_x_mint.decimals,
+                self.token_y_mint.decimals,
+            )?,
+            Some(BinRange::BinId {
+                min_bin_id,
+                max_bin_id,
+            }) => (min_bin_id, max_bin_id),
+        };
+
+        ensure!(
+            min_bin_id <= max_bin_id,
+            "Min bin id {} is above max bin id {}",
+            min_bin_id,
+            max_bin_id
+        );
+        self.ensure_bin_in_position(min_bin_id)?;
+        self.ensure_bin_in_position(max_bin_id)?;
+
+        Ok((min_bin_id, max_bin_id))
+    }
+
+    pub fn ensure_bin_in_position(&self, bin_id: i32) -> Result<()> {
+        ensure!(
+            bin_id >= self.position_state.lower_bin_id
+                && bin_id <= self.position_state.upper_bin_id,
+            "Bin {} is out of position {} bins {} - {}",
+            bin_id,
+            self.position,
+            self.position_state.lower_bin_id,
+            self.position_state.upper_bin_id
+        );
+        Ok(())
+    }
+
+    /// A one-sided deposit only fills the bins on its side of the active bin: token X above, token Y below.
+    pub fn ensure_bins_on_side(
+        &self,
+        min_bin_id: i32,
+        max_bin_id: i32,
+        token_y: bool,
+    ) -> Result<()> {
+        let active_id = self.lb_pair_state.active_id;
+        if token_y {
+            ensure!(
+                min_bin_id <= active_id,
+                "Token Y is only deposited to the bins up to the active bin {}",
+                active_id
+            );
+        } else {
+            ensure!(
+                max_bin_id >= active_id,
+                "Token X is only deposited to the bins from the active bin {}",
+                active_id
+            );
+        }
+        Ok(())
+    }
+
+    /// Accounts of the two-sided deposits and of the withdrawals, creating the user token accounts when missing.
+    pub async fn get_modify_liquidity_accounts<C: Deref<Target = impl Signer> + Clone>(
+        &self,
+        program: &Program<C>,
+        transaction_config: TransactionConfig,
+        compute_unit_price: Option<Instruction>,
+    ) -> Result<accounts::ModifyLiquidity> {
+        let lb_pair = self.lb_pair();
+        let [bin_array_lower, bin_array_upper] =
+            derive_position_bin_arrays(lb_pair, self.position_state.lower_bin_id)?;
+
+        let user_token_x = get_or_create_ata(
+            program,
+            transaction_config,
+            self.lb_pair_state.token_x_mint,
+            program.payer(),
+            compute_unit_price.clone(),
+        )
+        .await?;
+        let user_token_y = get_or_create_ata(
+            program,
+            transaction_config,
+            self.lb_pair_state.token_y_mint,
+            program.payer(),
+            compute_unit_price,
+        )
+        .await?;
+
+        let bin_array_bitmap_extension = get_bin_array_bitmap_extension(program, lb_pair)
+            .await
+            .map(|(key, _)| key);
+        let (event_authority, _bump) = derive_event_authority_pda();
+
+        Ok(accounts::ModifyLiquidity {
+            bin_array_lower,
+            bin_array_upper,
+            lb_pair,
+            bin_array_bitmap_extension,
+            position: self.position,
+            reserve_x: self.lb_pair_state.reserve_x,
+            reserve_y: self.lb_pair_state.reserve_y,
+            token_x_mint: self.lb_pair_state.token_x_mint,
+            token_y_mint: self.lb_pair_state.token_y_mint,
+            sender: program.payer(),
+            user_token_x,
+            user_token_y,
+            token_x_program: anchor_spl::token::ID,
+            token_y_program: anchor_spl::token::ID,
+            event_authority,
+            program: lb_clmm::ID,
+        })
+    }
+
+    /// Accounts of the one-sided deposits of token Y when `token_y`, else of token X, creating the user token
+    /// account when missing.
+    pub async fn get_modify_liquidity_one_side_accounts<C: Deref<Target = impl Signer> + Clone>(
+        &self,
+        program: &Program<C>,
+        token_y: bool,
+        transaction_config: TransactionConfig,
+        compute_unit_price: Option<Instruction>,
+    ) -> Result<accounts::ModifyLiquidityOneSide> {
+        let lb_pair = self.lb_pair();
+        let [bin_array_lower, bin_array_upper] =
+            derive_position_bin_arrays(lb_pair, self.position_state.lower_bin_id)?;
+
+        let (token_mint, reserve) = if token_y {
+            (
+                self.lb_pair_state.token_y_mint,
+                self.lb_pair_state.reserve_y,
+            )
+        } else {
+            (
+                self.lb_pair_state.token_x_mint,
+                self.lb_pair_state.reserve_x,
+            )
+        };
+        let user_token = get_or_create_ata(
+            program,
+            transaction_config,
+            token_mint,
+            program.payer(),
+            compute_unit_price,
+        )
+        .await?;
+
+        let bin_array_bitmap_extension = get_bin_array_bitmap_extension(program, lb_pair)
+            .await
+            .map(|(key, _)| key);
+        let (event_authority, _bump) = derive_event_authority_pda();
+
+        Ok(accounts::ModifyLiquidityOneSide {
+            position: self.position,
+            lb_pair,
+            bin_array_bitmap_extension,
+            user_token,
+            reserve,
+            token_mint,
+            bin_array_lower,
+            bin_array_upper,
+            sender: program.payer(),
+            token_program: anchor_spl::token::ID,
+            event_authority,
+            program: lb_clmm::ID,
+        })
+    }
+}
+
+/// Weights of the bins of a deposit by weight, sorted by bin id as the program requires.
+pub fn get_bin_weights(
+    target: &LiquidityTarget,
+    mut bin_weights: Vec<(i32, u16)>,
+) -> Result<Vec<BinLiquidityDistributionByWeight>> {
+    bin_weights.sort_by_key(|(bin_id, _)| *bin_id);
+
+    ensure!(!bin_weights.is_empty(), "No bin weight given");
+    for (i, &(bin_id, weight)) in bin_weights.iter().enumerate() {
+        target.ensure_bin_in_position(bin_id)?;
+        ensure!(weight > 0, "Weight of bin {} is zero", bin_id);
+        ensure!(
+            i == 0 || bin_weights[i - 1].0 != bin_id,
+            "Bin {} is given more than once",
+            bin_id
+        );
+    }
+
+    Ok(bin_weights
+        .into_iter()
+        .map(|(bin_id, weight)| BinLiquidityDistributionByWeight { bin_id, weight })
+        .collect())
+}
+
+/// Compress the bin amounts of `add_liquidity_one_side_precise`, which takes u32 amounts multiplied by a common
+/// decompress multiplier. Amounts are rounded down to a multiple of the multiplier.
+pub fn compress_bin_amounts(
+    bin_amounts: &[(i32, u64)],
+) -> Result<(Vec<CompressedBinDepositAmount>, u64)> {
+    let max_amount = bin_amounts
+        .iter()
+        .map(|(_, amount)| *amount)
+        .max()
+        .context("No bin amount given")?;
+    let max_compressed_amount = u64::from(u32::MAX);
+    let decompress_multiplier = (max_amount / max_compressed_amount
+        + u64::from(max_amount % max_compressed_amount != 0))
+    .max(1);
+
+    let bins = bin_amounts
+        .iter()
+        .map(|&(bin_id, amount)| {
+            Ok(CompressedBinDepositAmount {
+                bin_id,
+                amount: (amount / decompress_multiplier).try_into()?,
+            })
+        })
+        .collect::<Result<Vec<_>>>()?;
+
+    Ok((bins, decompress_multiplier))
+}
+
+/// Convert a legacy position to `PositionV2` the same way the program migrates it.
+pub fn migrate_legacy_position(position: Position) -> Result<PositionV2> {
+    let mut position_v2 = PositionV2::default();
+    position_v2.migrate_from_v1(RefCell::new(position).borrow())?;
+    Ok(position_v2)
+}
+
+/// `get_multiple_accounts` for any number of accounts. The RPC accepts at most 100 keys per request.
+pub async fn get_multiple_accounts_chunked<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    keys: &[Pubkey],
+) -> Result<Vec<Option<Account>>> {
+    let mut accounts = Vec::with_capacity(keys.len());
+    for chunk in keys.chunks(100) {
+        accounts.extend(program.async_rpc().get_multiple_accounts(chunk).await?);
+    }
+    Ok(accounts)
+}
+
+/// Offset of `PositionV2::owner`, after the discriminator and `lb_pair`. Same for legacy positions.
+pub const POSITION_OWNER_OFFSET: usize = 8 + 32;
+/// Offset of `PositionV2::operator`. It is followed by `lock_release_point`, `_padding_0`, `fee_owner` and `_reserved`.
+pub const POSITION_V2_OPERATOR_OFFSET: usize = 8 + PositionV2::INIT_SPACE - (32 + 8 + 1 + 32 + 87);
+/// Offset of `PositionV2::fee_owner`, after `operator`, `lock_release_point` and `_padding_0`.
+pub const POSITION_V2_FEE_OWNER_OFFSET: usize = POSITION_V2_OPERATOR_OFFSET + 32 + 8 + 1;
+
+/// Position owned or operated by a wallet. Legacy positions are converted to `PositionV2`.
+pub struct OwnedPosition {
+    pub key: Pubkey,
+    /// 1 for the legacy `Position` account, 2 for `PositionV2`
+    pub version: u8,
+    /// owner or operator
+    pub role: &'static str,
+    pub state: PositionV2,
+}
+
+/// Positions owned by `owner`, and the ones it operates. A position both owned and operated is listed once, as owner.
+pub async fn get_owned_positions<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    owner: Pubkey,
+) -> Result<Vec<OwnedPosition>> {
+    let owner_filter = || {
+        vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            POSITION_OWNER_OFFSET,
+            &owner.to_bytes(),
+        ))]
+    };
+
+    let owned_positions: Vec<(Pubkey, PositionV2)> = program.accounts(owner_filter()).await?;
+    let legacy_positions: Vec<(Pubkey, Position)> = program.accounts(owner_filter()).await?;
+    let operated_positions: Vec<(Pubkey, PositionV2)> = program
+        .accounts(vec![RpcFilterType::Memcmp(Memcmp::new_base58_encoded(
+            POSITION_V2_OPERATOR_OFFSET,
+            &owner.to_bytes(),
+        ))])
+        .await?;
+
+    let mut positions = owned_positions
+        .into_iter()
+        .map(|(key, state)| OwnedPosition {
+            key,
+            version: 2,
+            role: "owner",
+            state,
+        })
+        .collect::<Vec<_>>();
+
+    for (key, state) in legacy_positions {
+        positions.push(OwnedPosition {
+            key,
+            version: 1,
+            role: "owner",
+            state: migrate_legacy_position(state)?,
+        });
+    }
+
+    for (key, state) in operated_positions {
+        if state.owner != owner {
+            positions.push(OwnedPosition {
+                key,
+                version: 2,
+                role: "operator",
+                state,
+            });
+        }
+    }
+
+    Ok(positions)
+}
+
+pub async fn get_clock<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+) -> Result<Clock> {
+    let clock_account = program.async_rpc().get_account(&Clock::id()).await?;
+    let clock: Clock = bincode::deserialize(clock_account.data.as_ref())?;
+
+    Ok(clock)
+}
+
+/// Price token X, token Y and the reward tokens in token Y. Token X is priced at the active bin price. Reward tokens other than X and Y use the given UI prices, indexed by reward index.
+pub async fn get_reward_prices_in_token_y<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    lb_pair_state: &LbPair,
+    reward_prices: &[f64],
+) -> Result<RewardPrices> {
+    let x_mint: Mint = program.account(lb_pair_state.token_x_mint).await?;
+    let y_mint: Mint = program.account(lb_pair_state.token_y_mint).await?;
+
+    let q64x64_price = get_price_from_id(lb_pair_state.active_id, lb_pair_state.bin_step)?;
+    let decimal_price_per_lamport =
+        q64x64_price_to_decimal(q64x64_price).context("q64x64 price to decimal overflow")?;
+
+    let token_x_price = price_per_lamport_to_price_per_token(
+        decimal_price_per_lamport
+            .to_f64()
+            .context("Decimal conversion to f64 fail")?,
+        x_mint.decimals,
+        y_mint.decimals,
+    )
+    .and_then(|price| price.to_f64())
+    .context("price_per_lamport_to_price_per_token overflow")?;
+
+    let token_x = TokenPrice {
+        decimals: x_mint.decimals,
+        price: token_x_price,
+    };
+    let token_y = TokenPrice {
+        decimals: y_mint.decimals,
+        price: 1.0,
+    };
+
+    let mut rewards = [None; NUM_REWARDS];
+    for (i, reward_info) in lb_pair_state.reward_infos.iter().enumerate() {
+        if !reward_info.initialized() {
+            continue;
+        }
+        rewards[i] = if reward_info.mint == lb_pair_state.token_x_mint {
+            Some(token_x)
+        } else if reward_info.mint == lb_pair_state.token_y_mint {
+            Some(token_y)
+        } else if let Some(&price) = reward_prices.get(i) {
+            let reward_mint: Mint = program.account(reward_info.mint).await?;
+            Some(TokenPrice {
+                decimals: reward_mint.decimals,
+                price,
+            })
+        } else {
+            None
+        };
+    }
+
+    Ok(RewardPrices {
+        token_x,
+        token_y,
+        rewards,
+    })
+}
+
+/// Decimals of the reward mints, indexed by reward index. 0 for uninitialized rewards.
+pub async fn get_reward_mint_decimals<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    lb_pair_state: &LbPair,
+) -> Result<[u8; NUM_REWARDS]> {
+    let mut decimals = [0u8; NUM_REWARDS];
+    for (i, reward_info) in lb_pair_state.reward_infos.iter().enumerate() {
+        if !reward_info.initialized() {
+            continue;
+        }
+        let reward_mint: Mint = program.account(reward_info.mint).await?;
+        decimals[i] = reward_mint.decimals;
+    }
+
+    Ok(decimals)
+}
+
+#[derive(Debug, Serialize)]
+pub struct RewardReport {
+    pub reward_index: usize,
+    pub mint: String,
+    /// UI amount of reward accrued per day
+    pub reward_per_day: f64,
+    /// UI amount of reward left until the farm ends
+    pub remaining_reward: f64,
+    pub remaining_seconds: u64,
+    /// None when the reward or liquidity can't be priced
+    pub apr_pct: Option<f64>,
+}
+
+impl RewardReport {
+    /// Projections are in token amount. `reward_decimals` are the decimals of the reward mints, indexed by reward index.
+    pub fn new(projection: &RewardProjection, reward_decimals: &[u8; NUM_REWARDS]) -> Self {
+        let decimals = reward_decimals[projection.reward_index];
+        let to_ui_amount = |amount: f64| amount / 10f64.powi(decimals.into());
+        Self {
+            reward_index: projection.reward_index,
+            mint: projection.mint.to_string(),
+            reward_per_day: to_ui_amount(projection.reward_per_day),
+            remaining_reward: to_ui_amount(projection.remaining_reward),
+            remaining_seconds: projection.remaining_seconds,
+            apr_pct: projection.apr.map(|apr| apr * 100.0),
+        }
+    }
+}
+
+pub fn print_reward_reports(rewards: &[RewardReport]) {
+    if rewards.is_empty() {
+        return;
+    }
+
+    let rows = rewards
+        .iter()
+        .map(|reward| {
+            vec![
+                reward.reward_index.to_string(),
+                reward.mint.clone(),
+                reward.reward_per_day.to_string(),
+                reward.remaining_reward.to_string(),
+                reward.remaining_seconds.to_string(),
+                format_optional(reward.apr_pct.map(|apr| format!("{}%", apr))),
+            ]
+        })
+        .collect::<Vec<_>>();
+
+    println!();
+    print_table(
+        &[
+            "Reward",
+            "Mint",
+            "Per day",
+            "Remaining",
+            "Remaining seconds",
+            "APR",
+        ],
+        &rows,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lb_clmm::constants::{MAX_BIN_PER_POSITION, NUM_REWARDS};
+    use lb_clmm::state::position::{FeeInfo, UserRewardInfo};
+
+    #[test]
+    fn test_pack_instructions() {
+        let payer = Pubkey::new_unique();
+        let instruction = |data_len: usize, compute_units: u32| BudgetedInstruction {
+            instruction: Instruction {
+                program_id: lb_clmm::ID,
+                accounts: vec![],
+                data: vec![0u8; data_len],
+            },
+            compute_units,
+        };
+
+        // Split on the compute unit limit
+        let transactions = pack_instructions(
+            (0..5).map(|_| instruction(10, 400_000)).collect(),
+            &payer,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(
+            transactions.iter().map(Vec::len).collect::<Vec<_>>(),
+            vec![4, 3]
+        );
+
+        // Split on the transaction size
+        let transactions = pack_instructions(
+            (0..3).map(|_| instruction(500, 1_000)).collect(),
+            &payer,
+            None,
+            None,
+        )
+        .unwrap();
+        assert_eq!(transactions.len(), 2);
+
+        assert!(pack_instructions(vec![instruction(2_000, 1_000)], &payer, None, None).is_err());
+    }
+
+    #[test]
+    fn test_compress_bin_amounts() {
+        let (bins, decompress_multiplier) = compress_bin_amounts(&[(1, 10), (2, 20)]).unwrap();
+        assert_eq!(decompress_multiplier, 1);
+        assert_eq!(
+            bins.iter()
+                .map(|bin| (bin.bin_id, bin.amount))
+                .collect::<Vec<_>>(),
+            vec![(1, 10), (2, 20)]
+        );
+
+        let max_amount = u64::from(u32::MAX) * 3 + 1;
+        let (bins, decompress_multiplier) =
+            compress_bin_amounts(&[(1, max_amount), (2, 7)]).unwrap();
+        assert_eq!(decompress_multiplier, 4);
+        assert_eq!(bins[0].amount, (max_amount / 4) as u32);
+        assert_eq!(bins[1].amount, 1);
+
+        assert!(compress_bin_amounts(&[]).is_err());
+    }
+
+    #[test]
+    fn test_position_v2_operator_offset() {
+        let offset = 8
+            + 32
+            + 32
+            + 16 * MAX_BIN_PER_POSITION
+            + UserRewardInfo::INIT_SPACE * MAX_BIN_PER_POSITION
+            + FeeInfo::INIT_SPACE * MAX_BIN_PER_POSITION
+            + 4
+            + 4
+            + 8
+            + 8
+            + 8
+            + 8 * NUM_REWARDS;
+        assert_eq!(POSITION_V2_OPERATOR_OFFSET, offset);
+        assert_eq!(
+            POSITION_V2_FEE_OWNER_OFFSET + 32 + 87,
+            8 + PositionV2::INIT_SPACE
+        );
+    }
+
+    #[test]
+    fn test_reward_report_in_ui_amount() {
+        let projection = RewardProjection {
+            reward_index: 1,
+            mint: Pubkey::new_unique(),
+            reward_per_day: 86_400_000_000.0,
+            remaining_reward: 864_000_000_000.0,
+            remaining_seconds: 864_000,
+            apr: Some(0.5),
+        };
+
+        let report = RewardReport::new(&projection, &[9, 6]);
+        assert_eq!(report.reward_per_day, 86_400.0);
+        assert_eq!(report.remaining_reward, 864_000.0);
+        assert_eq!(report.apr_pct, Some(50.0));
+    }
+}
@@ -0,0 +1,118 @@
+use crate::instructions::utils::{get_clock, get_or_create_ata};
+use crate::math::to_ui_amount;
+use crate::transaction::{send_or_simulate, TransactionConfig};
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::{solana_sdk::pubkey::Pubkey, solana_sdk::signer::Signer, Program};
+use anchor_spl::token::Mint;
+use anyhow::*;
+use commons::rewards::get_ineligible_reward;
+use lb_clmm::accounts;
+use lb_clmm::constants::NUM_REWARDS;
+use lb_clmm::instruction;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::utils::pda::*;
+use std::ops::Deref;
+
+#[derive(Debug)]
+pub struct WithdrawIneligibleRewardParams {
+    pub lb_pair: Pubkey,
+    pub reward_index: u64,
+}
+
+pub async fn withdraw_ineligible_reward<C: Deref<Target = impl Signer> + Clone>(
+    params: WithdrawIneligibleRewardParams,
+    program: &Program<C>,
+    transaction_config: TransactionConfig,
+    compute_unit_price: Option<Instruction>,
+) -> Result<()> {
+    let WithdrawIneligibleRewardParams {
+        lb_pair,
+        reward_index,
+    } = params;
+
+    ensure!(
+        reward_index < NUM_REWARDS as u64,
+        "Reward index must be below {}",
+        NUM_REWARDS
+    );
+
+    let lb_pair_state: LbPair = program.account(lb_pair).await?;
+    let reward_info = lb_pair_state.reward_infos[reward_index as usize];
+    ensure!(
+        reward_info.initialized(),
+        "Reward {} of pair {} is not initialized",
+        reward_index,
+        lb_pair
+    );
+
+    let clock = get_clock(program).await?;
+    let current_timestamp = clock.unix_timestamp as u64;
+    ensure!(
+        current_timestamp > reward_info.reward_duration_end,
+        "Reward {} ends at {}. Its ineligible reward can only be withdrawn after",
+        reward_index,
+        reward_info.reward_duration_end
+    );
+
+    let active_bin_array_idx = BinArray::bin_id_to_bin_array_index(lb_pair_state.active_id)?;
+    let (bin_array, _bump) = derive_bin_array_pda(lb_pair, active_bin_array_idx.into());
+    let bin_array_state: BinArray = program.account(bin_array).await?;
+    let active_bin = bin_array_state.get_bin(lb_pair_state.active_id)?;
+
+    let ineligible_reward = get_ineligible_reward(
+        &lb_pair_state,
+        active_bin,
+        reward_index as usize,
+        current_timestamp,
+    )?;
+    let reward_mint: Mint = program.account(reward_info.mint).await?;
+    println!(
+        "Ineligible reward {} of pair {}: {} ({})",
+        reward_index,
+        lb_pair,
+        to_ui_amount(ineligible_reward, reward_mint.decimals),
+        ineligible_reward
+    );
+    if ineligible_reward == 0 {
+        println!("Nothing to withdraw");
+        return Ok(());
+    }
+
+    let funder_token_account = get_or_create_ata(
+        program,
+        transaction_config,
+        reward_info.mint,
+        program.payer(),
+        compute_unit_price.clone(),
+    )
+    .await?;
+
+    let (reward_vault, _bump) = derive_reward_vault_pda(lb_pair, reward_index);
+    let (event_authority, _bump) = derive_event_authority_pda();
+
+    let accounts = accounts::WithdrawIneligibleReward {
+        lb_pair,
+        reward_vault,
+        reward_mint: reward_info.mint,
+        funder_token_account,
+        funder: program.payer(),
+        bin_array,
+        token_program: anchor_spl::token::ID,
+        event_authority,
+        program: lb_clmm::ID,
+    };
+
+    let ix = instruction::WithdrawIneligibleReward { reward_index };
+
+    let mut request_builder = program.request();
+    if let Some(compute_unit_price) = compute_unit_price {
+        request_builder = request_builder.instruction(compute_unit_price);
+    }
+    let request_builder = request_builder.accounts(accounts).args(ix);
+    if let Some(signature) = send_or_simulate(program, request_builder, transaction_config).await? {
+        println!("Withdraw ineligible reward. Signature: {}", signature);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,350 @@
+use std::fs::File;
+use std::future::Future;
+use std::io::{BufReader, BufWriter, Write};
+use std::ops::Deref;
+use std::time::Duration;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::{Program, RequestBuilder};
+use anyhow::*;
+use commons::sender::SendEvent;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+use crate::transaction::{send_or_simulate_with_events, TransactionConfig, TransactionMode};
+
+/// Blocks a started step may still land after the last valid block height recorded when it started. The blockhash
+/// of the transaction is fetched right after the recorded one, so it may expire a little later.
+const LAST_VALID_BLOCK_HEIGHT_MARGIN: u64 = 32;
+/// Polling interval while waiting for a started step to land or expire
+const PENDING_STEP_POLL_INTERVAL: Duration = Duration::from_secs(2);
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case", tag = "status")]
+pub enum StepStatus {
+    /// The transaction of the step was sent, and may land until `last_valid_block_height`
+    Started { last_valid_block_height: u64 },
+    /// The step landed. The signature is unknown when the step was found done on chain.
+    Confirmed { signature: Option<String> },
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct JournalStep {
+    pub key: String,
+    #[serde(flatten)]
+    pub status: StepStatus,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct JournalFile {
+    command: String,
+    steps: Vec<JournalStep>,
+    /// Command specific state, such as the snapshot taken before the dust redistribution of the seeding
+    #[serde(default)]
+    data: serde_json::Value,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum StepAction {
+    Skip,
+    Send,
+    /// A previous attempt may still land
+    Wait,
+}
+
+/// What to do with a step, from its journal status, whether its effect is on chain, and the current block height.
+fn get_step_action(
+    status: Option<&StepStatus>,
+    landed: bool,
+    block_height: u64,
+) -> Result<StepAction> {
+    match (status, landed) {
+        (_, true) => Ok(StepAction::Skip),
+        (None, false) => Ok(StepAction::Send),
+        (Some(StepStatus::Confirmed { .. }), false) => {
+            Err(anyhow!("confirmed in the journal but not found on chain"))
+        }
+        (
+            Some(StepStatus::Started {
+                last_valid_block_height,
+            }),
+            false,
+        ) => {
+            if block_height > last_valid_block_height + LAST_VALID_BLOCK_HEIGHT_MARGIN {
+                Ok(StepAction::Send)
+            } else {
+                Ok(StepAction::Wait)
+            }
+        }
+    }
+}
+
+/// Progress of a multi-transaction command, kept in `<TARGET>_<COMMAND>_journal.json` so a crashed or failed run
+/// resumes without sending a step twice. Each step is checked against the chain before it is sent. Nothing is
+/// written when the transactions are simulated or exported.
+pub struct Journal {
+    path: String,
+    persist: bool,
+    file: JournalFile,
+}
+
+impl Journal {
+    pub fn open(
+        command: &str,
+        target: Pubkey,
+        transaction_config: TransactionConfig,
+    ) -> Result<Self> {
+        let path = format!("{}_{}_journal.json", target, command);
+
+        let file = match File::open(&path) {
+            std::io::Result::Ok(file) => {
+                let file: JournalFile = serde_json::from_reader(BufReader::new(file))
+                    .with_context(|| format!("Invalid journal {}", path))?;
+                ensure!(
+                    file.command == command,
+                    "Journal {} belongs to {}",
+                    path,
+                    file.command
+                );
+                println!("Resume from journal {}", path);
+                file
+            }
+            std::io::Result::Err(_) => JournalFile {
+                command: command.to_string(),
+                ..Default::default()
+            },
+        };
+
+        Ok(Self {
+            path,
+            persist: matches!(transaction_config.mode, TransactionMode::Send),
+            file,
+        })
+    }
+
+    pub fn path(&self) -> &str {
+        &self.path
+    }
+
+    pub fn step(&self, key: &str) -> Option<&JournalStep> {
+        self.file.steps.iter().find(|step| step.key == key)
+    }
+
+    /// Command specific state, or the default when none was saved.
+    pub fn data<T: DeserializeOwned + Default>(&self) -> Result<T> {
+        if self.file.data.is_null() {
+            return Ok(T::default());
+        }
+        Ok(serde_json::from_value(self.file.data.clone())?)
+    }
+
+    pub fn set_data<T: Serialize>(&mut self, data: &T) -> Result<()> {
+        self.file.data = serde_json::to_value(data)?;
+        self.write()
+    }
+
+    fn set_status(&mut self, key: &str, status: StepStatus) -> Result<()> {
+        match self.file.steps.iter_mut().find(|step| step.key == key) {
+            Some(step) => step.status = status,
+            None => self.file.steps.push(JournalStep {
+                key: key.to_string(),
+                status,
+            }),
+        }
+        self.write()
+    }
+
+    fn write(&self) -> Result<()> {
+        if !self.persist {
+            return Ok(());
+        }
+        let mut writer = BufWriter::new(File::create(&self.path)?);
+        serde_json::to_writer_pretty(&mut writer, &self.file)?;
+        writer.flush()?;
+        Ok(())
+    }
+
+    /// Send the transaction of step `key` unless `landed` finds its effect on chain. A step started by a previous
+    /// run is only sent again once its transaction can no longer land. Returns the signature when the step was sent.
+    /// `signers` sign the transaction besides the payer, as in `send_or_simulate_with_signers`.
+    pub async fn run_step<'a, C: Deref<Target = impl Signer> + Clone, F, Fut>(
+        &mut self,
+        program: &'a Program<C>,
+        key: &str,
+        landed: F,
+        request_builder: RequestBuilder<'a, C>,
+        signers: &[&'a dyn Signer],
+        transaction_config: TransactionConfig<'_>,
+    ) -> Result<Option<Signature>>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<bool>>,
+    {
+        if self.wait_step(program, key, landed).await? {
+            return Ok(None);
+        }
+        self.send_step(program, key, request_builder, signers, transaction_config)
+            .await
+    }
+
+    /// Wait until step `key` either landed, or can be sent without a previous attempt landing as well. Returns true
+    /// when `landed` finds its effect on chain.
+    pub async fn wait_step<C: Deref<Target = impl Signer> + Clone, F, Fut>(
+        &mut self,
+        program: &Program<C>,
+        key: &str,
+        landed: F,
+    ) -> Result<bool>
+    where
+        F: Fn() -> Fut,
+        Fut: Future<Output = Result<bool>>,
+    {
+        let rpc_client = program.async_rpc();
+
+        let mut waiting = false;
+        loop {
+            let landed = landed().await?;
+            let status = self.step(key).map(|step| step.status.clone());
+            let block_height = match status {
+                Some(StepStatus::Started { .. }) if !landed => {
+                    rpc_client.get_block_height().await?
+                }
+                _ => 0,
+            };
+
+            let action =
+                get_step_action(status.as_ref(), landed, block_height).with_context(|| {
+                    format!(
+                    "Step {} is inconsistent. Check the chain state, then delete {} to start over",
+                    key, self.path
+                )
+                })?;
+            match action {
+                StepAction::Skip => {
+                    if !matches!(status, Some(StepStatus::Confirmed { .. })) {
+                        self.set_status(key, StepStatus::Confirmed { signature: None })?;
+                    }
+                    return Ok(true);
+                }
+                StepAction::Send => return Ok(false),
+                StepAction::Wait => {
+                    if !waiting {
+                        println!(
+                            "Wait for the previous attempt of step {} to land or expire",
+                            key
+                        );
+                        waiting = true;
+                    }
+                    tokio::time::sleep(PENDING_STEP_POLL_INTERVAL).await;
+                }
+            }
+        }
+    }
+
+    /// Send the transaction of step `key`, recorded as started with the expiry of its latest signing until it is
+    /// confirmed. Call [`Journal::wait_step`] first.
+    pub async fn send_step<'a, C: Deref<Target = impl Signer> + Clone>(
+        &mut self,
+        program: &'a Program<C>,
+        key: &str,
+        request_builder: RequestBuilder<'a, C>,
+        signers: &[&'a dyn Signer],
+        transaction_config: TransactionConfig<'_>,
+    ) -> Result<Option<Signature>> {
+        self.send_steps(
+            program,
+            &[key.to_string()],
+            request_builder,
+            signers,
+            transaction_config,
+        )
+        .await
+    }
+
+    /// Same as [`Journal::send_step`] for a transaction doing the steps `keys` at once. As the transaction lands
+    /// atomically, they're all recorded with the same status. Call [`Journal::wait_step`] for each of them first.
+    pub async fn send_steps<'a, C: Deref<Target = impl Signer> + Clone>(
+        &mut self,
+        program: &'a Program<C>,
+        keys: &[String],
+        request_builder: RequestBuilder<'a, C>,
+        signers: &[&'a dyn Signer],
+        transaction_config: TransactionConfig<'_>,
+    ) -> Result<Option<Signature>> {
+        // Each signing with a fresh blockhash extends the time the step may land
+        let signature = send_or_simulate_with_events(
+            program,
+            request_builder,
+            signers,
+            transaction_config,
+            |event| {
+                println!("{event}");
+                if let SendEvent::Signed {
+                    last_valid_block_height,
+                    ..
+                } = event
+                {
+                    for key in keys {
+                        self.set_status(
+                            key,
+                            StepStatus::Started {
+                                last_valid_block_height: *last_valid_block_height,
+                            },
+                        )?;
+                    }
+                }
+                Ok(())
+            },
+        )
+        .await?;
+        if let Some(signature) = signature {
+            for key in keys {
+                self.set_status(
+                    key,
+                    StepStatus::Confirmed {
+                        signature: Some(signature.to_string()),
+                    },
+                )?;
+            }
+        }
+
+        Ok(signature)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_step_action() {
+        let started = StepStatus::Started {
+            last_valid_block_height: 100,
+        };
+        let confirmed = StepStatus::Confirmed { signature: None };
+
+        assert_eq!(get_step_action(None, false, 0).unwrap(), StepAction::Send);
+        assert_eq!(get_step_action(None, true, 0).unwrap(), StepAction::Skip);
+        assert_eq!(
+            get_step_action(Some(&confirmed), true, 0).unwrap(),
+            StepAction::Skip
+        );
+        assert!(get_step_action(Some(&confirmed), false, 0).is_err());
+
+        // A started step is only sent again once its transaction expired
+        assert_eq!(
+            get_step_action(Some(&started), true, 50).unwrap(),
+            StepAction::Skip
+        );
+        assert_eq!(
+            get_step_action(Some(&started), false, 100 + LAST_VALID_BLOCK_HEIGHT_MARGIN).unwrap(),
+            StepAction::Wait
+        );
+        assert_eq!(
+            get_step_action(Some(&started), false, 101 + LAST_VALID_BLOCK_HEIGHT_MARGIN).unwrap(),
+            StepAction::Send
+        );
+    }
+}
@@ -0,0 +1,150 @@
+use std::fmt;
+use std::rc::Rc;
+
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_sdk::address_lookup_table::state::AddressLookupTable;
+use anchor_client::solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::{v0, VersionedMessage};
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::transaction::VersionedTransaction;
+use anyhow::*;
+
+/// Address lookup tables given by `--lookup-table`. Transactions are compiled against them as v0 transactions.
+pub struct LookupTables {
+    /// Fee payer. The anchor request builders only sign legacy transactions, so the v0 ones are signed here.
+    payer: Rc<dyn Signer>,
+    accounts: Vec<AddressLookupTableAccount>,
+}
+
+impl fmt::Debug for LookupTables {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.debug_struct("LookupTables")
+            .field("payer", &self.payer.pubkey())
+            .field(
+                "accounts",
+                &self
+                    .accounts
+                    .iter()
+                    .map(|account| account.key)
+                    .collect::<Vec<_>>(),
+            )
+            .finish()
+    }
+}
+
+fn deserialize_lookup_table(key: &Pubkey, data: &[u8]) -> Result<AddressLookupTableAccount> {
+    let lookup_table = AddressLookupTable::deserialize(data)
+        .map_err(|err| anyhow!("{} is not an address lookup table: {}", key, err))?;
+    Ok(AddressLookupTableAccount {
+        key: *key,
+        addresses: lookup_table.addresses.to_vec(),
+    })
+}
+
+impl LookupTables {
+    pub async fn fetch(
+        rpc_client: &RpcClient,
+        payer: Rc<dyn Signer>,
+        keys: &[Pubkey],
+    ) -> Result<Self> {
+        let accounts = rpc_client
+            .get_multiple_accounts(keys)
+            .await?
+            .into_iter()
+            .zip(keys)
+            .map(|(account, key)| {
+                let account = account.with_context(|| format!("Lookup table {} not found", key))?;
+                deserialize_lookup_table(key, &account.data)
+            })
+            .collect::<Result<Vec<_>>>()?;
+
+        Ok(Self { payer, accounts })
+    }
+
+    pub fn accounts(&self) -> &[AddressLookupTableAccount] {
+        &self.accounts
+    }
+
+    pub fn compile(
+        &self,
+        instructions: &[Instruction],
+        blockhash: Hash,
+    ) -> Result<VersionedMessage> {
+        let message = v0::Message::try_compile(
+            &self.payer.pubkey(),
+            instructions,
+            &self.accounts,
+            blockhash,
+        )?;
+        Ok(VersionedMessage::V0(message))
+    }
+
+    /// Compile and sign `instructions` with the fee payer and the `signers` the message requires.
+    pub fn sign(
+        &self,
+        instructions: &[Instruction],
+        signers: &[&dyn Signer],
+        blockhash: Hash,
+    ) -> Result<VersionedTransaction> {
+        let message = self.compile(instructions, blockhash)?;
+        let required_signers =
+            &message.static_account_keys()[..usize::from(message.header().num_required_signatures)];
+
+        // The signers must match the required signers exactly, without duplicates
+        let mut keypairs: Vec<&dyn Signer> = vec![];
+        for signer in std::iter::once(self.payer.as_ref()).chain(signers.iter().copied()) {
+            let pubkey = signer.pubkey();
+            if required_signers.contains(&pubkey)
+                && !keypairs.iter().any(|keypair| keypair.pubkey() == pubkey)
+            {
+                keypairs.push(signer);
+            }
+        }
+
+        Ok(VersionedTransaction::try_new(message, &keypairs)?)
+    }
+
+    /// Account keys of `message`, in the order its instructions index them: the static keys, then the writable and
+    /// the readonly keys loaded from the lookup tables.
+    pub fn get_account_keys(&self, message: &VersionedMessage) -> Result<Vec<Pubkey>> {
+        let mut account_keys = message.static_account_keys().to_vec();
+        let Some(lookups) = message.address_table_lookups() else {
+            return Ok(account_keys);
+        };
+
+        let mut writable_keys = vec![];
+        let mut readonly_keys = vec![];
+        for lookup in lookups {
+            let account = self
+                .accounts
+                .iter()
+                .find(|account| account.key == lookup.account_key)
+                .with_context(|| format!("Lookup table {} not given", lookup.account_key))?;
+            let load = |index: &u8| {
+                account
+                    .addresses
+                    .get(usize::from(*index))
+                    .copied()
+                    .with_context(|| {
+                        format!(
+                            "Address {} of lookup table {} not found",
+                            index, account.key
+                        )
+                    })
+            };
+            for index in lookup.writable_indexes.iter() {
+                writable_keys.push(load(index)?);
+            }
+            for index in lookup.readonly_indexes.iter() {
+                readonly_keys.push(load(index)?);
+            }
+        }
+        account_keys.extend(writable_keys);
+        account_keys.extend(readonly_keys);
+
+        Ok(account_keys)
+    }
+}
@@ -7,18 +7,32 @@ use anchor_client::Client;
 use anchor_client::{
     solana_client::rpc_config::RpcSendTransactionConfig,
     solana_sdk::{
-        commitment_config::CommitmentConfig,
+        pubkey::Pubkey,
         signer::{keypair::*, Signer},
     },
 };
 use anyhow::*;
 use clap::*;
+use commons::compute_budget::{ComputeBudgetConfig, PriorityFee, DEFAULT_COMPUTE_UNIT_MARGIN_BPS};
 
 mod args;
+mod config;
+mod export;
 mod instructions;
+mod journal;
+mod lookup_table;
 mod math;
+mod output;
+mod seed_curve;
+mod transaction;
+mod wallet;
 
 use args::*;
+use config::{
+    get_config_path, get_profile_arg, run_config_command, set_pair_aliases, ConfigFile,
+    ResolvedConfig,
+};
+use export::{BlockhashSource, TransactionExport};
 use instructions::get_all_positions::get_all_positions;
 use instructions::initialize_customizable_permissionless_lb_pair::InitCustomizablePermissionlessLbPairParameters;
 use instructions::initialize_lb_pair::*;
@@ -31,7 +45,12 @@ use instructions::seed_liquidity_single_bin::{
 use instructions::seed_liquidity_single_bin_by_operator::{
     seed_liquidity_single_bin_by_operator, SeedLiquiditySingleBinByOperatorParameters,
 };
-use lb_clmm::state::preset_parameters::PresetParameter;
+use instructions::seed_plan::SeedPlanOptions;
+use lookup_table::LookupTables;
+use output::OutputFormat;
+use seed_curve::{PowerCurve, SeedCurve};
+use transaction::{TransactionConfig, TransactionMode};
+use wallet::Wallet;
 
 use crate::instructions::initialize_bin_array_with_bin_range::{
     initialize_bin_array_with_bin_range, InitBinArrayWithBinRangeParameters,
@@ -44,11 +63,22 @@ use crate::{
     args::Command,
     instructions::{
         add_liquidity::{add_liquidity, AddLiquidityParam},
+        add_liquidity_by_strategy::{add_liquidity_by_strategy, AddLiquidityByStrategyParameters},
+        add_liquidity_by_strategy_one_side::{
+            add_liquidity_by_strategy_one_side, AddLiquidityByStrategyOneSideParameters,
+        },
+        add_liquidity_by_weight::{add_liquidity_by_weight, AddLiquidityByWeightParameters},
+        add_liquidity_one_side::{add_liquidity_one_side, AddLiquidityOneSideParameters},
+        add_liquidity_one_side_precise::{
+            add_liquidity_one_side_precise, AddLiquidityOneSidePreciseParameters,
+        },
         check_my_balance::{check_my_balance, CheckMyBalanceParameters},
+        claim_all::{claim_all, ClaimAllParameters},
         claim_fee::claim_fee,
         claim_reward::*,
         close_position::close_position,
         close_preset_parameter::close_preset_parameter,
+        find_pairs::{find_pairs, FindPairsParameters},
         fund_reward::*,
         increase_length::{increase_length, IncreaseLengthParams},
         initialize_bin_array::{initialize_bin_array, InitBinArrayParameters},
@@ -63,6 +93,12 @@ use crate::{
         initialize_preset_parameter::initialize_preset_parameter,
         initialize_reward::*,
         list_all_binstep::list_all_binstep,
+        migrate_bin_array::{migrate_bin_array, MigrateBinArrayParameters},
+        migrate_position::{migrate_position, MigratePositionParameters},
+        portfolio::{portfolio, PortfolioParameters},
+        quote::{quote, QuoteMode, QuoteParameters},
+        rebalance_position::{rebalance_position, RebalancePositionParameters},
+        remove_all_liquidity::remove_all_liquidity,
         remove_liquidity::{remove_liquidity, RemoveLiquidityParameters},
         remove_liquidity_by_price_range::{
             remove_liquidity_by_price_range, RemoveLiquidityByPriceRangeParameters,
@@ -73,14 +109,22 @@ use crate::{
         set_pre_activation_swap_address::{
             set_pre_activation_swap_address, SetPreactivationSwapAddressParam,
         },
+        setup_pair_lookup_table::{setup_pair_lookup_table, SetupPairLookupTableParameters},
         show_pair::show_pair,
+        show_position::show_position,
+        show_preset_parameter::show_preset_parameter,
         simulate_swap_demand::{simulate_swap_demand, SimulateSwapDemandParameters},
+        submit::{submit, SubmitParameters},
         swap_exact_in::{swap, SwapExactInParameters},
         swap_exact_out::{swap_exact_out, SwapExactOutParameters},
         swap_with_price_impact::{swap_with_price_impact, SwapWithPriceImpactParameters},
         toggle_pair_status::toggle_pool_status,
+        update_fee_parameters::{update_fee_parameters, UpdateFeeParametersParams},
+        update_fees_and_rewards::update_fees_and_rewards,
+        update_position_operator::{update_position_operator, UpdatePositionOperatorParameters},
         update_reward_duration::*,
         update_reward_funder::*,
+        withdraw_ineligible_reward::{withdraw_ineligible_reward, WithdrawIneligibleRewardParams},
         withdraw_protocol_fee::{withdraw_protocol_fee, WithdrawProtocolFeeParams},
     },
 };
@@ -95,33 +139,126 @@ fn get_set_compute_unit_price_ix(micro_lamports: u64) -> Option<Instruction> {
     }
 }
 
+fn get_compute_budget_config(
+    config_override: &ConfigOverride,
+    config: &ResolvedConfig,
+) -> Option<ComputeBudgetConfig> {
+    if !config_override.auto_compute_budget && config.priority_fee_percentile.is_none() {
+        return None;
+    }
+    Some(ComputeBudgetConfig {
+        compute_unit_margin_bps: config_override
+            .compute_unit_margin_bps
+            .unwrap_or(DEFAULT_COMPUTE_UNIT_MARGIN_BPS),
+        priority_fee: match config.priority_fee_percentile {
+            Some(percentile) => PriorityFee::Percentile(percentile),
+            None => PriorityFee::Fixed(config.priority_fee),
+        },
+        max_priority_fee_lamports: config.max_priority_fee,
+    })
+}
+
 #[tokio::main]
 async fn main() -> Result<()> {
+    let config_path = get_config_path();
+    let config_file = ConfigFile::read(&config_path)?;
+    let profile_name = config_file
+        .get_profile_name(get_profile_arg(&std::env::args().collect::<Vec<_>>()).as_deref());
+    // The pair aliases must be known before parsing, as they are accepted wherever a public key is.
+    // A missing profile is reported once the command is known, since `config set` creates it.
+    if let Result::Ok(profile) = config_file.get_profile(&profile_name) {
+        set_pair_aliases(&profile)?;
+    }
+
     let cli = Cli::parse();
 
-    let payer =
-        read_keypair_file(cli.config_override.wallet).expect("Wallet keypair file not found");
+    if let Command::Config(config_command) = cli.command {
+        return run_config_command(config_command, config_file, &profile_name, &config_path);
+    }
+    let config = config_file
+        .get_profile(&profile_name)?
+        .resolve(&cli.config_override)?;
+
+    let wallet = match Wallet::from_arg(&config.wallet) {
+        Result::Ok(wallet) => Some(wallet),
+        Err(_) if cli.command.is_read_only() => None,
+        Err(err) => return Err(err),
+    };
+
+    let output = cli.config_override.output;
+    // Keep stdout parseable for json output
+    if output != OutputFormat::Json {
+        if let Some(wallet) = wallet.as_ref() {
+            println!("Wallet {:#?}", wallet.pubkey());
+        }
+    }
+
+    // Read-only commands never sign, any public key does as the payer of the client
+    let wallet = wallet.unwrap_or(Wallet::Pubkey(Pubkey::default()));
+    let wallet_pubkey = wallet.pubkey();
 
-    println!("Wallet {:#?}", payer.pubkey());
+    let transaction_export = match cli.config_override.export {
+        Some(path) => {
+            let blockhash_source = match (cli.config_override.blockhash, cli.config_override.nonce)
+            {
+                (Some(blockhash), _) => BlockhashSource::Fixed(blockhash),
+                (None, Some(nonce)) => BlockhashSource::Nonce {
+                    account: nonce,
+                    authority: cli.config_override.nonce_authority.unwrap_or(wallet_pubkey),
+                },
+                (None, None) => BlockhashSource::Recent,
+            };
+            let signer = if cli.config_override.sign_only {
+                let keypair = wallet
+                    .keypair()
+                    .context("--sign-only requires a wallet keypair file")?;
+                Some(Keypair::from_bytes(&keypair.to_bytes())?)
+            } else {
+                None
+            };
+            Some(TransactionExport::new(path, blockhash_source, signer))
+        }
+        None => None,
+    };
 
-    let commitment_config = CommitmentConfig::confirmed();
-    let client = Client::new_with_options(
-        cli.config_override.cluster,
-        Rc::new(Keypair::from_bytes(&payer.to_bytes())?),
-        commitment_config,
-    );
+    let commitment_config = config.commitment;
+    let wallet = Rc::new(wallet);
+    let client =
+        Client::new_with_options(config.cluster.clone(), wallet.clone(), commitment_config);
 
     let amm_program = client.program(lb_clmm::ID).unwrap();
 
-    let transaction_config: RpcSendTransactionConfig = RpcSendTransactionConfig {
-        skip_preflight: false,
-        preflight_commitment: Some(commitment_config.commitment),
-        encoding: None,
-        max_retries: None,
-        min_context_slot: None,
+    let lookup_tables = if cli.config_override.lookup_tables.is_empty() {
+        None
+    } else {
+        Some(
+            LookupTables::fetch(
+                &amm_program.async_rpc(),
+                wallet,
+                &cli.config_override.lookup_tables,
+            )
+            .await?,
+        )
     };
 
-    let compute_unit_price_ix = get_set_compute_unit_price_ix(cli.config_override.priority_fee);
+    let transaction_config = TransactionConfig {
+        send_config: RpcSendTransactionConfig {
+            skip_preflight: false,
+            preflight_commitment: Some(commitment_config.commitment),
+            encoding: None,
+            max_retries: None,
+            min_context_slot: None,
+        },
+        mode: match transaction_export.as_ref() {
+            Some(transaction_export) => TransactionMode::Export(transaction_export),
+            None if cli.config_override.simulate => TransactionMode::Simulate,
+            None => TransactionMode::Send,
+        },
+        compute_budget: get_compute_budget_config(&cli.config_override, &config),
+        lookup_tables: lookup_tables.as_ref(),
+    };
+
+    let compute_unit_price_ix = get_set_compute_unit_price_ix(config.priority_fee);
 
     match cli.command {
         Command::InitializePair {
@@ -222,6 +359,116 @@ async fn main() -> Result<()> {
             )
             .await?;
         }
+        Command::AddLiquidityByStrategy {
+            position,
+            amount_x,
+            amount_y,
+            bin_range,
+            strategy,
+            max_active_bin_slippage,
+        } => {
+            let params = AddLiquidityByStrategyParameters {
+                position,
+                amount_x,
+                amount_y,
+                bin_range: bin_range.into(),
+                strategy_type: strategy.into(),
+                max_active_bin_slippage,
+            };
+            add_liquidity_by_strategy(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::AddLiquidityByStrategyOneSide {
+            position,
+            token,
+            amount,
+            bin_range,
+            strategy,
+            max_active_bin_slippage,
+        } => {
+            let params = AddLiquidityByStrategyOneSideParameters {
+                position,
+                token_y: token == TokenSide::Y,
+                amount,
+                bin_range: bin_range.into(),
+                strategy_type: strategy.into(),
+                max_active_bin_slippage,
+            };
+            add_liquidity_by_strategy_one_side(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::AddLiquidityByWeight {
+            position,
+            amount_x,
+            amount_y,
+            bin_weights,
+            max_active_bin_slippage,
+        } => {
+            let params = AddLiquidityByWeightParameters {
+                position,
+                amount_x,
+                amount_y,
+                bin_weights,
+                max_active_bin_slippage,
+            };
+            add_liquidity_by_weight(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::AddLiquidityOneSide {
+            position,
+            token,
+            amount,
+            bin_weights,
+            max_active_bin_slippage,
+        } => {
+            let params = AddLiquidityOneSideParameters {
+                position,
+                token_y: token == TokenSide::Y,
+                amount,
+                bin_weights,
+                max_active_bin_slippage,
+            };
+            add_liquidity_one_side(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::AddLiquidityOneSidePrecise {
+            position,
+            token,
+            bin_amounts,
+        } => {
+            let params = AddLiquidityOneSidePreciseParameters {
+                position,
+                token_y: token == TokenSide::Y,
+                bin_amounts,
+            };
+            add_liquidity_one_side_precise(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
         Command::RemoveLiquidity {
             lb_pair,
             position,
@@ -244,22 +491,84 @@ async fn main() -> Result<()> {
             lb_pair,
             amount_in,
             swap_for_y,
+            slippage_bps,
+            min_amount_out,
+            host_fee_account,
         } => {
             let params = SwapExactInParameters {
                 amount_in,
                 lb_pair,
                 swap_for_y,
+                slippage_bps: slippage_bps.unwrap_or(config.slippage_bps),
+                min_amount_out,
+                host_fee_account,
             };
-            swap(params, &amm_program, transaction_config).await?;
+            swap(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
         }
 
-        Command::ShowPair { lb_pair } => {
-            show_pair(lb_pair, &amm_program).await?;
+        Command::QuoteExactIn {
+            lb_pair,
+            amount_in,
+            swap_for_y,
+            account_files,
+        } => {
+            let params = QuoteParameters {
+                lb_pair,
+                swap_for_y,
+                mode: QuoteMode::ExactIn { amount_in },
+                account_files,
+            };
+            quote(params, &amm_program, output).await?;
         }
-        Command::ShowPosition { position } => {
-            let position: lb_clmm::state::position::Position =
-                amm_program.account(position).await?;
-            println!("{:#?}", position);
+        Command::QuoteExactOut {
+            lb_pair,
+            amount_out,
+            swap_for_y,
+            account_files,
+        } => {
+            let params = QuoteParameters {
+                lb_pair,
+                swap_for_y,
+                mode: QuoteMode::ExactOut { amount_out },
+                account_files,
+            };
+            quote(params, &amm_program, output).await?;
+        }
+        Command::QuotePriceImpact {
+            lb_pair,
+            amount_in,
+            swap_for_y,
+            price_impact_bps,
+            account_files,
+        } => {
+            let params = QuoteParameters {
+                lb_pair,
+                swap_for_y,
+                mode: QuoteMode::PriceImpact {
+                    amount_in,
+                    max_price_impact_bps: price_impact_bps,
+                },
+                account_files,
+            };
+            quote(params, &amm_program, output).await?;
+        }
+        Command::ShowPair {
+            lb_pair,
+            reward_prices,
+        } => {
+            show_pair(lb_pair, reward_prices, &amm_program, output).await?;
+        }
+        Command::ShowPosition {
+            position,
+            reward_prices,
+        } => {
+            show_position(position, reward_prices, &amm_program, output).await?;
         }
 
         Command::ClaimReward {
@@ -307,6 +616,63 @@ async fn main() -> Result<()> {
         Command::ClosePosition { position } => {
             close_position(position, &amm_program, transaction_config).await?;
         }
+        Command::RemoveAllLiquidity { position } => {
+            remove_all_liquidity(
+                position,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::MigratePosition { position, all } => {
+            let params = MigratePositionParameters {
+                position: if all { None } else { position },
+            };
+            migrate_position(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::MigrateBinArray {
+            lb_pair,
+            bin_arrays,
+            all,
+        } => {
+            let params = MigrateBinArrayParameters {
+                lb_pair,
+                bin_arrays: if all { vec![] } else { bin_arrays },
+            };
+            migrate_bin_array(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::UpdatePositionOperator { position, operator } => {
+            let params = UpdatePositionOperatorParameters { position, operator };
+            update_position_operator(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::UpdateFeesAndRewards { position } => {
+            update_fees_and_rewards(
+                position,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
         Command::ClaimFee { position } => {
             claim_fee(
                 position,
@@ -316,6 +682,40 @@ async fn main() -> Result<()> {
             )
             .await?;
         }
+        Command::ClaimAll { fee_owner } => {
+            let params = ClaimAllParameters { fee_owner };
+            claim_all(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
+        Command::RebalancePosition {
+            position,
+            strategy,
+            width,
+            target_x_ratio,
+            slippage_bps,
+            max_active_bin_slippage,
+        } => {
+            let params = RebalancePositionParameters {
+                position,
+                strategy_type: strategy.into(),
+                width,
+                target_x_ratio,
+                slippage_bps: slippage_bps.unwrap_or(config.slippage_bps),
+                max_active_bin_slippage,
+            };
+            rebalance_position(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
+        }
         Command::IncreaseLength {
             lb_pair,
             length_to_add,
@@ -328,12 +728,11 @@ async fn main() -> Result<()> {
         }
 
         Command::ShowPresetParameter { preset_parameter } => {
-            let preset_param_state: PresetParameter = amm_program.account(preset_parameter).await?;
-            println!("{:#?}", preset_param_state);
+            show_preset_parameter(preset_parameter, &amm_program, output).await?;
         }
 
         Command::ListAllBinStep => {
-            list_all_binstep(&amm_program).await?;
+            list_all_binstep(&amm_program, output).await?;
         }
         Command::SimulateSwapDemand {
             lb_pair,
@@ -359,27 +758,47 @@ async fn main() -> Result<()> {
             lb_pair,
             amount_out,
             swap_for_y,
+            slippage_bps,
+            max_amount_in,
+            host_fee_account,
         } => {
             let params = SwapExactOutParameters {
                 lb_pair,
                 amount_out,
                 swap_for_y,
+                slippage_bps: slippage_bps.unwrap_or(config.slippage_bps),
+                max_amount_in,
+                host_fee_account,
             };
-            swap_exact_out(params, &amm_program, transaction_config).await?;
+            swap_exact_out(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
         }
         Command::SwapWithPriceImpact {
             lb_pair,
             amount_in,
             swap_for_y,
             price_impact_bps,
+            host_fee_account,
         } => {
             let params = SwapWithPriceImpactParameters {
                 lb_pair,
                 amount_in,
                 swap_for_y,
                 price_impact_bps,
+                host_fee_account,
             };
-            swap_with_price_impact(params, &amm_program, transaction_config).await?;
+            swap_with_price_impact(
+                params,
+                &amm_program,
+                transaction_config,
+                compute_unit_price_ix,
+            )
+            .await?;
         }
         Command::InitializeCustomizablePermissionlessLbPair {
             token_mint_x,
@@ -419,9 +838,16 @@ async fn main() -> Result<()> {
             max_price,
             base_pubkey,
             curvature,
+            curve,
+            plot,
+            plan,
             position_owner_path,
             max_retries,
         } => {
+            let curve = curve
+                .or(curvature.map(|curvature| SeedCurve::Power(PowerCurve { curvature })))
+                .context("--curve or --curvature is required")?;
+
             let mut retry_count = 0;
             loop {
                 let position_base_kp = read_keypair_file(base_position_path.clone())
@@ -438,7 +864,12 @@ async fn main() -> Result<()> {
                     max_price,
                     base_pubkey,
                     position_owner_kp,
-                    curvature,
+                    curve: curve.clone(),
+                    plot,
+                    plan: plan.then_some(SeedPlanOptions {
+                        compute_unit_price: config.priority_fee,
+                        output,
+                    }),
                 };
                 if let Err(err) = seed_liquidity(
                     params,
@@ -468,11 +899,18 @@ async fn main() -> Result<()> {
             max_price,
             base_pubkey,
             curvature,
+            curve,
+            plot,
+            plan,
             position_owner,
             fee_owner,
             lock_release_point,
             max_retries,
         } => {
+            let curve = curve
+                .or(curvature.map(|curvature| SeedCurve::Power(PowerCurve { curvature })))
+                .context("--curve or --curvature is required")?;
+
             let mut retry_count = 0;
             loop {
                 let position_base_kp = read_keypair_file(base_position_path.clone())
@@ -488,7 +926,12 @@ async fn main() -> Result<()> {
                     position_owner,
                     fee_owner,
                     lock_release_point,
-                    curvature,
+                    curve: curve.clone(),
+                    plot,
+                    plan: plan.then_some(SeedPlanOptions {
+                        compute_unit_price: config.priority_fee,
+                        output,
+                    }),
                 };
                 if let Err(err) = seed_liquidity_by_operator(
                     params,
@@ -575,8 +1018,46 @@ async fn main() -> Result<()> {
             )
             .await?;
         }
+        Command::FindPairs {
+            token_a_mint,
+            token_b_mint,
+            depth_bps,
+        } => {
+            let params = FindPairsParameters {
+                token_a_mint,
+                token_b_mint,
+                depth_bps,
+            };
+            find_pairs(params, &amm_program, output).await?;
+        }
         Command::GetAllPositionsForAnOwner { lb_pair, owner } => {
-            get_all_positions(&amm_program, lb_pair, owner).await?;
+            get_all_positions(&amm_program, lb_pair, owner, output).await?;
+        }
+        Command::Portfolio {
+            owner,
+            quote_prices,
+        } => {
+            let params = PortfolioParameters {
+                owner,
+                quote_prices,
+            };
+            portfolio(params, &amm_program, output).await?;
+        }
+        Command::SetupPairLookupTable {
+            lb_pair,
+            table,
+            bin_array_radius,
+        } => {
+            let params = SetupPairLookupTableParameters {
+                lb_pair,
+                table,
+                bin_array_radius,
+            };
+            setup_pair_lookup_table(params, &amm_program, transaction_config).await?;
+        }
+        Command::Submit { files, signatures } => {
+            let params = SubmitParameters { files, signatures };
+            submit(params, &amm_program, transaction_config).await?;
         }
         Command::Admin(admin_command) => match admin_command {
             AdminCommand::InitializePermissionPair {
@@ -638,7 +1119,7 @@ async fn main() -> Result<()> {
                     min_price,
                     max_price,
                 };
-                check_my_balance(params, &amm_program).await?;
+                check_my_balance(params, &amm_program, output).await?;
             }
             AdminCommand::SetActivationPoint {
                 activation_point,
@@ -726,6 +1207,36 @@ async fn main() -> Result<()> {
                 };
                 initialize_reward(params, &amm_program, transaction_config).await?;
             }
+            AdminCommand::UpdateFeeParameters {
+                lb_pair,
+                protocol_share,
+                base_factor,
+                base_fee_bps,
+            } => {
+                let params = UpdateFeeParametersParams {
+                    lb_pair,
+                    protocol_share,
+                    base_factor,
+                    base_fee_bps,
+                };
+                update_fee_parameters(params, &amm_program, transaction_config).await?;
+            }
+            AdminCommand::WithdrawIneligibleReward {
+                lb_pair,
+                reward_index,
+            } => {
+                let params = WithdrawIneligibleRewardParams {
+                    lb_pair,
+                    reward_index,
+                };
+                withdraw_ineligible_reward(
+                    params,
+                    &amm_program,
+                    transaction_config,
+                    compute_unit_price_ix,
+                )
+                .await?;
+            }
             AdminCommand::SetPreActivationSwapAddress {
                 lb_pair,
                 pre_activation_swap_address,
@@ -1,12 +1,10 @@
-use anyhow::{anyhow, Result};
-use lb_clmm::constants::BASIS_POINT_MAX;
+use anyhow::{anyhow, ensure, Context, Result};
+use lb_clmm::constants::{BASIS_POINT_MAX, FEE_PRECISION};
 use lb_clmm::math::price_math::get_price_from_id;
-use lb_clmm::math::u128x128_math::Rounding;
-use rust_decimal::MathematicalOps;
-use rust_decimal::{
-    prelude::{FromPrimitive, ToPrimitive},
-    Decimal,
-};
+use rust_decimal::prelude::{FromPrimitive, ToPrimitive};
+use rust_decimal::Decimal;
+
+pub use commons::math::*;
 
 pub fn find_swappable_min_max_bin_id(bin_step: u16) -> Result<(i32, i32)> {
     let base = 1.0f64 + (bin_step as f64 / BASIS_POINT_MAX as f64);
@@ -76,167 +74,48 @@ pub fn compute_base_factor_from_fee_bps(bin_step: u16, fee_bps: u16) -> Result<u
     Ok(computed_base_factor as u16)
 }
 
-pub fn get_precise_id_from_price(bin_step: u16, price: &Decimal) -> Option<i32> {
-    let bps = Decimal::from_u16(bin_step)?.checked_div(Decimal::from_i32(BASIS_POINT_MAX)?)?;
-    let base = Decimal::ONE.checked_add(bps)?;
-
-    let id = price.log10().checked_div(base.log10())?.to_f64()?;
-    let trimmed_id = id as i32;
-    let trimmed_id_f64 = trimmed_id as f64;
-
-    if trimmed_id_f64 == id {
-        id.to_i32()
-    } else {
-        None
-    }
-}
-
-/// Calculate the bin id based on price. If the bin id is in between 2 bins, it will round up.
-pub fn get_id_from_price(bin_step: u16, price: &Decimal, rounding: Rounding) -> Option<i32> {
-    let bps = Decimal::from_u16(bin_step)?.checked_div(Decimal::from_i32(BASIS_POINT_MAX)?)?;
-    let base = Decimal::ONE.checked_add(bps)?;
-
-    let id = match rounding {
-        Rounding::Down => price.log10().checked_div(base.log10())?.floor(),
-        Rounding::Up => price.log10().checked_div(base.log10())?.ceil(),
-    };
-
-    id.to_i32()
-}
-
-/// Convert Q64xQ64 price to human readable decimal. This is price per lamport.
-pub fn q64x64_price_to_decimal(q64x64_price: u128) -> Option<Decimal> {
-    let q_price = Decimal::from_u128(q64x64_price)?;
-    let scale_off = Decimal::TWO.powu(lb_clmm::math::u64x64_math::SCALE_OFFSET.into());
-    q_price.checked_div(scale_off)
+/// UI price of token X in token Y of the bin.
+pub fn get_ui_price_from_id(
+    bin_id: i32,
+    bin_step: u16,
+    token_x_decimals: u8,
+    token_y_decimals: u8,
+) -> Result<f64> {
+    let q64x64_price = get_price_from_id(bin_id, bin_step)?;
+    let decimal_price_per_lamport = q64x64_price_to_decimal(q64x64_price)
+        .and_then(|price| price.to_f64())
+        .context("q64x64 price to decimal overflow")?;
+
+    price_per_lamport_to_price_per_token(
+        decimal_price_per_lamport,
+        token_x_decimals,
+        token_y_decimals,
+    )
+    .and_then(|price| price.to_f64())
+    .context("price_per_lamport_to_price_per_token overflow")
 }
 
-/// price_per_lamport = price_per_token * 10 ** quote_token_decimal / 10 ** base_token_decimal
-pub fn price_per_token_to_per_lamport(
-    price_per_token: f64,
-    base_token_decimal: u8,
-    quote_token_decimal: u8,
-) -> Option<Decimal> {
-    let price_per_token = Decimal::from_f64(price_per_token)?;
-    price_per_token
-        .checked_mul(Decimal::TEN.powu(quote_token_decimal.into()))?
-        .checked_div(Decimal::TEN.powu(base_token_decimal.into()))
+pub fn to_ui_amount(amount: u64, decimals: u8) -> f64 {
+    amount as f64 / 10f64.powi(decimals.into())
 }
 
-/// price_per_token = price_per_lamport * 10 ** base_token_decimal / 10 ** quote_token_decimal, Solve for price_per_lamport
-pub fn price_per_lamport_to_price_per_token(
-    price_per_lamport: f64,
-    base_token_decimal: u8,
-    quote_token_decimal: u8,
-) -> Option<Decimal> {
-    let one_ui_base_token_amount = Decimal::TEN.powu(base_token_decimal.into());
-    let one_ui_quote_token_amount = Decimal::TEN.powu(quote_token_decimal.into());
-    let price_per_lamport = Decimal::from_f64(price_per_lamport)?;
-
-    one_ui_base_token_amount
-        .checked_mul(price_per_lamport)?
-        .checked_div(one_ui_quote_token_amount)
+/// Amount in the smallest unit of the token of a UI amount, rounded down.
+pub fn to_amount(ui_amount: f64, decimals: u8) -> Result<u64> {
+    ensure!(
+        ui_amount.is_finite() && ui_amount >= 0.0,
+        "Invalid amount {}",
+        ui_amount
+    );
+    Decimal::from_f64(ui_amount)
+        .and_then(|amount| amount.checked_mul(Decimal::from(10u64.checked_pow(decimals.into())?)))
+        .and_then(|amount| amount.floor().to_u64())
+        .with_context(|| format!("Amount {} overflow", ui_amount))
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-    use lb_clmm::math::{price_math::get_price_from_id, u64x64_math::SCALE_OFFSET};
-    use proptest::proptest;
-
-    proptest! {
-        #[test]
-        fn test_get_id_from_price_range(
-            bin_step in 1..=BASIS_POINT_MAX as u16,
-            price in 0.000000000000000001f64..=u64::MAX as f64
-        ) {
-            let price = Decimal::from_f64(price);
-            assert!(price.is_some());
-            let id = get_id_from_price(bin_step, &price.unwrap(), Rounding::Up);
-            assert!(id.is_some());
-        }
-    }
-
-    #[test]
-    fn test_q64x64_price_to_decimal() {
-        let q64x64_price: u128 = 408988714829317079040;
-        let decimal_price = q64x64_price_to_decimal(q64x64_price);
-
-        assert!(decimal_price.is_some());
-        assert_eq!(
-            decimal_price.unwrap().to_string(),
-            "22.17132265700000104402533907"
-        );
-    }
-
-    #[test]
-    fn test_price_per_lamport_to_price_per_token() {
-        let price_per_lamport = 0.211713226574294_f64;
-        let base_token_decimal = 8u8;
-        let quote_token_decimal = 6u8;
-
-        let price_per_token = price_per_lamport_to_price_per_token(
-            price_per_lamport,
-            base_token_decimal,
-            quote_token_decimal,
-        );
-        assert!(price_per_token.is_some());
-
-        let recomputed_price_per_lamport = price_per_token.unwrap()
-            * Decimal::TEN.powu(quote_token_decimal.into())
-            / Decimal::TEN.powu(base_token_decimal.into());
-
-        let recomputed_price_per_lamport = recomputed_price_per_lamport.to_f64();
-        assert!(recomputed_price_per_lamport.is_some());
-        assert_eq!(Some(price_per_lamport), recomputed_price_per_lamport);
-    }
-
-    #[test]
-    fn test_price_per_token_to_per_lamport() {
-        let price_per_token = 9.95769;
-        let base_token_decimal = 8u8;
-        let quote_token_decimal = 6u8;
-
-        let price_per_lamport = price_per_token_to_per_lamport(
-            price_per_token,
-            base_token_decimal,
-            quote_token_decimal,
-        );
-        assert!(price_per_lamport.is_some());
-
-        let recomputed_price_per_token = price_per_lamport.unwrap()
-            * Decimal::TEN.powu(base_token_decimal.into())
-            / Decimal::TEN.powu(quote_token_decimal.into());
-
-        let recomputed_price_per_token = recomputed_price_per_token.to_f64();
-        assert!(recomputed_price_per_token.is_some());
-
-        assert_eq!(Some(price_per_token), recomputed_price_per_token);
-    }
-
-    #[test]
-    fn test_get_id_from_price() {
-        let bin_step = 15;
-        let quote_decimal = 6u8;
-        let price = Decimal::from_f64(208.929000).unwrap();
-
-        let computed_id = get_id_from_price(bin_step, &price, Rounding::Up);
-        assert!(computed_id.is_some());
-
-        let program_computed_price = get_price_from_id(computed_id.unwrap(), bin_step);
-        assert!(program_computed_price.is_ok());
-
-        let computed_price_fixed = Decimal::from_u128(program_computed_price.unwrap());
-        assert!(computed_price_fixed.is_some());
-
-        let fixed_to_dec_scale_off = Decimal::TWO.powu(SCALE_OFFSET.into());
-
-        let computed_price_dec = (computed_price_fixed.unwrap()
-            * Decimal::TEN.powu(quote_decimal.into())
-            / fixed_to_dec_scale_off)
-            .floor();
-
-        let computed_price = computed_price_dec.to_u64();
-        assert_eq!(computed_price, Some(208929004));
-    }
+/// Convert a fee rate in FEE_PRECISION to percentage.
+pub fn fee_rate_to_fee_pct(fee_rate: u128) -> Option<f64> {
+    Decimal::from_u128(fee_rate)?
+        .checked_div(Decimal::from(FEE_PRECISION))?
+        .checked_mul(Decimal::ONE_HUNDRED)?
+        .to_f64()
 }
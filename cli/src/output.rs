@@ -0,0 +1,129 @@
+use anyhow::Result;
+use clap::ValueEnum;
+use serde::Serialize;
+use std::fmt::Debug;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, ValueEnum)]
+pub enum OutputFormat {
+    /// Versioned JSON document, for scripts
+    Json,
+    /// Human readable tables
+    #[default]
+    Table,
+    /// Rust debug dump of the report
+    Debug,
+}
+
+/// Output of a read command. The JSON schema of a report is identified by `COMMAND` and `SCHEMA_VERSION`.
+/// Bump `SCHEMA_VERSION` whenever a field is renamed, removed or changes meaning. Adding a field is not a breaking change.
+pub trait Report: Serialize + Debug {
+    const COMMAND: &'static str;
+    const SCHEMA_VERSION: u32;
+
+    fn print_table(&self);
+}
+
+#[derive(Serialize)]
+struct Envelope<'a, T: Serialize> {
+    command: &'static str,
+    schema_version: u32,
+    data: &'a T,
+}
+
+pub fn to_json<T: Report>(report: &T) -> Result<String> {
+    let envelope = Envelope {
+        command: T::COMMAND,
+        schema_version: T::SCHEMA_VERSION,
+        data: report,
+    };
+    Ok(serde_json::to_string_pretty(&envelope)?)
+}
+
+pub fn print_report<T: Report>(output: OutputFormat, report: &T) -> Result<()> {
+    match output {
+        OutputFormat::Json => println!("{}", to_json(report)?),
+        OutputFormat::Table => report.print_table(),
+        OutputFormat::Debug => println!("{:#?}", report),
+    }
+
+    Ok(())
+}
+
+/// Print one `key: value` per line, with the values aligned.
+pub fn print_key_values(rows: &[(&str, String)]) {
+    let width = rows.iter().map(|(key, _)| key.len()).max().unwrap_or(0);
+    for (key, value) in rows {
+        println!("{:<width$}  {}", key, value, width = width);
+    }
+}
+
+/// Print rows as columns aligned on the widest cell.
+pub fn print_table(headers: &[&str], rows: &[Vec<String>]) {
+    let mut widths = headers
+        .iter()
+        .map(|header| header.len())
+        .collect::<Vec<_>>();
+    for row in rows {
+        for (width, cell) in widths.iter_mut().zip(row) {
+            *width = (*width).max(cell.len());
+        }
+    }
+
+    let format_row = |cells: Vec<&str>| {
+        cells
+            .iter()
+            .zip(widths.iter())
+            .map(|(cell, width)| format!("{:<width$}", cell, width = width))
+            .collect::<Vec<_>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+
+    let separator = widths
+        .iter()
+        .map(|width| "-".repeat(*width))
+        .collect::<Vec<_>>();
+
+    println!("{}", format_row(headers.to_vec()));
+    println!(
+        "{}",
+        format_row(separator.iter().map(String::as_str).collect())
+    );
+    for row in rows {
+        println!("{}", format_row(row.iter().map(String::as_str).collect()));
+    }
+}
+
+pub fn format_optional<T: ToString>(value: Option<T>) -> String {
+    value
+        .map(|value| value.to_string())
+        .unwrap_or_else(|| "N/A".to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[derive(Debug, Serialize)]
+    struct TestReport {
+        amount: u64,
+    }
+
+    impl Report for TestReport {
+        const COMMAND: &'static str = "test";
+        const SCHEMA_VERSION: u32 = 2;
+
+        fn print_table(&self) {}
+    }
+
+    #[test]
+    fn test_json_envelope() {
+        let json = to_json(&TestReport { amount: u64::MAX }).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+
+        assert_eq!(value["command"], "test");
+        assert_eq!(value["schema_version"], 2);
+        assert_eq!(value["data"]["amount"], u64::MAX);
+    }
+}
@@ -0,0 +1,445 @@
+use std::fs::read_to_string;
+use std::str::FromStr;
+
+use anyhow::*;
+
+/// Width of the longest bar of `plot_bin_amounts`
+const PLOT_WIDTH: usize = 50;
+/// Max number of rows of `plot_bin_amounts`. Neighbouring bins are summed into a row beyond it.
+const PLOT_MAX_ROWS: usize = 40;
+
+/// Bin of a seeding range, with the UI prices of its lower and upper edges
+#[derive(Debug, Clone, Copy)]
+pub struct BinPrice {
+    pub bin_id: i32,
+    pub price: f64,
+    pub next_price: f64,
+}
+
+/// Shape of the liquidity seeded over a price range.
+pub trait BinDistribution {
+    /// Relative, non-negative weight of each bin, in the order of `bins`. `min_price` and `max_price` are the UI
+    /// prices of the seeding range.
+    fn weights(&self, bins: &[BinPrice], min_price: f64, max_price: f64) -> Result<Vec<f64>>;
+}
+
+/// c(p) = amount * ((p - min_price) / (max_price - min_price)) ^ (1 / curvature). A bin gets c(next_price) - c(price).
+#[derive(Debug, Clone)]
+pub struct PowerCurve {
+    pub curvature: f64,
+}
+
+impl BinDistribution for PowerCurve {
+    fn weights(&self, bins: &[BinPrice], min_price: f64, max_price: f64) -> Result<Vec<f64>> {
+        ensure!(self.curvature > 0.0, "Curvature must be positive");
+        ensure!(max_price > min_price, "Invalid price range");
+
+        let k = 1.0 / self.curvature;
+        let c = |price: f64| {
+            ((price - min_price) / (max_price - min_price))
+                .clamp(0.0, 1.0)
+                .powf(k)
+        };
+
+        Ok(bins
+            .iter()
+            .map(|bin| (c(bin.next_price) - c(bin.price)).max(0.0))
+            .collect())
+    }
+}
+
+/// Weight moving linearly from `start_weight` on the first bin to `end_weight` on the last bin
+#[derive(Debug, Clone)]
+pub struct LinearCurve {
+    pub start_weight: f64,
+    pub end_weight: f64,
+}
+
+impl BinDistribution for LinearCurve {
+    fn weights(&self, bins: &[BinPrice], _min_price: f64, _max_price: f64) -> Result<Vec<f64>> {
+        Ok((0..bins.len())
+            .map(|i| {
+                self.start_weight
+                    + (self.end_weight - self.start_weight) * bin_position(i, bins.len())
+            })
+            .collect())
+    }
+}
+
+/// Weight of exp(rate * x), x moving from 0 on the first bin to 1 on the last bin. A negative rate front-loads the range.
+#[derive(Debug, Clone)]
+pub struct ExponentialCurve {
+    pub rate: f64,
+}
+
+impl BinDistribution for ExponentialCurve {
+    fn weights(&self, bins: &[BinPrice], _min_price: f64, _max_price: f64) -> Result<Vec<f64>> {
+        Ok((0..bins.len())
+            .map(|i| (self.rate * bin_position(i, bins.len())).exp())
+            .collect())
+    }
+}
+
+/// Log-normal bell centered on `mean_price`, `sigma` being the standard deviation of the log price
+#[derive(Debug, Clone)]
+pub struct BellCurve {
+    pub mean_price: f64,
+    pub sigma: f64,
+}
+
+impl BinDistribution for BellCurve {
+    fn weights(&self, bins: &[BinPrice], _min_price: f64, _max_price: f64) -> Result<Vec<f64>> {
+        ensure!(self.mean_price > 0.0, "Mean price must be positive");
+        ensure!(self.sigma > 0.0, "Sigma must be positive");
+
+        Ok(bins
+            .iter()
+            .map(|bin| {
+                let mid_price = (bin.price * bin.next_price).sqrt();
+                let z = (mid_price / self.mean_price).ln() / self.sigma;
+                (-z * z / 2.0).exp()
+            })
+            .collect())
+    }
+}
+
+/// Steps of (start price, share). A tranche covers the bins from its start price to the start price of the next one,
+/// and its share is split evenly between them. Bins below the first tranche get nothing.
+#[derive(Debug, Clone)]
+pub struct TrancheCurve {
+    pub tranches: Vec<(f64, f64)>,
+}
+
+impl BinDistribution for TrancheCurve {
+    fn weights(&self, bins: &[BinPrice], _min_price: f64, _max_price: f64) -> Result<Vec<f64>> {
+        let mut tranches = self.tranches.clone();
+        tranches.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let tranche_of =
+            |bin: &BinPrice| tranches.iter().rposition(|(price, _)| bin.price >= *price);
+
+        let mut bin_counts = vec![0usize; tranches.len()];
+        for tranche in bins.iter().filter_map(tranche_of) {
+            bin_counts[tranche] += 1;
+        }
+
+        Ok(bins
+            .iter()
+            .map(|bin| match tranche_of(bin) {
+                Some(tranche) => tranches[tranche].1 / bin_counts[tranche] as f64,
+                None => 0.0,
+            })
+            .collect())
+    }
+}
+
+/// Weights of a CSV file of `price,weight` rows. A bin gets the weight of the last row priced at or below it, and
+/// nothing below the first row. A header row is allowed.
+#[derive(Debug, Clone)]
+pub struct CsvCurve {
+    pub path: String,
+}
+
+impl CsvCurve {
+    fn read_points(&self) -> Result<Vec<(f64, f64)>> {
+        let content =
+            read_to_string(&self.path).with_context(|| format!("Failed to read {}", self.path))?;
+
+        let mut points = vec![];
+        for (i, line) in content.lines().enumerate() {
+            let line = line.trim();
+            if line.is_empty() {
+                continue;
+            }
+            let point = line.split_once(',').and_then(|(price, weight)| {
+                Some((
+                    price.trim().parse::<f64>().ok()?,
+                    weight.trim().parse::<f64>().ok()?,
+                ))
+            });
+            match point {
+                Some(point) => points.push(point),
+                None if i == 0 => continue,
+                None => bail!("Invalid row {} of {}: {}", i + 1, self.path, line),
+            }
+        }
+        ensure!(!points.is_empty(), "{} has no price,weight row", self.path);
+
+        points.sort_by(|a, b| a.0.total_cmp(&b.0));
+        Ok(points)
+    }
+}
+
+impl BinDistribution for CsvCurve {
+    fn weights(&self, bins: &[BinPrice], _min_price: f64, _max_price: f64) -> Result<Vec<f64>> {
+        let points = self.read_points()?;
+
+        Ok(bins
+            .iter()
+            .map(|bin| {
+                points
+                    .iter()
+                    .rev()
+                    .find(|(price, _)| bin.price >= *price)
+                    .map(|(_, weight)| *weight)
+                    .unwrap_or_default()
+            })
+            .collect())
+    }
+}
+
+/// Curve of the `seed-liquidity*` commands, parsed from `--curve`.
+///
+/// power:CURVATURE | linear:START,END | exponential:RATE | bell:MEAN_PRICE,SIGMA | tranche:PRICE=SHARE,... | csv:PATH
+#[derive(Debug, Clone)]
+pub enum SeedCurve {
+    Power(PowerCurve),
+    Linear(LinearCurve),
+    Exponential(ExponentialCurve),
+    Bell(BellCurve),
+    Tranche(TrancheCurve),
+    Csv(CsvCurve),
+}
+
+impl BinDistribution for SeedCurve {
+    fn weights(&self, bins: &[BinPrice], min_price: f64, max_price: f64) -> Result<Vec<f64>> {
+        match self {
+            SeedCurve::Power(curve) => curve.weights(bins, min_price, max_price),
+            SeedCurve::Linear(curve) => curve.weights(bins, min_price, max_price),
+            SeedCurve::Exponential(curve) => curve.weights(bins, min_price, max_price),
+            SeedCurve::Bell(curve) => curve.weights(bins, min_price, max_price),
+            SeedCurve::Tranche(curve) => curve.weights(bins, min_price, max_price),
+            SeedCurve::Csv(curve) => curve.weights(bins, min_price, max_price),
+        }
+    }
+}
+
+fn parse_f64s<const N: usize>(src: &str) -> Result<[f64; N]> {
+    let values = src
+        .split(',')
+        .map(|value| value.trim().parse::<f64>())
+        .collect::<std::result::Result<Vec<_>, _>>()
+        .with_context(|| format!("Invalid number in {}", src))?;
+
+    values
+        .try_into()
+        .map_err(|_| anyhow!("Expected {} comma separated numbers, got {}", N, src))
+}
+
+impl FromStr for SeedCurve {
+    type Err = Error;
+
+    fn from_str(src: &str) -> Result<Self> {
+        let (kind, params) = src.split_once(':').unwrap_or((src, ""));
+
+        let curve = match kind {
+            "power" => {
+                let [curvature] = parse_f64s(params)?;
+                SeedCurve::Power(PowerCurve { curvature })
+            }
+            "linear" => {
+                let [start_weight, end_weight] = parse_f64s(params)?;
+                SeedCurve::Linear(LinearCurve {
+                    start_weight,
+                    end_weight,
+                })
+            }
+            "exponential" => {
+                let [rate] = parse_f64s(params)?;
+                SeedCurve::Exponential(ExponentialCurve { rate })
+            }
+            "bell" => {
+                let [mean_price, sigma] = parse_f64s(params)?;
+                SeedCurve::Bell(BellCurve { mean_price, sigma })
+            }
+            "tranche" => {
+                let tranches = params
+                    .split(',')
+                    .map(|tranche| {
+                        let (price, share) = tranche
+                            .split_once('=')
+                            .with_context(|| format!("Expected PRICE=SHARE, got {}", tranche))?;
+                        Ok((price.trim().parse::<f64>()?, share.trim().parse::<f64>()?))
+                    })
+                    .collect::<Result<Vec<_>>>()?;
+                SeedCurve::Tranche(TrancheCurve { tranches })
+            }
+            "csv" => {
+                ensure!(!params.is_empty(), "Missing csv path");
+                SeedCurve::Csv(CsvCurve {
+                    path: params.to_string(),
+                })
+            }
+            _ => bail!(
+                "Unknown curve {}. Expected power, linear, exponential, bell, tranche or csv",
+                kind
+            ),
+        };
+
+        Ok(curve)
+    }
+}
+
+/// Position of the i-th of `count` bins, from 0 for the first bin to 1 for the last one
+fn bin_position(i: usize, count: usize) -> f64 {
+    if count > 1 {
+        i as f64 / (count - 1) as f64
+    } else {
+        0.0
+    }
+}
+
+/// Split `amount` proportionally to `weights`. The amounts sum to `amount` exactly: the units lost to rounding down
+/// go one each to the bins with the largest rounding remainders, the lower index first on ties.
+pub fn distribute_amount(amount: u64, weights: &[f64]) -> Result<Vec<u64>> {
+    ensure!(!weights.is_empty(), "No bin to distribute to");
+    ensure!(
+        weights
+            .iter()
+            .all(|weight| weight.is_finite() && *weight >= 0.0),
+        "Curve weights must be finite and non-negative"
+    );
+
+    let total_weight = weights.iter().sum::<f64>();
+    ensure!(total_weight > 0.0, "Curve weights are all zero");
+
+    // Integer weights, with the precision of a f64 mantissa
+    let weights = weights
+        .iter()
+        .map(|weight| (weight / total_weight * (1u64 << 52) as f64) as u128)
+        .collect::<Vec<_>>();
+    let total_weight = weights.iter().sum::<u128>();
+    ensure!(total_weight > 0, "Curve weights are all zero");
+
+    let mut amounts = vec![];
+    let mut remainders = vec![];
+    for weight in weights.iter() {
+        let scaled_amount = u128::from(amount) * weight;
+        amounts.push((scaled_amount / total_weight) as u64);
+        remainders.push(scaled_amount % total_weight);
+    }
+
+    let distributed = amounts
+        .iter()
+        .map(|amount| u128::from(*amount))
+        .sum::<u128>();
+    let leftover = (u128::from(amount) - distributed) as usize;
+
+    let mut order = (0..amounts.len()).collect::<Vec<_>>();
+    order.sort_by(|a, b| remainders[*b].cmp(&remainders[*a]).then(a.cmp(b)));
+    for i in order.into_iter().take(leftover) {
+        amounts[i] += 1;
+    }
+
+    Ok(amounts)
+}
+
+/// Horizontal bar chart of the amount seeded to each bin. Neighbouring bins are summed into a row when there are
+/// more than `PLOT_MAX_ROWS` bins.
+pub fn plot_bin_amounts(bins: &[BinPrice], amounts: &[u64]) {
+    let bins_per_row = bins.len().div_ceil(PLOT_MAX_ROWS).max(1);
+
+    let rows = bins
+        .chunks(bins_per_row)
+        .zip(amounts.chunks(bins_per_row))
+        .map(|(bins, amounts)| (bins[0].price, amounts.iter().sum::<u64>()))
+        .collect::<Vec<_>>();
+    let max_amount = rows.iter().map(|(_, amount)| *amount).max().unwrap_or(0);
+
+    for (price, amount) in rows {
+        let width = if max_amount > 0 {
+            (amount as f64 / max_amount as f64 * PLOT_WIDTH as f64).round() as usize
+        } else {
+            0
+        };
+        println!(
+            "{:>16.8} | {:<PLOT_WIDTH$} {}",
+            price,
+            "█".repeat(width),
+            amount
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn bins(prices: &[f64]) -> Vec<BinPrice> {
+        prices
+            .windows(2)
+            .enumerate()
+            .map(|(i, window)| BinPrice {
+                bin_id: i as i32,
+                price: window[0],
+                next_price: window[1],
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_distribute_amount() {
+        let amounts = distribute_amount(10, &[1.0, 1.0, 1.0]).unwrap();
+        assert_eq!(amounts, vec![4, 3, 3]);
+
+        let amounts = distribute_amount(u64::MAX, &[0.3, 0.0, 2.7, 1e-9]).unwrap();
+        assert_eq!(
+            amounts.iter().map(|a| *a as u128).sum::<u128>(),
+            u64::MAX as u128
+        );
+        assert_eq!(amounts[1], 0);
+
+        assert!(distribute_amount(10, &[0.0, 0.0]).is_err());
+        assert!(distribute_amount(10, &[1.0, f64::NAN]).is_err());
+        assert!(distribute_amount(10, &[]).is_err());
+    }
+
+    #[test]
+    fn test_curves() {
+        let bins = bins(&[1.0, 2.0, 3.0, 4.0, 5.0]);
+
+        // Curvature 1 spreads the amount evenly over the price
+        let weights = PowerCurve { curvature: 1.0 }
+            .weights(&bins, 1.0, 5.0)
+            .unwrap();
+        assert_eq!(weights, vec![0.25; 4]);
+
+        let weights = LinearCurve {
+            start_weight: 1.0,
+            end_weight: 4.0,
+        }
+        .weights(&bins, 1.0, 5.0)
+        .unwrap();
+        assert_eq!(weights, vec![1.0, 2.0, 3.0, 4.0]);
+
+        let weights = TrancheCurve {
+            tranches: vec![(3.0, 60.0), (2.0, 40.0)],
+        }
+        .weights(&bins, 1.0, 5.0)
+        .unwrap();
+        assert_eq!(weights, vec![0.0, 40.0, 30.0, 30.0]);
+
+        let weights = BellCurve {
+            mean_price: 6f64.sqrt(),
+            sigma: 0.5,
+        }
+        .weights(&bins, 1.0, 5.0)
+        .unwrap();
+        assert_eq!(weights[1], 1.0);
+        assert!(weights[0] < weights[1] && weights[2] < weights[1]);
+    }
+
+    #[test]
+    fn test_parse_seed_curve() {
+        assert!(matches!(
+            "power:0.8".parse::<SeedCurve>().unwrap(),
+            SeedCurve::Power(PowerCurve { curvature }) if curvature == 0.8
+        ));
+        assert!(matches!(
+            "tranche:0.1=40, 0.2=60".parse::<SeedCurve>().unwrap(),
+            SeedCurve::Tranche(TrancheCurve { tranches }) if tranches == vec![(0.1, 40.0), (0.2, 60.0)]
+        ));
+        assert!("linear:1".parse::<SeedCurve>().is_err());
+        assert!("cubic:1".parse::<SeedCurve>().is_err());
+    }
+}
@@ -0,0 +1,595 @@
+use std::collections::HashSet;
+use std::ops::Deref;
+
+use anchor_client::solana_account_decoder::UiAccount;
+use anchor_client::solana_client::rpc_config::{RpcSendTransactionConfig, RpcTransactionConfig};
+use anchor_client::solana_client::rpc_request::RpcRequest;
+use anchor_client::solana_client::rpc_response::Response;
+use anchor_client::solana_sdk::account::Account;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::signer::Signer;
+use anchor_client::solana_sdk::transaction::{Transaction, TransactionError, VersionedTransaction};
+use anchor_client::{Program, RequestBuilder};
+use anchor_lang::event::EVENT_IX_TAG_LE;
+use anchor_lang::{AccountDeserialize, AnchorDeserialize, Discriminator};
+use anchor_spl::token::TokenAccount;
+use anyhow::*;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use commons::compute_budget::{estimate_compute_budget, ComputeBudgetConfig};
+use commons::sender::{send_and_confirm, SendConfig, SendEvent};
+use lb_clmm::events;
+use lb_clmm::state::lb_pair::LbPair;
+use serde::Deserialize;
+use serde_json::json;
+use solana_transaction_status::{
+    UiInnerInstructions, UiInstruction, UiParsedInstruction, UiTransactionEncoding,
+};
+
+use crate::export::TransactionExport;
+use crate::lookup_table::LookupTables;
+use crate::output::print_table;
+
+#[derive(Debug, Clone, Copy)]
+pub enum TransactionMode<'a> {
+    Send,
+    /// Only simulate the transactions and print what they would do. Nothing is sent.
+    Simulate,
+    /// Write the transactions to an export file for offline or multisig signing. Nothing is sent.
+    Export(&'a TransactionExport),
+}
+
+/// How the state-changing commands deliver their transactions.
+#[derive(Debug, Clone, Copy)]
+pub struct TransactionConfig<'a> {
+    pub send_config: RpcSendTransactionConfig,
+    pub mode: TransactionMode<'a>,
+    /// Size the compute unit limit and price of each sent or exported transaction by simulation, instead of the
+    /// compute budget set by the command
+    pub compute_budget: Option<ComputeBudgetConfig>,
+    /// Compile the sent and simulated transactions against these address lookup tables, as v0 transactions
+    pub lookup_tables: Option<&'a LookupTables>,
+}
+
+/// Send the transaction built by `request_builder`, or simulate or export it depending on the mode.
+/// Returns `None` when the transaction was not sent.
+pub async fn send_or_simulate<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    request_builder: RequestBuilder<'_, C>,
+    transaction_config: TransactionConfig<'_>,
+) -> Result<Option<Signature>> {
+    send_or_simulate_with_signers(program, request_builder, &[], transaction_config).await
+}
+
+/// Same as `send_or_simulate` for a transaction also signed by `signers`. They are given here instead of to the
+/// request builder, so the transaction can be rebuilt with the estimated compute budget.
+pub async fn send_or_simulate_with_signers<'a, C: Deref<Target = impl Signer> + Clone>(
+    program: &'a Program<C>,
+    request_builder: RequestBuilder<'a, C>,
+    signers: &[&'a dyn Signer],
+    transaction_config: TransactionConfig<'_>,
+) -> Result<Option<Signature>> {
+    send_or_simulate_with_events(
+        program,
+        request_builder,
+        signers,
+        transaction_config,
+        print_send_event,
+    )
+    .await
+}
+
+fn print_send_event(event: &SendEvent) -> Result<()> {
+    println!("{event}");
+    Ok(())
+}
+
+fn get_send_config(
+    commitment: CommitmentConfig,
+    transaction_config: TransactionConfig<'_>,
+) -> SendConfig {
+    SendConfig {
+        commitment,
+        rpc_send_config: transaction_config.send_config,
+        ..Default::default()
+    }
+}
+
+/// Same as `send_or_simulate_with_signers`, with `on_event` receiving the progress of the sending. The transaction
+/// is signed again with a fresh blockhash when it expires before landing.
+pub async fn send_or_simulate_with_events<'a, C: Deref<Target = impl Signer> + Clone>(
+    program: &'a Program<C>,
+    request_builder: RequestBuilder<'a, C>,
+    signers: &[&'a dyn Signer],
+    transaction_config: TransactionConfig<'_>,
+    on_event: impl FnMut(&SendEvent) -> Result<()>,
+) -> Result<Option<Signature>> {
+    let request_builder = match transaction_config.compute_budget {
+        Some(compute_budget) if !matches!(transaction_config.mode, TransactionMode::Simulate) => {
+            let instructions = estimate_compute_budget(
+                &program.async_rpc(),
+                &request_builder.instructions()?,
+                &program.payer(),
+                transaction_config
+                    .lookup_tables
+                    .map_or(&[], |lookup_tables| lookup_tables.accounts()),
+                &compute_budget,
+            )
+            .await?;
+            instructions
+                .into_iter()
+                .fold(program.request(), |bld, ix| bld.instruction(ix))
+        }
+        _ => request_builder,
+    };
+    let request_builder = signers
+        .iter()
+        .fold(request_builder, |bld, signer| bld.signer(*signer));
+
+    match transaction_config.mode {
+        TransactionMode::Send => {
+            let rpc_client = program.async_rpc();
+            let send_config = get_send_config(rpc_client.commitment(), transaction_config);
+            let signature = match transaction_config.lookup_tables {
+                Some(lookup_tables) => {
+                    let instructions = &request_builder.instructions()?;
+                    let rpc_client = &rpc_client;
+                    send_and_confirm(
+                        rpc_client,
+                        move || async move {
+                            let blockhash = rpc_client.get_latest_blockhash().await?;
+                            lookup_tables.sign(instructions, signers, blockhash)
+                        },
+                        &send_config,
+                        on_event,
+                    )
+                    .await?
+                }
+                None => {
+                    let request_builder = &request_builder;
+                    send_and_confirm(
+                        &rpc_client,
+                        move || async move { Ok(request_builder.signed_transaction().await?.into()) },
+                        &send_config,
+                        on_event,
+                    )
+                    .await?
+                }
+            };
+            Ok(Some(signature))
+        }
+        TransactionMode::Simulate => {
+            let instructions = request_builder.instructions()?;
+            let transaction = match transaction_config.lookup_tables {
+                Some(lookup_tables) => {
+                    let message = lookup_tables.compile(&instructions, Hash::default())?;
+                    VersionedTransaction {
+                        signatures: vec![
+                            Signature::default();
+                            usize::from(message.header().num_required_signatures)
+                        ],
+                        message,
+                    }
+                }
+                None => Transaction::new_with_payer(&instructions, Some(&program.payer())).into(),
+            };
+            simulate_transaction(program, &transaction, transaction_config.lookup_tables).await?;
+            Ok(None)
+        }
+        TransactionMode::Export(export) => {
+            export
+                .add(
+                    program,
+                    request_builder.instructions()?,
+                    signers,
+                    transaction_config.lookup_tables,
+                )
+                .await?;
+            Ok(None)
+        }
+    }
+}
+
+/// Same as `send_or_simulate` for a transaction which is already signed. The simulation of a v0 transaction needs
+/// the lookup tables it uses in `transaction_config` to report the balance changes.
+pub async fn send_or_simulate_transaction<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    transaction: &VersionedTransaction,
+    transaction_config: TransactionConfig<'_>,
+) -> Result<Option<Signature>> {
+    match transaction_config.mode {
+        TransactionMode::Send => {
+            // Signed elsewhere, so it can't be signed again when its blockhash expires
+            let rpc_client = program.async_rpc();
+            let send_config = SendConfig {
+                max_attempts: 1,
+                ..get_send_config(rpc_client.commitment(), transaction_config)
+            };
+            let signature = send_and_confirm(
+                &rpc_client,
+                move || async move { Ok(transaction.clone()) },
+                &send_config,
+                print_send_event,
+            )
+            .await?;
+            Ok(Some(signature))
+        }
+        TransactionMode::Simulate => {
+            simulate_transaction(program, transaction, transaction_config.lookup_tables).await?;
+            Ok(None)
+        }
+        TransactionMode::Export(_) => Err(anyhow!("Signed transactions cannot be exported")),
+    }
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SimulationResult {
+    err: Option<TransactionError>,
+    logs: Option<Vec<String>>,
+    accounts: Option<Vec<Option<UiAccount>>>,
+    units_consumed: Option<u64>,
+    /// Only returned by nodes supporting the `innerInstructions` simulation option
+    inner_instructions: Option<Vec<UiInnerInstructions>>,
+}
+
+async fn simulate_transaction<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    transaction: &VersionedTransaction,
+    lookup_tables: Option<&LookupTables>,
+) -> Result<()> {
+    // Fee payer of the transaction, which is not the wallet of a submitted transaction run without a keypair
+    let payer = transaction.message.static_account_keys()[0];
+    let account_keys = match lookup_tables {
+        Some(lookup_tables) => lookup_tables.get_account_keys(&transaction.message)?,
+        None => transaction.message.static_account_keys().to_vec(),
+    };
+    let writable_keys = account_keys
+        .iter()
+        .enumerate()
+        .filter(|(i, _)| transaction.message.is_maybe_writable(*i))
+        .map(|(_, key)| *key)
+        .collect::<Vec<_>>();
+
+    let rpc_client = program.async_rpc();
+    let pre_accounts = rpc_client.get_multiple_accounts(&account_keys).await?;
+
+    let reserves = pre_accounts
+        .iter()
+        .flatten()
+        .filter(|account| account.owner == lb_clmm::ID)
+        .filter_map(|account| LbPair::try_deserialize(&mut account.data.as_ref()).ok())
+        .flat_map(|lb_pair| [lb_pair.reserve_x, lb_pair.reserve_y])
+        .collect::<HashSet<_>>();
+
+    let params = json!([
+        STANDARD.encode(bincode::serialize(transaction)?),
+        {
+            "encoding": "base64",
+            "sigVerify": false,
+            "replaceRecentBlockhash": true,
+            "commitment": rpc_client.commitment().commitment,
+            "innerInstructions": true,
+            "accounts": {
+                "encoding": "base64",
+                "addresses": writable_keys.iter().map(Pubkey::to_string).collect::<Vec<_>>(),
+            },
+        },
+    ]);
+    let response: Response<SimulationResult> = rpc_client
+        .send(RpcRequest::SimulateTransaction, params)
+        .await?;
+    let simulation = response.value;
+
+    println!("Simulation at slot {}", response.context.slot);
+    match &simulation.err {
+        Some(err) => println!("Result: failed, {err}"),
+        None => println!("Result: success"),
+    }
+    println!(
+        "Compute units consumed: {}",
+        simulation
+            .units_consumed
+            .map(|units| units.to_string())
+            .unwrap_or_else(|| "N/A".to_string())
+    );
+
+    println!("\nLogs:");
+    for log in simulation.logs.iter().flatten() {
+        println!("  {log}");
+    }
+
+    match &simulation.inner_instructions {
+        Some(inner_instructions) => {
+            println!("\nEvents:");
+            for event in decode_events(inner_instructions, &account_keys) {
+                println!("{event}");
+            }
+        }
+        None => println!("\nEvents: not returned by the RPC node"),
+    }
+
+    let post_accounts = simulation
+        .accounts
+        .unwrap_or_default()
+        .into_iter()
+        .map(|account| account.and_then(|account| account.decode::<Account>()));
+
+    let mut rows = vec![];
+    for ((key, pre_account), post_account) in writable_keys
+        .iter()
+        .map(|key| {
+            let index = account_keys.iter().position(|k| k == key).unwrap();
+            (key, pre_accounts[index].as_ref())
+        })
+        .zip(post_accounts)
+    {
+        if *key == payer {
+            let before = pre_account.map_or(0, |account| account.lamports);
+            let after = post_account.as_ref().map_or(0, |account| account.lamports);
+            rows.push(balance_row(key, "SOL", "payer", before, after));
+            continue;
+        }
+
+        let pre_token = pre_account.and_then(decode_token_account);
+        let post_token = post_account.as_ref().and_then(decode_token_account);
+        let Some(token) = post_token.as_ref().or(pre_token.as_ref()) else {
+            continue;
+        };
+
+        let label = if reserves.contains(key) {
+            "reserve"
+        } else if token.owner == payer {
+            "payer"
+        } else {
+            continue;
+        };
+
+        rows.push(balance_row(
+            key,
+            &token.mint.to_string(),
+            label,
+            pre_token.map_or(0, |token| token.amount),
+            post_token.map_or(0, |token| token.amount),
+        ));
+    }
+
+    println!("\nBalance changes:");
+    print_table(
+        &["Account", "Mint", "Holder", "Before", "After", "Change"],
+        &rows,
+    );
+
+    if let Some(err) = simulation.err {
+        bail!("Transaction simulation failed: {err}");
+    }
+
+    Ok(())
+}
+
+fn decode_token_account(account: &Account) -> Option<TokenAccount> {
+    if account.owner != anchor_spl::token::ID {
+        return None;
+    }
+    TokenAccount::try_deserialize(&mut account.data.as_ref()).ok()
+}
+
+fn balance_row(key: &Pubkey, mint: &str, holder: &str, before: u64, after: u64) -> Vec<String> {
+    vec![
+        key.to_string(),
+        mint.to_string(),
+        holder.to_string(),
+        before.to_string(),
+        after.to_string(),
+        format!("{:+}", i128::from(after) - i128::from(before)),
+    ]
+}
+
+/// lb_clmm emits its events through a self CPI, so they only show up in the inner instructions.
+/// Returns the instruction data of these self CPIs.
+fn get_event_instruction_data(
+    inner_instructions: &[UiInnerInstructions],
+    account_keys: &[Pubkey],
+) -> Vec<Vec<u8>> {
+    inner_instructions
+        .iter()
+        .flat_map(|ix| ix.instructions.iter())
+        .filter_map(|ix| {
+            let (program_id, data) = match ix {
+                UiInstruction::Compiled(ix) => (
+                    *account_keys.get(usize::from(ix.program_id_index))?,
+                    ix.data.as_str(),
+                ),
+                UiInstruction::Parsed(UiParsedInstruction::PartiallyDecoded(ix)) => {
+                    (ix.program_id.parse().ok()?, ix.data.as_str())
+                }
+                UiInstruction::Parsed(UiParsedInstruction::Parsed(_)) => return None,
+            };
+            if program_id != lb_clmm::ID {
+                return None;
+            }
+            bs58::decode(data).into_vec().ok()
+        })
+        .collect()
+}
+
+fn decode_events(
+    inner_instructions: &[UiInnerInstructions],
+    account_keys: &[Pubkey],
+) -> Vec<String> {
+    get_event_instruction_data(inner_instructions, account_keys)
+        .iter()
+        .filter_map(|ix_data| decode_event(ix_data))
+        .collect()
+}
+
+/// Event instruction data of lb_clmm in a confirmed transaction. Decode them with `parse_event`.
+pub async fn get_transaction_events<C: Deref<Target = impl Signer> + Clone>(
+    program: &Program<C>,
+    signature: &Signature,
+) -> Result<Vec<Vec<u8>>> {
+    let rpc_client = program.async_rpc();
+    let transaction = rpc_client
+        .get_transaction_with_config(
+            signature,
+            RpcTransactionConfig {
+                encoding: Some(UiTransactionEncoding::Json),
+                commitment: Some(rpc_client.commitment()),
+                max_supported_transaction_version: Some(0),
+            },
+        )
+        .await?;
+
+    let account_keys = transaction
+        .transaction
+        .transaction
+        .decode()
+        .context("Failed to decode the transaction")?
+        .message
+        .static_account_keys()
+        .to_vec();
+    let inner_instructions: Option<Vec<UiInnerInstructions>> = transaction
+        .transaction
+        .meta
+        .context("Transaction status not found")?
+        .inner_instructions
+        .into();
+
+    Ok(get_event_instruction_data(
+        &inner_instructions.unwrap_or_default(),
+        &account_keys,
+    ))
+}
+
+/// Decode the event instruction data as `T`. None when it holds another event.
+pub fn parse_event<T: Discriminator + AnchorDeserialize>(ix_data: &[u8]) -> Option<T> {
+    if ix_data.len() < 16 || ix_data[..8] != EVENT_IX_TAG_LE[..] {
+        return None;
+    }
+    let (discriminator, event_data) = ix_data[8..].split_at(8);
+    if discriminator != T::discriminator().as_slice() {
+        return None;
+    }
+    T::try_from_slice(event_data).ok()
+}
+
+/// Text form of a lb_clmm event, printed by the simulation. The program events don't implement `Debug`, so their
+/// fields are listed here.
+trait EventFields {
+    const NAME: &'static str;
+
+    fn fields(&self) -> Vec<(&'static str, String)>;
+}
+
+macro_rules! event_fields {
+    ($($event:ident { $($field:ident),* $(,)? }),* $(,)?) => {
+        $(
+            impl EventFields for events::$event {
+                const NAME: &'static str = stringify!($event);
+
+                fn fields(&self) -> Vec<(&'static str, String)> {
+                    vec![$((stringify!($field), format!("{:?}", self.$field))),*]
+                }
+            }
+        )*
+
+        const EVENT_DECODERS: &[EventDecoder] = &[$(decode_event_as::<events::$event>),*];
+    };
+}
+
+type EventDecoder = fn(&[u8]) -> Option<String>;
+
+event_fields! {
+    CompositionFee {
+        from,
+        bin_id,
+        token_x_fee_amount,
+        token_y_fee_amount,
+        protocol_token_x_fee_amount,
+        protocol_token_y_fee_amount,
+    },
+    AddLiquidity { lb_pair, from, position, amounts, active_bin_id },
+    RemoveLiquidity { lb_pair, from, position, amounts, active_bin_id },
+    Swap {
+        lb_pair,
+        from,
+        start_bin_id,
+        end_bin_id,
+        amount_in,
+        amount_out,
+        swap_for_y,
+        fee,
+        protocol_fee,
+        fee_bps,
+        host_fee,
+    },
+    ClaimReward { lb_pair, position, owner, reward_index, total_reward },
+    FundReward { lb_pair, funder, reward_index, amount },
+    InitializeReward { lb_pair, reward_mint, funder, reward_index, reward_duration },
+    UpdateRewardDuration { lb_pair, reward_index, old_reward_duration, new_reward_duration },
+    UpdateRewardFunder { lb_pair, reward_index, old_funder, new_funder },
+    PositionClose { position, owner },
+    ClaimFee { lb_pair, position, owner, fee_x, fee_y },
+    LbPairCreate { lb_pair, bin_step, token_x, token_y },
+    PositionCreate { lb_pair, position, owner },
+    FeeParameterUpdate { lb_pair, protocol_share, base_factor },
+    IncreaseObservation { oracle, new_observation_length },
+    WithdrawIneligibleReward { lb_pair, reward_mint, amount },
+    UpdatePositionOperator { position, old_operator, new_operator },
+    UpdatePositionLockReleasePoint {
+        position,
+        current_point,
+        new_lock_release_point,
+        old_lock_release_point,
+        sender,
+    },
+}
+
+fn decode_event(ix_data: &[u8]) -> Option<String> {
+    EVENT_DECODERS.iter().find_map(|decode| decode(ix_data))
+}
+
+fn decode_event_as<T: Discriminator + AnchorDeserialize + EventFields>(
+    ix_data: &[u8],
+) -> Option<String> {
+    parse_event::<T>(ix_data).map(|event| format_event(T::NAME, &event.fields()))
+}
+
+fn format_event(name: &str, fields: &[(&str, String)]) -> String {
+    let mut text = format!("{} {{", name);
+    for (field, value) in fields {
+        text.push_str(&format!("\n    {}: {},", field, value));
+    }
+    text.push_str("\n}");
+    text
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::AnchorSerialize;
+
+    #[test]
+    fn test_decode_event() {
+        let event = events::IncreaseObservation {
+            oracle: Pubkey::new_unique(),
+            new_observation_length: 100,
+        };
+
+        let mut ix_data = EVENT_IX_TAG_LE.to_vec();
+        ix_data.extend_from_slice(&events::IncreaseObservation::discriminator());
+        ix_data.extend_from_slice(&event.try_to_vec().unwrap());
+
+        let decoded = decode_event(&ix_data).unwrap();
+        assert!(decoded.starts_with("IncreaseObservation"));
+        assert!(decoded.contains(&event.oracle.to_string()));
+
+        assert!(decode_event(&ix_data[8..]).is_none());
+
+        let parsed = parse_event::<events::IncreaseObservation>(&ix_data).unwrap();
+        assert_eq!(parsed.new_observation_length, 100);
+        assert!(parse_event::<events::ClaimFee>(&ix_data).is_none());
+    }
+}
@@ -0,0 +1,56 @@
+use std::str::FromStr;
+
+use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_client::solana_sdk::signature::{read_keypair_file, Keypair, Signature};
+use anchor_client::solana_sdk::signer::{Signer, SignerError};
+use anyhow::{anyhow, Result};
+
+/// Wallet given by `--provider.wallet`. Either a keypair file, or only the public key of a signer
+/// that lives elsewhere, such as a multisig member exporting transactions with `--export`.
+pub enum Wallet {
+    Keypair(Keypair),
+    Pubkey(Pubkey),
+}
+
+impl Wallet {
+    pub fn from_arg(wallet: &str) -> Result<Self> {
+        if let Ok(pubkey) = Pubkey::from_str(wallet) {
+            return Ok(Wallet::Pubkey(pubkey));
+        }
+
+        let keypair = read_keypair_file(wallet)
+            .map_err(|err| anyhow!("Wallet keypair file {} not found: {}", wallet, err))?;
+
+        Ok(Wallet::Keypair(keypair))
+    }
+
+    pub fn keypair(&self) -> Option<&Keypair> {
+        match self {
+            Wallet::Keypair(keypair) => Some(keypair),
+            Wallet::Pubkey(_) => None,
+        }
+    }
+}
+
+impl Signer for Wallet {
+    fn try_pubkey(&self) -> Result<Pubkey, SignerError> {
+        match self {
+            Wallet::Keypair(keypair) => keypair.try_pubkey(),
+            Wallet::Pubkey(pubkey) => Ok(*pubkey),
+        }
+    }
+
+    fn try_sign_message(&self, message: &[u8]) -> Result<Signature, SignerError> {
+        match self {
+            Wallet::Keypair(keypair) => keypair.try_sign_message(message),
+            Wallet::Pubkey(pubkey) => Err(SignerError::Custom(format!(
+                "Wallet {} is a public key and cannot sign. Use --export to sign the transaction elsewhere",
+                pubkey
+            ))),
+        }
+    }
+
+    fn is_interactive(&self) -> bool {
+        false
+    }
+}
@@ -0,0 +1,234 @@
+use crate::math::{
+    get_id_from_price, price_per_lamport_to_price_per_token, price_per_token_to_per_lamport,
+    q64x64_price_to_decimal,
+};
+use crate::position::deserialize_bin_array;
+use crate::quote::{quote_exact_in, quote_exact_out};
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anyhow::{Context, Result};
+use lb_clmm::math::u128x128_math::Rounding;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::bin_array_bitmap_extension::BinArrayBitmapExtension;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::utils::pda::derive_bin_array_pda;
+use rust_decimal::prelude::ToPrimitive;
+use std::collections::HashMap;
+use std::str::FromStr;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Total amount in, including fee
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+/// Decode raw bin array accounts. V0 bin arrays are migrated as the program does on access.
+pub fn decode_bin_arrays<T: AsRef<[u8]>>(bin_arrays_data: &[T]) -> Result<Vec<BinArray>> {
+    bin_arrays_data
+        .iter()
+        .map(|data| deserialize_bin_array(data.as_ref()))
+        .collect()
+}
+
+/// Quote a swap of exact `amount` in, or out when `exact_in` is false, from the raw pair accounts.
+/// `bin_arrays_data` are the bin arrays around the active bin.
+#[allow(clippy::too_many_arguments)]
+pub fn quote<T: AsRef<[u8]>>(
+    lb_pair_pubkey: &str,
+    lb_pair_data: &[u8],
+    bitmap_extension_data: Option<&[u8]>,
+    bin_arrays_data: &[T],
+    amount: u64,
+    swap_for_y: bool,
+    exact_in: bool,
+    current_timestamp: u64,
+    current_slot: u64,
+) -> Result<SwapQuote> {
+    let lb_pair_pubkey = Pubkey::from_str(lb_pair_pubkey)?;
+    let lb_pair = LbPair::try_deserialize(&mut &lb_pair_data[..])?;
+    let bitmap_extension = bitmap_extension_data
+        .map(|mut data| BinArrayBitmapExtension::try_deserialize(&mut data))
+        .transpose()?;
+
+    // Bin arrays are keyed by address, which can be derived from the account content
+    let bin_arrays = decode_bin_arrays(bin_arrays_data)?
+        .into_iter()
+        .map(|bin_array| {
+            let (key, _bump) = derive_bin_array_pda(bin_array.lb_pair, bin_array.index);
+            (key, bin_array)
+        })
+        .collect::<HashMap<_, _>>();
+
+    if exact_in {
+        let quote = quote_exact_in(
+            lb_pair_pubkey,
+            &lb_pair,
+            amount,
+            swap_for_y,
+            bin_arrays,
+            bitmap_extension.as_ref(),
+            current_timestamp,
+            current_slot,
+        )?;
+        Ok(SwapQuote {
+            amount_in: amount,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+        })
+    } else {
+        let quote = quote_exact_out(
+            lb_pair_pubkey,
+            &lb_pair,
+            amount,
+            swap_for_y,
+            bin_arrays,
+            bitmap_extension.as_ref(),
+            current_timestamp,
+            current_slot,
+        )?;
+        Ok(SwapQuote {
+            amount_in: quote
+                .amount_in
+                .checked_add(quote.fee)
+                .context("MathOverflow")?,
+            amount_out: amount,
+            fee: quote.fee,
+        })
+    }
+}
+
+/// UI price of token X in token Y of the bin.
+pub fn get_ui_price_from_id(
+    bin_id: i32,
+    bin_step: u16,
+    decimals_x: u8,
+    decimals_y: u8,
+) -> Result<f64> {
+    let q64x64_price = lb_clmm::math::price_math::get_price_from_id(bin_id, bin_step)?;
+    let price_per_lamport = q64x64_price_to_decimal(q64x64_price)
+        .and_then(|price| price.to_f64())
+        .context("Price overflow")?;
+
+    price_per_lamport_to_price_per_token(price_per_lamport, decimals_x, decimals_y)
+        .and_then(|price| price.to_f64())
+        .context("Price overflow")
+}
+
+/// Bin id of the UI price of token X in token Y. Price in between 2 bins are rounded by `round_down`.
+pub fn get_id_from_ui_price(
+    price: f64,
+    bin_step: u16,
+    decimals_x: u8,
+    decimals_y: u8,
+    round_down: bool,
+) -> Result<i32> {
+    let price_per_lamport =
+        price_per_token_to_per_lamport(price, decimals_x, decimals_y).context("Price overflow")?;
+    let rounding = if round_down {
+        Rounding::Down
+    } else {
+        Rounding::Up
+    };
+
+    get_id_from_price(bin_step, &price_per_lamport, rounding).context("Invalid price")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LB_PAIR: &str = "EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig";
+    const LB_PAIR_DATA: &[u8] = include_bytes!(
+        "../../programs/lb_clmm/tests/fixtures/EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig/lb_pair.bin"
+    );
+    const BIN_ARRAY_1_DATA: &[u8] = include_bytes!(
+        "../../programs/lb_clmm/tests/fixtures/EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig/bin_array_1.bin"
+    );
+    const BIN_ARRAY_2_DATA: &[u8] = include_bytes!(
+        "../../programs/lb_clmm/tests/fixtures/EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig/bin_array_2.bin"
+    );
+
+    #[test]
+    fn test_quote_matches_quote_engine() {
+        let bin_arrays_data = [BIN_ARRAY_1_DATA, BIN_ARRAY_2_DATA];
+        let lb_pair_pubkey = Pubkey::from_str(LB_PAIR).unwrap();
+        let lb_pair = LbPair::try_deserialize(&mut &LB_PAIR_DATA[..]).unwrap();
+        let timestamp = lb_pair.v_parameters.last_update_timestamp as u64 + 1;
+
+        let exact_in = quote(
+            LB_PAIR,
+            LB_PAIR_DATA,
+            None,
+            &bin_arrays_data,
+            100_000,
+            false,
+            true,
+            timestamp,
+            0,
+        )
+        .unwrap();
+
+        let bin_arrays = decode_bin_arrays(&bin_arrays_data)
+            .unwrap()
+            .into_iter()
+            .map(|bin_array| {
+                let (key, _bump) = derive_bin_array_pda(lb_pair_pubkey, bin_array.index);
+                (key, bin_array)
+            })
+            .collect::<HashMap<_, _>>();
+        let expected = quote_exact_in(
+            lb_pair_pubkey,
+            &lb_pair,
+            100_000,
+            false,
+            bin_arrays,
+            None,
+            timestamp,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(exact_in.amount_in, 100_000);
+        assert_eq!(exact_in.amount_out, expected.amount_out);
+        assert_eq!(exact_in.fee, expected.fee);
+
+        let exact_out = quote(
+            LB_PAIR,
+            LB_PAIR_DATA,
+            None,
+            &bin_arrays_data,
+            exact_in.amount_out,
+            false,
+            false,
+            timestamp,
+            0,
+        )
+        .unwrap();
+
+        assert_eq!(exact_out.amount_out, exact_in.amount_out);
+        assert!(exact_out.amount_in <= exact_in.amount_in);
+    }
+
+    #[test]
+    fn test_price_and_bin_id_round_trip() {
+        let bin_step = 10;
+        let price = get_ui_price_from_id(100, bin_step, 9, 6).unwrap();
+        // 1.001 ^ 100 * 10 ^ 9 / 10 ^ 6
+        assert!((price - 1105.115697720767).abs() < 1e-6);
+
+        assert_eq!(
+            get_id_from_ui_price(price, bin_step, 9, 6, true).unwrap(),
+            100
+        );
+        assert_eq!(
+            get_id_from_ui_price(price * 1.0005, bin_step, 9, 6, true).unwrap(),
+            100
+        );
+        assert_eq!(
+            get_id_from_ui_price(price * 1.0005, bin_step, 9, 6, false).unwrap(),
+            101
+        );
+    }
+}
@@ -0,0 +1,323 @@
+use crate::rpc::{block_on, Rpc};
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_client::RpcClient as BlockingRpcClient;
+use anchor_client::solana_client::rpc_config::RpcSimulateTransactionConfig;
+use anchor_client::solana_client::rpc_response::RpcSimulateTransactionResult;
+use anchor_client::solana_sdk::address_lookup_table_account::AddressLookupTableAccount;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::compute_budget::{self, ComputeBudgetInstruction};
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::instruction::Instruction;
+use anchor_client::solana_sdk::message::{v0, VersionedMessage};
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::transaction::{Transaction, VersionedTransaction};
+use anchor_lang::prelude::Pubkey;
+use anyhow::{bail, Result};
+use std::collections::HashSet;
+
+/// Compute unit limit of a transaction.
+pub const MAX_COMPUTE_UNIT_LIMIT: u32 = 1_400_000;
+/// Margin added to the simulated compute units by default, in bps.
+pub const DEFAULT_COMPUTE_UNIT_MARGIN_BPS: u16 = 1_000;
+/// Accounts accepted by `getRecentPrioritizationFees`.
+const MAX_PRIORITIZATION_FEE_ACCOUNTS: usize = 128;
+const MICRO_LAMPORTS_PER_LAMPORT: u128 = 1_000_000;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PriorityFee {
+    /// Compute unit price in micro lamports
+    Fixed(u64),
+    /// Percentile of the recent prioritization fees paid to write the accounts of the transaction
+    Percentile(u8),
+}
+
+/// Compute budget of transactions sized by simulation instead of hard-coded limits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ComputeBudgetConfig {
+    /// Margin added to the compute units consumed by the simulation, in bps
+    pub compute_unit_margin_bps: u16,
+    pub priority_fee: PriorityFee,
+    /// Max priority fee paid by a transaction, in lamports. The compute unit price is lowered to fit it.
+    pub max_priority_fee_lamports: Option<u64>,
+}
+
+impl Default for ComputeBudgetConfig {
+    fn default() -> Self {
+        Self {
+            compute_unit_margin_bps: DEFAULT_COMPUTE_UNIT_MARGIN_BPS,
+            priority_fee: PriorityFee::Fixed(0),
+            max_priority_fee_lamports: None,
+        }
+    }
+}
+
+pub fn is_compute_budget_instruction(instruction: &Instruction) -> bool {
+    instruction.program_id == compute_budget::ID
+}
+
+/// Compute unit limit covering `units_consumed` with a margin of `margin_bps`.
+pub fn get_compute_unit_limit(units_consumed: u64, margin_bps: u16) -> u32 {
+    let limit = u128::from(units_consumed) * (10_000 + u128::from(margin_bps)) / 10_000;
+    limit.min(u128::from(MAX_COMPUTE_UNIT_LIMIT)) as u32
+}
+
+/// Fee at `percentile` of `fees`, by the nearest rank method. 0 without fees.
+pub fn get_fee_percentile(mut fees: Vec<u64>, percentile: u8) -> u64 {
+    if fees.is_empty() {
+        return 0;
+    }
+    fees.sort_unstable();
+    let percentile = usize::from(percentile.min(100));
+    let rank = ((percentile * fees.len() + 99) / 100).max(1);
+    fees[rank - 1]
+}
+
+/// Compute unit price, lowered so a transaction of `compute_unit_limit` pays at most `max_priority_fee_lamports`.
+pub fn get_compute_unit_price(
+    compute_unit_price: u64,
+    compute_unit_limit: u32,
+    max_priority_fee_lamports: Option<u64>,
+) -> u64 {
+    match max_priority_fee_lamports {
+        Some(max_fee) if compute_unit_limit > 0 => {
+            let max_price =
+                u128::from(max_fee) * MICRO_LAMPORTS_PER_LAMPORT / u128::from(compute_unit_limit);
+            compute_unit_price.min(max_price.min(u128::from(u64::MAX)) as u64)
+        }
+        _ => compute_unit_price,
+    }
+}
+
+/// Writable accounts of `instructions` besides the signers. Their recent prioritization fees price the transaction.
+pub fn get_writable_accounts(instructions: &[Instruction]) -> Vec<Pubkey> {
+    let mut seen = HashSet::new();
+    instructions
+        .iter()
+        .flat_map(|ix| ix.accounts.iter())
+        .filter(|meta| meta.is_writable && !meta.is_signer)
+        .map(|meta| meta.pubkey)
+        .filter(|key| seen.insert(*key))
+        .take(MAX_PRIORITIZATION_FEE_ACCOUNTS)
+        .collect()
+}
+
+/// Unsigned transaction simulating `instructions` at the max compute unit limit. It's compiled as a v0 transaction
+/// against `lookup_tables` when given, so it stays within the size of the transaction being estimated.
+fn get_simulation_transaction(
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+) -> Result<VersionedTransaction> {
+    let mut simulated_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        MAX_COMPUTE_UNIT_LIMIT,
+    )];
+    simulated_instructions.extend(instructions.iter().cloned());
+
+    if lookup_tables.is_empty() {
+        return Ok(Transaction::new_with_payer(&simulated_instructions, Some(payer)).into());
+    }
+
+    let message = VersionedMessage::V0(v0::Message::try_compile(
+        payer,
+        &simulated_instructions,
+        lookup_tables,
+        Hash::default(),
+    )?);
+    Ok(VersionedTransaction {
+        signatures: vec![
+            Signature::default();
+            usize::from(message.header().num_required_signatures)
+        ],
+        message,
+    })
+}
+
+fn get_simulation_config(commitment: CommitmentConfig) -> RpcSimulateTransactionConfig {
+    RpcSimulateTransactionConfig {
+        sig_verify: false,
+        replace_recent_blockhash: true,
+        commitment: Some(commitment),
+        ..Default::default()
+    }
+}
+
+fn get_simulated_compute_unit_limit(
+    simulation: RpcSimulateTransactionResult,
+    config: &ComputeBudgetConfig,
+) -> Result<u32> {
+    if let Some(err) = simulation.err {
+        bail!(
+            "Transaction simulation failed: {err}\n{}",
+            simulation.logs.unwrap_or_default().join("\n")
+        );
+    }
+    Ok(simulation
+        .units_consumed
+        .map_or(MAX_COMPUTE_UNIT_LIMIT, |units| {
+            get_compute_unit_limit(units, config.compute_unit_margin_bps)
+        }))
+}
+
+/// Prepend the compute budget instructions to `instructions`.
+fn with_compute_budget(
+    instructions: Vec<Instruction>,
+    compute_unit_limit: u32,
+    recent_fees: Vec<u64>,
+    config: &ComputeBudgetConfig,
+) -> Vec<Instruction> {
+    let compute_unit_price = match config.priority_fee {
+        PriorityFee::Fixed(compute_unit_price) => compute_unit_price,
+        PriorityFee::Percentile(percentile) => get_fee_percentile(recent_fees, percentile),
+    };
+    let compute_unit_price = get_compute_unit_price(
+        compute_unit_price,
+        compute_unit_limit,
+        config.max_priority_fee_lamports,
+    );
+
+    let mut budgeted_instructions = vec![ComputeBudgetInstruction::set_compute_unit_limit(
+        compute_unit_limit,
+    )];
+    if compute_unit_price > 0 {
+        budgeted_instructions.push(ComputeBudgetInstruction::set_compute_unit_price(
+            compute_unit_price,
+        ));
+    }
+    budgeted_instructions.extend(instructions);
+    budgeted_instructions
+}
+
+fn without_compute_budget(instructions: &[Instruction]) -> Vec<Instruction> {
+    instructions
+        .iter()
+        .filter(|ix| !is_compute_budget_instruction(ix))
+        .cloned()
+        .collect()
+}
+
+/// Replace the compute budget instructions of `instructions` by a limit sized by simulating them, and a price set by
+/// `config.priority_fee`. Fails when the simulation fails. `lookup_tables` are the address lookup tables the
+/// transaction is compiled against, if any.
+pub async fn estimate_compute_budget(
+    rpc_client: &RpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    config: &ComputeBudgetConfig,
+) -> Result<Vec<Instruction>> {
+    estimate_compute_budget_with(
+        Rpc::Nonblocking(rpc_client),
+        instructions,
+        payer,
+        lookup_tables,
+        config,
+    )
+    .await
+}
+
+/// Same as `estimate_compute_budget` with a blocking RPC client.
+pub fn estimate_compute_budget_blocking(
+    rpc_client: &BlockingRpcClient,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    config: &ComputeBudgetConfig,
+) -> Result<Vec<Instruction>> {
+    block_on(estimate_compute_budget_with(
+        Rpc::Blocking(rpc_client),
+        instructions,
+        payer,
+        lookup_tables,
+        config,
+    ))
+}
+
+async fn estimate_compute_budget_with(
+    rpc: Rpc<'_>,
+    instructions: &[Instruction],
+    payer: &Pubkey,
+    lookup_tables: &[AddressLookupTableAccount],
+    config: &ComputeBudgetConfig,
+) -> Result<Vec<Instruction>> {
+    let instructions = without_compute_budget(instructions);
+
+    let simulation = rpc
+        .simulate_transaction_with_config(
+            &get_simulation_transaction(&instructions, payer, lookup_tables)?,
+            get_simulation_config(rpc.commitment()),
+        )
+        .await?;
+    let compute_unit_limit = get_simulated_compute_unit_limit(simulation, config)?;
+
+    let recent_fees = match config.priority_fee {
+        PriorityFee::Fixed(_) => vec![],
+        PriorityFee::Percentile(_) => rpc
+            .get_recent_prioritization_fees(&get_writable_accounts(&instructions))
+            .await?
+            .into_iter()
+            .map(|fee| fee.prioritization_fee)
+            .collect(),
+    };
+
+    Ok(with_compute_budget(
+        instructions,
+        compute_unit_limit,
+        recent_fees,
+        config,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::AccountMeta;
+
+    #[test]
+    fn test_get_compute_unit_limit() {
+        assert_eq!(get_compute_unit_limit(100_000, 1_000), 110_000);
+        assert_eq!(get_compute_unit_limit(100_000, 0), 100_000);
+        assert_eq!(
+            get_compute_unit_limit(1_300_000, 1_000),
+            MAX_COMPUTE_UNIT_LIMIT
+        );
+    }
+
+    #[test]
+    fn test_get_fee_percentile() {
+        let fees = (1..=10).rev().collect::<Vec<u64>>();
+        assert_eq!(get_fee_percentile(fees.clone(), 50), 5);
+        assert_eq!(get_fee_percentile(fees.clone(), 75), 8);
+        assert_eq!(get_fee_percentile(fees.clone(), 100), 10);
+        assert_eq!(get_fee_percentile(fees, 0), 1);
+        assert_eq!(get_fee_percentile(vec![], 50), 0);
+    }
+
+    #[test]
+    fn test_get_compute_unit_price() {
+        // 200k CU at 10k micro lamports is 2000 lamports
+        assert_eq!(get_compute_unit_price(10_000, 200_000, None), 10_000);
+        assert_eq!(get_compute_unit_price(10_000, 200_000, Some(2_000)), 10_000);
+        assert_eq!(get_compute_unit_price(10_000, 200_000, Some(1_000)), 5_000);
+    }
+
+    #[test]
+    fn test_get_writable_accounts() {
+        let payer = Pubkey::new_unique();
+        let lb_pair = Pubkey::new_unique();
+        let oracle = Pubkey::new_unique();
+        let instructions = [
+            ComputeBudgetInstruction::set_compute_unit_limit(MAX_COMPUTE_UNIT_LIMIT),
+            Instruction::new_with_bytes(
+                Pubkey::new_unique(),
+                &[],
+                vec![
+                    AccountMeta::new(payer, true),
+                    AccountMeta::new(lb_pair, false),
+                    AccountMeta::new_readonly(oracle, false),
+                    AccountMeta::new(lb_pair, false),
+                ],
+            ),
+        ];
+        assert_eq!(get_writable_accounts(&instructions), vec![lb_pair]);
+    }
+}
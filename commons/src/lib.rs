@@ -1 +1,16 @@
+#[cfg(feature = "bindings")]
+pub mod bindings;
+#[cfg(feature = "client")]
+pub mod compute_budget;
+pub mod lookup_table;
+pub mod math;
+#[cfg(feature = "subscription")]
+pub mod pair_cache;
+pub mod pair_discovery;
+pub mod position;
 pub mod quote;
+pub mod rewards;
+#[cfg(feature = "client")]
+mod rpc;
+#[cfg(feature = "client")]
+pub mod sender;
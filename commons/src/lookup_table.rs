@@ -0,0 +1,91 @@
+use anchor_lang::prelude::Pubkey;
+use anyhow::Result;
+use lb_clmm::{
+    state::{bin::BinArray, lb_pair::LbPair},
+    utils::pda::{
+        derive_bin_array_bitmap_extension, derive_bin_array_pda, derive_event_authority_pda,
+    },
+};
+use std::collections::HashSet;
+
+/// Bin arrays on each side of the active bin array put in a pair lookup table by default
+pub const DEFAULT_LOOKUP_TABLE_BIN_ARRAY_RADIUS: i64 = 5;
+
+/// Accounts shared by the instructions of a pair, to put in its address lookup table: the pair, reserves, mints,
+/// oracle, event authority, bitmap extension and the bin arrays within `bin_array_radius` of the active bin array.
+/// The bitmap extension and the bin arrays are included whether they are initialized or not.
+pub fn get_pair_lookup_table_addresses(
+    lb_pair_pubkey: Pubkey,
+    lb_pair: &LbPair,
+    bin_array_radius: i64,
+) -> Result<Vec<Pubkey>> {
+    let (event_authority, _bump) = derive_event_authority_pda();
+    let (bitmap_extension, _bump) = derive_bin_array_bitmap_extension(lb_pair_pubkey);
+
+    let mut addresses = vec![
+        lb_pair_pubkey,
+        lb_pair.reserve_x,
+        lb_pair.reserve_y,
+        lb_pair.token_x_mint,
+        lb_pair.token_y_mint,
+        lb_pair.oracle,
+        event_authority,
+        bitmap_extension,
+    ];
+
+    let active_bin_array_idx = i64::from(BinArray::bin_id_to_bin_array_index(lb_pair.active_id)?);
+    for bin_array_idx in
+        active_bin_array_idx - bin_array_radius..=active_bin_array_idx + bin_array_radius
+    {
+        let (bin_array, _bump) = derive_bin_array_pda(lb_pair_pubkey, bin_array_idx);
+        addresses.push(bin_array);
+    }
+
+    Ok(addresses)
+}
+
+/// Addresses missing from a lookup table holding `table_addresses`, in order and without duplicates
+pub fn get_missing_addresses(table_addresses: &[Pubkey], addresses: &[Pubkey]) -> Vec<Pubkey> {
+    let mut seen = table_addresses.iter().collect::<HashSet<_>>();
+    addresses
+        .iter()
+        .filter(|address| seen.insert(*address))
+        .copied()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_get_pair_lookup_table_addresses() {
+        let lb_pair_pubkey = Pubkey::new_unique();
+        let lb_pair = LbPair {
+            active_id: 0,
+            reserve_x: Pubkey::new_unique(),
+            reserve_y: Pubkey::new_unique(),
+            token_x_mint: Pubkey::new_unique(),
+            token_y_mint: Pubkey::new_unique(),
+            oracle: Pubkey::new_unique(),
+            ..Default::default()
+        };
+
+        let addresses = get_pair_lookup_table_addresses(lb_pair_pubkey, &lb_pair, 2).unwrap();
+        assert_eq!(addresses.len(), 8 + 5);
+        assert_eq!(addresses[0], lb_pair_pubkey);
+        assert_eq!(addresses[8], derive_bin_array_pda(lb_pair_pubkey, -2).0);
+        assert_eq!(addresses[12], derive_bin_array_pda(lb_pair_pubkey, 2).0);
+    }
+
+    #[test]
+    fn test_get_missing_addresses() {
+        let [a, b, c] = [
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+            Pubkey::new_unique(),
+        ];
+        assert_eq!(get_missing_addresses(&[a], &[a, b, c, b]), vec![b, c]);
+        assert!(get_missing_addresses(&[a, b], &[b, a]).is_empty());
+    }
+}
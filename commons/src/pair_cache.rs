@@ -0,0 +1,502 @@
+use crate::quote::get_bin_array_pubkeys_for_swap;
+use anchor_client::solana_account_decoder::UiAccountEncoding;
+use anchor_client::solana_client::nonblocking::pubsub_client::PubsubClient;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_config::RpcAccountInfoConfig;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_lang::prelude::Pubkey;
+use anchor_lang::AccountDeserialize;
+use anyhow::{anyhow, Context, Result};
+use futures::future::try_join_all;
+use futures::StreamExt;
+use lb_clmm::state::{
+    bin::BinArray, bin_array_bitmap_extension::BinArrayBitmapExtension, lb_pair::LbPair,
+};
+use lb_clmm::utils::pda::derive_bin_array_bitmap_extension;
+use std::collections::{HashMap, HashSet};
+use std::sync::{Arc, RwLock};
+use tokio::sync::{mpsc, oneshot};
+use tokio::task::JoinHandle;
+
+/// State of a pair, as of the latest notification of each of its accounts. The accounts are updated independently as
+/// their notifications arrive, so they can be of different slots: the `LbPair` of a slot may come with bin arrays of
+/// an earlier slot while their notifications are in flight.
+#[derive(Debug, Clone, Default)]
+pub struct PairSnapshot {
+    /// Highest slot among the account updates applied to the snapshot. Some accounts may be older.
+    pub slot: u64,
+    pub lb_pair: LbPair,
+    pub bitmap_extension: Option<BinArrayBitmapExtension>,
+    /// Initialized bin arrays within `bin_array_radius` of the active bin array, keyed by address
+    pub bin_arrays: HashMap<Pubkey, BinArray>,
+}
+
+#[derive(Default)]
+struct CacheState {
+    snapshot: PairSnapshot,
+    /// Slot of the last update applied per account, to drop notifications arriving out of order
+    account_slots: HashMap<Pubkey, u64>,
+    error: Option<String>,
+}
+
+impl CacheState {
+    /// Returns whether the update is newer than the one already applied for the account.
+    fn apply(&mut self, lb_pair_pubkey: Pubkey, update: &AccountUpdate) -> Result<bool> {
+        if self
+            .account_slots
+            .get(&update.pubkey)
+            .map_or(false, |slot| *slot > update.slot)
+        {
+            return Ok(false);
+        }
+
+        let snapshot = &mut self.snapshot;
+        if update.pubkey == lb_pair_pubkey {
+            snapshot.lb_pair = LbPair::try_deserialize(&mut update.data.as_ref())?;
+        } else if update.pubkey == derive_bin_array_bitmap_extension(lb_pair_pubkey).0 {
+            snapshot.bitmap_extension = Some(BinArrayBitmapExtension::try_deserialize(
+                &mut update.data.as_ref(),
+            )?);
+        } else {
+            let bin_array = BinArray::try_deserialize(&mut update.data.as_ref())?;
+            snapshot.bin_arrays.insert(update.pubkey, bin_array);
+        }
+
+        self.account_slots.insert(update.pubkey, update.slot);
+        snapshot.slot = snapshot.slot.max(update.slot);
+
+        Ok(true)
+    }
+
+    fn remove_bin_array(&mut self, bin_array_pubkey: &Pubkey) {
+        self.snapshot.bin_arrays.remove(bin_array_pubkey);
+        self.account_slots.remove(bin_array_pubkey);
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AccountUpdate {
+    pubkey: Pubkey,
+    slot: u64,
+    data: Vec<u8>,
+}
+
+struct AccountSubscription {
+    stop: oneshot::Sender<()>,
+    handle: JoinHandle<()>,
+}
+
+impl AccountSubscription {
+    /// Subscribe to the account and forward its updates to `sender` until stopped. Returns once the subscription is
+    /// established, so an update made after it returns is notified.
+    async fn start(
+        pubsub_client: Arc<PubsubClient>,
+        pubkey: Pubkey,
+        commitment: CommitmentConfig,
+        sender: mpsc::UnboundedSender<Result<AccountUpdate>>,
+    ) -> Result<Self> {
+        let (stop, mut stop_receiver) = oneshot::channel();
+        let (ready, ready_receiver) = oneshot::channel();
+
+        let handle = tokio::spawn(async move {
+            let config = RpcAccountInfoConfig {
+                encoding: Some(UiAccountEncoding::Base64),
+                commitment: Some(commitment),
+                ..Default::default()
+            };
+
+            let (mut stream, unsubscribe) =
+                match pubsub_client.account_subscribe(&pubkey, Some(config)).await {
+                    Ok(subscription) => {
+                        let _ = ready.send(Ok(()));
+                        subscription
+                    }
+                    Err(err) => {
+                        let _ = ready.send(Err(anyhow!("Subscribe {} failed: {}", pubkey, err)));
+                        return;
+                    }
+                };
+
+            loop {
+                tokio::select! {
+                    _ = &mut stop_receiver => break,
+                    response = stream.next() => {
+                        let Some(response) = response else {
+                            let _ = sender.send(Err(anyhow!("Subscription of {} closed", pubkey)));
+                            break;
+                        };
+                        let Some(data) = response.value.data.decode() else {
+                            continue;
+                        };
+                        let update = AccountUpdate {
+                            pubkey,
+                            slot: response.context.slot,
+                            data,
+                        };
+                        if sender.send(Ok(update)).is_err() {
+                            break;
+                        }
+                    }
+                }
+            }
+
+            drop(stream);
+            unsubscribe().await;
+        });
+
+        ready_receiver
+            .await
+            .map_err(|_| anyhow!("Subscription of {} stopped", pubkey))??;
+
+        Ok(Self { stop, handle })
+    }
+
+    async fn stop(self) {
+        let _ = self.stop.send(());
+        let _ = self.handle.await;
+    }
+}
+
+/// Bin array addresses within `bin_array_radius` initialized bin arrays to the left and to the right of the active bin.
+fn get_bin_array_pubkeys_in_range(
+    lb_pair_pubkey: Pubkey,
+    snapshot: &PairSnapshot,
+    bin_array_radius: u8,
+) -> Result<HashSet<Pubkey>> {
+    let mut bin_array_pubkeys = HashSet::new();
+
+    for swap_for_y in [true, false] {
+        bin_array_pubkeys.extend(get_bin_array_pubkeys_for_swap(
+            lb_pair_pubkey,
+            &snapshot.lb_pair,
+            snapshot.bitmap_extension.as_ref(),
+            swap_for_y,
+            bin_array_radius,
+        )?);
+    }
+
+    Ok(bin_array_pubkeys)
+}
+
+struct PairCacheDriver {
+    lb_pair: Pubkey,
+    bin_array_radius: u8,
+    commitment: CommitmentConfig,
+    rpc_client: RpcClient,
+    pubsub_client: Arc<PubsubClient>,
+    state: Arc<RwLock<CacheState>>,
+    sender: mpsc::UnboundedSender<Result<AccountUpdate>>,
+    bin_array_subscriptions: HashMap<Pubkey, AccountSubscription>,
+}
+
+impl PairCacheDriver {
+    /// Fetch the accounts once through RPC, so newly tracked accounts are in the snapshot before their first notification.
+    async fn fetch(&self, pubkeys: &[Pubkey]) -> Result<()> {
+        let response = self
+            .rpc_client
+            .get_multiple_accounts_with_commitment(pubkeys, self.commitment)
+            .await?;
+        let slot = response.context.slot;
+
+        let mut state = self.state.write().map_err(|_| anyhow!("Poisoned lock"))?;
+        for (pubkey, account) in pubkeys.iter().zip(response.value) {
+            let Some(account) = account else {
+                continue;
+            };
+            state.apply(
+                self.lb_pair,
+                &AccountUpdate {
+                    pubkey: *pubkey,
+                    slot,
+                    data: account.data,
+                },
+            )?;
+        }
+
+        Ok(())
+    }
+
+    /// Subscribe to the bin arrays which came into range of the active bin, and drop the ones out of range.
+    async fn sync_bin_arrays(&mut self) -> Result<()> {
+        let wanted = {
+            let state = self.state.read().map_err(|_| anyhow!("Poisoned lock"))?;
+            get_bin_array_pubkeys_in_range(self.lb_pair, &state.snapshot, self.bin_array_radius)?
+        };
+
+        let removed = self
+            .bin_array_subscriptions
+            .keys()
+            .filter(|pubkey| !wanted.contains(pubkey))
+            .copied()
+            .collect::<Vec<_>>();
+
+        for pubkey in removed {
+            if let Some(subscription) = self.bin_array_subscriptions.remove(&pubkey) {
+                subscription.stop().await;
+            }
+            let mut state = self.state.write().map_err(|_| anyhow!("Poisoned lock"))?;
+            state.remove_bin_array(&pubkey);
+        }
+
+        let added = wanted
+            .into_iter()
+            .filter(|pubkey| !self.bin_array_subscriptions.contains_key(pubkey))
+            .collect::<Vec<_>>();
+
+        // Subscribe before fetching, so no update between the fetch and the subscription is missed
+        let subscriptions = try_join_all(added.iter().map(|pubkey| {
+            AccountSubscription::start(
+                self.pubsub_client.clone(),
+                *pubkey,
+                self.commitment,
+                self.sender.clone(),
+            )
+        }))
+        .await?;
+        self.bin_array_subscriptions
+            .extend(added.iter().copied().zip(subscriptions));
+
+        if !added.is_empty() {
+            self.fetch(&added).await?;
+        }
+
+        Ok(())
+    }
+
+    async fn run(
+        mut self,
+        mut receiver: mpsc::UnboundedReceiver<Result<AccountUpdate>>,
+        mut stop_receiver: oneshot::Receiver<()>,
+    ) {
+        let result = async {
+            self.sync_bin_arrays().await?;
+
+            let bitmap_extension = derive_bin_array_bitmap_extension(self.lb_pair).0;
+            loop {
+                let update = tokio::select! {
+                    _ = &mut stop_receiver => break,
+                    update = receiver.recv() => match update {
+                        Some(update) => update?,
+                        None => break,
+                    },
+                };
+                let applied = {
+                    let mut state = self.state.write().map_err(|_| anyhow!("Poisoned lock"))?;
+                    state.apply(self.lb_pair, &update)?
+                };
+
+                // Active bin or bitmap changed. The set of bin arrays around the active bin might have changed.
+                if applied && (update.pubkey == self.lb_pair || update.pubkey == bitmap_extension) {
+                    self.sync_bin_arrays().await?;
+                }
+            }
+
+            Ok::<_, anyhow::Error>(())
+        }
+        .await;
+
+        for (_, subscription) in self.bin_array_subscriptions.drain() {
+            subscription.stop().await;
+        }
+
+        if let Err(err) = result {
+            if let Ok(mut state) = self.state.write() {
+                state.error = Some(format!("{:#}", err));
+            }
+        }
+    }
+}
+
+/// Cache of a pair, its bitmap extension and the bin arrays around the active bin, kept up to date by websocket
+/// account subscriptions.
+pub struct PairCache {
+    lb_pair: Pubkey,
+    state: Arc<RwLock<CacheState>>,
+    subscriptions: Vec<AccountSubscription>,
+    stop_driver: oneshot::Sender<()>,
+    driver: JoinHandle<()>,
+}
+
+impl PairCache {
+    /// Load the pair through `rpc_url` and subscribe to its accounts through `ws_url`.
+    /// `bin_array_radius` is the number of initialized bin arrays tracked on each side of the active bin.
+    pub async fn subscribe(
+        rpc_url: String,
+        ws_url: String,
+        lb_pair: Pubkey,
+        bin_array_radius: u8,
+        commitment: CommitmentConfig,
+    ) -> Result<Self> {
+        let pubsub_client = Arc::new(
+            PubsubClient::new(&ws_url)
+                .await
+                .with_context(|| format!("Failed to connect to {}", ws_url))?,
+        );
+        let rpc_client = RpcClient::new_with_commitment(rpc_url, commitment);
+        let state = Arc::new(RwLock::new(CacheState::default()));
+        let (sender, receiver) = mpsc::unbounded_channel();
+
+        let bitmap_extension = derive_bin_array_bitmap_extension(lb_pair).0;
+        // Subscribe before fetching, so no update between the fetch and the subscription is missed
+        let subscriptions = try_join_all([lb_pair, bitmap_extension].into_iter().map(|pubkey| {
+            AccountSubscription::start(pubsub_client.clone(), pubkey, commitment, sender.clone())
+        }))
+        .await?;
+
+        let driver = PairCacheDriver {
+            lb_pair,
+            bin_array_radius,
+            commitment,
+            rpc_client,
+            pubsub_client,
+            state: state.clone(),
+            sender,
+            bin_array_subscriptions: HashMap::new(),
+        };
+
+        driver.fetch(&[lb_pair, bitmap_extension]).await?;
+        {
+            let state = state.read().map_err(|_| anyhow!("Poisoned lock"))?;
+            if !state.account_slots.contains_key(&lb_pair) {
+                return Err(anyhow!("Pair {} not found", lb_pair));
+            }
+        }
+
+        let (stop_driver, stop_receiver) = oneshot::channel();
+        let driver = tokio::spawn(driver.run(receiver, stop_receiver));
+
+        Ok(Self {
+            lb_pair,
+            state,
+            subscriptions,
+            stop_driver,
+            driver,
+        })
+    }
+
+    pub fn lb_pair(&self) -> Pubkey {
+        self.lb_pair
+    }
+
+    /// Latest state of the pair, see [`PairSnapshot`]. Fails once a subscription is broken, rather than returning stale
+    /// state.
+    pub fn snapshot(&self) -> Result<PairSnapshot> {
+        let state = self.state.read().map_err(|_| anyhow!("Poisoned lock"))?;
+        if let Some(err) = state.error.as_ref() {
+            return Err(anyhow!("Pair cache of {} stopped: {}", self.lb_pair, err));
+        }
+
+        Ok(state.snapshot.clone())
+    }
+
+    /// Unsubscribe from all accounts of the pair.
+    pub async fn close(self) {
+        // The driver stops its bin array subscriptions on exit
+        let _ = self.stop_driver.send(());
+        let _ = self.driver.await;
+
+        for subscription in self.subscriptions {
+            subscription.stop().await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lb_clmm::constants::MAX_BIN_PER_ARRAY;
+    use lb_clmm::state::bin::Bin;
+    use std::str::FromStr;
+
+    fn account_data<T: anchor_lang::Discriminator + anchor_lang::ZeroCopy>(account: &T) -> Vec<u8> {
+        let mut data = T::discriminator().to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(account));
+        data
+    }
+
+    #[test]
+    fn test_cache_state_drops_stale_updates() {
+        let lb_pair_pubkey = Pubkey::new_unique();
+        let mut state = CacheState::default();
+
+        let lb_pair = LbPair {
+            active_id: 10,
+            ..Default::default()
+        };
+        let update = AccountUpdate {
+            pubkey: lb_pair_pubkey,
+            slot: 5,
+            data: account_data(&lb_pair),
+        };
+        assert!(state.apply(lb_pair_pubkey, &update).unwrap());
+        assert_eq!(state.snapshot.slot, 5);
+        assert_eq!(state.snapshot.lb_pair.active_id, 10);
+
+        // Older notification of the same account
+        let stale_lb_pair = LbPair {
+            active_id: 9,
+            ..Default::default()
+        };
+        let stale_update = AccountUpdate {
+            pubkey: lb_pair_pubkey,
+            slot: 4,
+            data: account_data(&stale_lb_pair),
+        };
+        assert!(!state.apply(lb_pair_pubkey, &stale_update).unwrap());
+        assert_eq!(state.snapshot.lb_pair.active_id, 10);
+
+        // Bin array update at a later slot
+        let bin_array = BinArray {
+            index: 0,
+            version: 1,
+            _padding: [0u8; 7],
+            lb_pair: lb_pair_pubkey,
+            bins: [Bin::default(); MAX_BIN_PER_ARRAY],
+        };
+        let bin_array_pubkey = Pubkey::new_unique();
+        let update = AccountUpdate {
+            pubkey: bin_array_pubkey,
+            slot: 7,
+            data: account_data(&bin_array),
+        };
+        assert!(state.apply(lb_pair_pubkey, &update).unwrap());
+        assert_eq!(state.snapshot.slot, 7);
+        assert!(state.snapshot.bin_arrays.contains_key(&bin_array_pubkey));
+
+        state.remove_bin_array(&bin_array_pubkey);
+        assert!(state.snapshot.bin_arrays.is_empty());
+    }
+
+    /// Requires a local validator with a mainnet pair cloned, for example
+    /// `solana-test-validator --url mainnet-beta --clone-upgradeable-program LBUZKhRxPF3XUpBCjp4YzTKgLccjZhTSDM9YuVaPwxo --clone HTvjzsfX3yU6BUodCjZ5vZkUrAxMDTrBs3CJaq43ashR --clone <bin arrays around the active bin>`
+    #[tokio::test]
+    #[ignore]
+    async fn test_pair_cache_against_test_validator() {
+        let lb_pair = Pubkey::from_str("HTvjzsfX3yU6BUodCjZ5vZkUrAxMDTrBs3CJaq43ashR").unwrap();
+
+        let cache = PairCache::subscribe(
+            "http://127.0.0.1:8899".to_string(),
+            "ws://127.0.0.1:8900".to_string(),
+            lb_pair,
+            1,
+            CommitmentConfig::confirmed(),
+        )
+        .await
+        .unwrap();
+
+        // Let the driver pick up the bin arrays around the active bin
+        tokio::time::sleep(std::time::Duration::from_secs(2)).await;
+
+        let snapshot = cache.snapshot().unwrap();
+        assert!(snapshot.slot > 0);
+
+        // One initialized bin array on each side, the active one is shared by both sides
+        assert!(snapshot.bin_arrays.len() <= 2);
+        assert!(snapshot
+            .bin_arrays
+            .values()
+            .all(|bin_array| bin_array.lb_pair == lb_pair));
+
+        cache.close().await;
+    }
+}
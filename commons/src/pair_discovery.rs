@@ -0,0 +1,193 @@
+use anchor_lang::prelude::Pubkey;
+use anyhow::{ensure, Context, Result};
+use lb_clmm::{
+    constants::BASIS_POINT_MAX,
+    state::{bin::BinArray, lb_pair::LbPair, preset_parameters::PresetParameter},
+    utils::pda::{
+        derive_bin_array_pda, derive_customizable_permissionless_lb_pair, derive_lb_pair_pda2,
+    },
+};
+
+use crate::rewards::find_bin;
+
+/// Offset of `LbPair::token_x_mint` in the account data, for memcmp filters
+pub const LB_PAIR_TOKEN_X_MINT_OFFSET: usize = 8 + 32 + 32 + 16;
+/// Offset of `LbPair::token_y_mint` in the account data, for memcmp filters
+pub const LB_PAIR_TOKEN_Y_MINT_OFFSET: usize = LB_PAIR_TOKEN_X_MINT_OFFSET + 32;
+
+/// Addresses the pairs of the two mints can be derived at: the permissionless pair of every preset parameter, the
+/// legacy pair of every bin step and the customizable permissionless pair. Most of them don't exist. Permission pairs
+/// depend on their base key and can only be found through memcmp on the mints.
+pub fn derive_pair_candidates(
+    token_a_mint: Pubkey,
+    token_b_mint: Pubkey,
+    preset_parameters: &[PresetParameter],
+) -> Vec<Pubkey> {
+    let mut candidates = vec![];
+    for preset_parameter in preset_parameters {
+        let (lb_pair, _bump) = derive_lb_pair_pda2(
+            token_a_mint,
+            token_b_mint,
+            preset_parameter.bin_step,
+            preset_parameter.base_factor,
+        );
+        candidates.push(lb_pair);
+
+        #[allow(deprecated)]
+        let (legacy_lb_pair, _bump) = lb_clmm::utils::pda::derive_lb_pair_pda(
+            token_a_mint,
+            token_b_mint,
+            preset_parameter.bin_step,
+        );
+        candidates.push(legacy_lb_pair);
+    }
+
+    let (customizable_lb_pair, _bump) =
+        derive_customizable_permissionless_lb_pair(token_a_mint, token_b_mint);
+    candidates.push(customizable_lb_pair);
+
+    candidates.sort();
+    candidates.dedup();
+    candidates
+}
+
+/// Liquidity of the bins priced within `depth_bps` of the active bin price
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PairDepth {
+    pub lower_bin_id: i32,
+    pub upper_bin_id: i32,
+    pub amount_x: u64,
+    pub amount_y: u64,
+}
+
+/// Bins priced within `depth_bps` of the active bin price, as (lower bin id, upper bin id).
+pub fn get_depth_bin_range(lb_pair: &LbPair, depth_bps: u16) -> Result<(i32, i32)> {
+    ensure!(lb_pair.bin_step > 0, "Invalid bin step");
+
+    let depth = 1.0 + f64::from(depth_bps) / BASIS_POINT_MAX as f64;
+    let bin_step = 1.0 + f64::from(lb_pair.bin_step) / BASIS_POINT_MAX as f64;
+    let bin_count = (depth.ln() / bin_step.ln()).floor() as i32;
+
+    let lower_bin_id = lb_pair
+        .active_id
+        .checked_sub(bin_count)
+        .context("MathOverflow")?;
+    let upper_bin_id = lb_pair
+        .active_id
+        .checked_add(bin_count)
+        .context("MathOverflow")?;
+
+    Ok((lower_bin_id, upper_bin_id))
+}
+
+/// Bin arrays to fetch for [`get_pair_depth`].
+pub fn get_depth_bin_array_pubkeys(
+    lb_pair_pubkey: Pubkey,
+    lb_pair: &LbPair,
+    depth_bps: u16,
+) -> Result<Vec<Pubkey>> {
+    let (lower_bin_id, upper_bin_id) = get_depth_bin_range(lb_pair, depth_bps)?;
+    let lower_bin_array_idx = BinArray::bin_id_to_bin_array_index(lower_bin_id)?;
+    let upper_bin_array_idx = BinArray::bin_id_to_bin_array_index(upper_bin_id)?;
+
+    Ok((lower_bin_array_idx..=upper_bin_array_idx)
+        .map(|idx| derive_bin_array_pda(lb_pair_pubkey, idx.into()).0)
+        .collect())
+}
+
+/// Token X and Y held by the bins priced within `depth_bps` of the active bin price. Bins of missing bin arrays
+/// hold no liquidity.
+pub fn get_pair_depth(
+    lb_pair: &LbPair,
+    bin_arrays: &[BinArray],
+    depth_bps: u16,
+) -> Result<PairDepth> {
+    let (lower_bin_id, upper_bin_id) = get_depth_bin_range(lb_pair, depth_bps)?;
+
+    let mut amount_x = 0u64;
+    let mut amount_y = 0u64;
+    for bin_id in lower_bin_id..=upper_bin_id {
+        if let Some(bin) = find_bin(bin_arrays, bin_id)? {
+            amount_x = amount_x.checked_add(bin.amount_x).context("MathOverflow")?;
+            amount_y = amount_y.checked_add(bin.amount_y).context("MathOverflow")?;
+        }
+    }
+
+    Ok(PairDepth {
+        lower_bin_id,
+        upper_bin_id,
+        amount_x,
+        amount_y,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lb_clmm::{constants::MAX_BIN_PER_ARRAY, state::bin::Bin};
+
+    #[test]
+    fn test_token_mint_offsets() {
+        let lb_pair = LbPair {
+            token_x_mint: Pubkey::new_unique(),
+            token_y_mint: Pubkey::new_unique(),
+            ..Default::default()
+        };
+        let mut data = vec![0u8; 8];
+        data.extend_from_slice(bytemuck::bytes_of(&lb_pair));
+
+        assert_eq!(
+            &data[LB_PAIR_TOKEN_X_MINT_OFFSET..LB_PAIR_TOKEN_X_MINT_OFFSET + 32],
+            lb_pair.token_x_mint.as_ref()
+        );
+        assert_eq!(
+            &data[LB_PAIR_TOKEN_Y_MINT_OFFSET..LB_PAIR_TOKEN_Y_MINT_OFFSET + 32],
+            lb_pair.token_y_mint.as_ref()
+        );
+    }
+
+    #[test]
+    fn test_get_pair_depth() {
+        let mut bin_array = BinArray {
+            index: 0,
+            version: 1,
+            _padding: [0u8; 7],
+            lb_pair: Default::default(),
+            bins: [Bin::default(); MAX_BIN_PER_ARRAY],
+        };
+        for (bin_id, bin) in bin_array.bins.iter_mut().enumerate() {
+            bin.amount_x = bin_id as u64;
+            bin.amount_y = 1;
+        }
+
+        // 100 bps bin step, 1.01^2 <= 1.03 < 1.01^3
+        let lb_pair = LbPair {
+            active_id: 10,
+            bin_step: 100,
+            ..Default::default()
+        };
+        let depth = get_pair_depth(&lb_pair, &[bin_array], 300).unwrap();
+        assert_eq!(
+            depth,
+            PairDepth {
+                lower_bin_id: 8,
+                upper_bin_id: 12,
+                amount_x: 8 + 9 + 10 + 11 + 12,
+                amount_y: 5,
+            }
+        );
+
+        // 1.01^2 > 1.02
+        let depth = get_pair_depth(&lb_pair, &[bin_array], 200).unwrap();
+        assert_eq!((depth.lower_bin_id, depth.upper_bin_id), (9, 11));
+
+        // Bins below 0 are in a missing bin array
+        let lb_pair = LbPair {
+            active_id: 0,
+            bin_step: 100,
+            ..Default::default()
+        };
+        let depth = get_pair_depth(&lb_pair, &[bin_array], 300).unwrap();
+        assert_eq!((depth.amount_x, depth.amount_y), (1 + 2, 3));
+    }
+}
@@ -0,0 +1,242 @@
+use anchor_lang::AccountDeserialize;
+use anyhow::{Context, Result};
+use lb_clmm::{
+    constants::NUM_REWARDS,
+    math::u64x64_math::SCALE_OFFSET,
+    state::{bin::BinArray, lb_pair::LbPair, position::PositionV2},
+};
+
+use crate::rewards::find_bin;
+
+/// Deserialize a bin array account. Bin arrays still in the V0 layout are migrated as the program does on access,
+/// so their liquidity supply is in the same scale as the position liquidity shares.
+pub fn deserialize_bin_array(data: &[u8]) -> Result<BinArray> {
+    let mut bin_array = BinArray::try_deserialize(&mut &data[..])?;
+    bin_array.migrate_to_v2()?;
+    Ok(bin_array)
+}
+
+/// Fees claimable by the position, including the fees accrued by its bins since the position was last updated.
+/// `bin_arrays` must cover the whole position range.
+pub fn get_position_pending_fees(
+    position: &PositionV2,
+    bin_arrays: &[BinArray],
+) -> Result<(u64, u64)> {
+    let mut position = *position;
+
+    for bin_id in position.lower_bin_id..=position.upper_bin_id {
+        let bin = find_bin(bin_arrays, bin_id)?
+            .with_context(|| format!("Bin array of bin {} not found", bin_id))?;
+        position.update_fee_per_token_stored(bin_id, bin)?;
+    }
+
+    Ok(position.claim_fee()?)
+}
+
+/// Rewards claimable by the position for each reward index, including the rewards accrued by the active bin since
+/// the pair rewards were last updated. `bin_arrays` must cover the whole position range.
+pub fn get_position_pending_rewards(
+    lb_pair: &LbPair,
+    position: &PositionV2,
+    bin_arrays: &[BinArray],
+    current_timestamp: u64,
+) -> Result<[u64; NUM_REWARDS]> {
+    let mut position = *position;
+
+    for bin_id in position.lower_bin_id..=position.upper_bin_id {
+        let mut bin = *find_bin(bin_arrays, bin_id)?
+            .with_context(|| format!("Bin array of bin {} not found", bin_id))?;
+
+        // Same as BinArray::update_all_rewards, without updating the pair
+        if bin_id == lb_pair.active_id && bin.liquidity_supply > 0 {
+            let liquidity_supply: u64 = (bin.liquidity_supply >> SCALE_OFFSET)
+                .try_into()
+                .context("TypeCastFailed")?;
+            for (reward_idx, reward_info) in lb_pair.reward_infos.iter().enumerate() {
+                if !reward_info.initialized() {
+                    continue;
+                }
+                let reward_per_token_stored_delta = reward_info
+                    .calculate_reward_per_token_stored_since_last_update(
+                        current_timestamp,
+                        liquidity_supply,
+                    )?;
+                bin.reward_per_token_stored[reward_idx] = bin.reward_per_token_stored[reward_idx]
+                    .checked_add(reward_per_token_stored_delta)
+                    .context("MathOverflow")?;
+            }
+        }
+
+        position.update_reward_per_token_stored(bin_id, &bin)?;
+    }
+
+    let mut rewards = [0u64; NUM_REWARDS];
+    for (reward_idx, reward) in rewards.iter_mut().enumerate() {
+        *reward = position.get_total_reward(reward_idx)?;
+    }
+
+    Ok(rewards)
+}
+
+/// Token X and token Y amounts the position would receive by withdrawing all its liquidity.
+/// `bin_arrays` must cover the whole position range.
+pub fn get_position_amounts(position: &PositionV2, bin_arrays: &[BinArray]) -> Result<(u64, u64)> {
+    let mut amount_x = 0u64;
+    let mut amount_y = 0u64;
+
+    for bin_id in position.lower_bin_id..=position.upper_bin_id {
+        let liquidity_share = position.get_liquidity_share_in_bin(bin_id)?;
+        if liquidity_share == 0 {
+            continue;
+        }
+
+        let bin = find_bin(bin_arrays, bin_id)?
+            .with_context(|| format!("Bin array of bin {} not found", bin_id))?;
+        let (bin_amount_x, bin_amount_y) = bin.calculate_out_amount(liquidity_share)?;
+
+        amount_x = amount_x.checked_add(bin_amount_x).context("MathOverflow")?;
+        amount_y = amount_y.checked_add(bin_amount_y).context("MathOverflow")?;
+    }
+
+    Ok((amount_x, amount_y))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::prelude::Pubkey;
+    use anchor_lang::Discriminator;
+    use lb_clmm::{constants::MAX_BIN_PER_ARRAY, state::bin::Bin};
+
+    fn bin_array(index: i64) -> BinArray {
+        BinArray {
+            index,
+            version: 1,
+            _padding: [0u8; 7],
+            lb_pair: Default::default(),
+            bins: [Bin::default(); MAX_BIN_PER_ARRAY],
+        }
+    }
+
+    #[test]
+    fn test_get_position_pending_fees() {
+        let mut bin_array = bin_array(0);
+        // 0.5 token X and 2 token Y fee per unit of liquidity
+        bin_array
+            .get_bin_mut(1)
+            .unwrap()
+            .fee_amount_x_per_token_stored = 1 << (SCALE_OFFSET - 1);
+        bin_array
+            .get_bin_mut(2)
+            .unwrap()
+            .fee_amount_y_per_token_stored = 2 << SCALE_OFFSET;
+
+        let mut position = PositionV2 {
+            lower_bin_id: 1,
+            upper_bin_id: 2,
+            ..Default::default()
+        };
+        position.liquidity_shares[0] = 1_000 << SCALE_OFFSET;
+        position.liquidity_shares[1] = 1_000 << SCALE_OFFSET;
+        // Fee already settled into the position
+        position.fee_infos[0].fee_x_pending = 10;
+
+        let (fee_x, fee_y) = get_position_pending_fees(&position, &[bin_array]).unwrap();
+        assert_eq!(fee_x, 510);
+        assert_eq!(fee_y, 2_000);
+
+        // Bin array not covering the position range
+        position.upper_bin_id = MAX_BIN_PER_ARRAY as i32;
+        assert!(get_position_pending_fees(&position, &[bin_array]).is_err());
+    }
+
+    #[test]
+    fn test_get_position_pending_rewards() {
+        const NOW: u64 = 1_700_000_000;
+
+        let mut bin_array = bin_array(0);
+        let bin = bin_array.get_bin_mut(1).unwrap();
+        bin.liquidity_supply = 2_000 << SCALE_OFFSET;
+        // 1 reward token per unit of liquidity already stored
+        bin.reward_per_token_stored[0] = 1 << SCALE_OFFSET;
+
+        let mut lb_pair = LbPair {
+            active_id: 1,
+            ..Default::default()
+        };
+        lb_pair.reward_infos[0].mint = Pubkey::new_unique();
+        // 10 tokens per second for 100 more seconds
+        lb_pair.reward_infos[0].reward_rate = 10 << SCALE_OFFSET;
+        lb_pair.reward_infos[0].last_update_time = NOW - 100;
+        lb_pair.reward_infos[0].reward_duration_end = NOW + 100;
+
+        let mut position = PositionV2 {
+            lower_bin_id: 1,
+            upper_bin_id: 2,
+            ..Default::default()
+        };
+        // Half of the active bin liquidity
+        position.liquidity_shares[0] = 1_000 << SCALE_OFFSET;
+        position.reward_infos[1].reward_pendings[1] = 7;
+
+        let rewards = get_position_pending_rewards(&lb_pair, &position, &[bin_array], NOW).unwrap();
+        // 1_000 stored, plus half of the 1_000 accrued by the active bin since the last update
+        assert_eq!(rewards, [1_500, 7]);
+    }
+
+    #[test]
+    fn test_get_position_amounts() {
+        let mut bin_array = bin_array(0);
+        let bin = bin_array.get_bin_mut(1).unwrap();
+        bin.amount_x = 1_000;
+        bin.amount_y = 3_000;
+        bin.liquidity_supply = 4_000 << SCALE_OFFSET;
+
+        let mut position = PositionV2 {
+            lower_bin_id: 1,
+            upper_bin_id: 2,
+            ..Default::default()
+        };
+        // A quarter of the bin liquidity
+        position.liquidity_shares[0] = 1_000 << SCALE_OFFSET;
+
+        let (amount_x, amount_y) = get_position_amounts(&position, &[bin_array]).unwrap();
+        assert_eq!(amount_x, 250);
+        assert_eq!(amount_y, 750);
+
+        // Empty bins outside of the bin arrays are skipped
+        position.upper_bin_id = MAX_BIN_PER_ARRAY as i32;
+        assert!(get_position_amounts(&position, &[bin_array]).is_ok());
+    }
+
+    #[test]
+    fn test_get_position_amounts_of_v0_bin_array() {
+        let mut bin_array = bin_array(0);
+        bin_array.version = 0;
+        let bin = bin_array.get_bin_mut(1).unwrap();
+        bin.amount_x = 1_000;
+        bin.amount_y = 3_000;
+        // Unscaled liquidity supply of the V0 layout
+        bin.liquidity_supply = 4_000;
+
+        let mut data = BinArray::discriminator().to_vec();
+        data.extend_from_slice(bytemuck::bytes_of(&bin_array));
+        let bin_array = deserialize_bin_array(&data).unwrap();
+        assert_eq!(bin_array.version, 1);
+        assert_eq!(
+            bin_array.get_bin(1).unwrap().liquidity_supply,
+            4_000 << SCALE_OFFSET
+        );
+
+        let mut position = PositionV2 {
+            lower_bin_id: 1,
+            upper_bin_id: 1,
+            ..Default::default()
+        };
+        position.liquidity_shares[0] = 1_000 << SCALE_OFFSET;
+
+        let (amount_x, amount_y) = get_position_amounts(&position, &[bin_array]).unwrap();
+        assert_eq!(amount_x, 250);
+        assert_eq!(amount_y, 750);
+    }
+}
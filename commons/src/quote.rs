@@ -1,6 +1,7 @@
-use anchor_client::solana_sdk::pubkey::Pubkey;
+use anchor_lang::prelude::Pubkey;
 use anyhow::{ensure, Context, Result};
 use lb_clmm::{
+    math::{price_math::get_price_from_id, u64x64_math::SCALE_OFFSET},
     pair_action_access::ActivationType,
     state::{
         bin::{Bin, BinArray, SwapResult},
@@ -15,15 +16,21 @@ use std::collections::HashMap;
 pub struct SwapExactInQuote {
     pub amount_out: u64,
     pub fee: u64,
+    /// Bin arrays crossed by the swap, in swap order
+    pub bin_arrays: Vec<Pubkey>,
 }
 
 #[derive(Debug)]
 pub struct SwapExactOutQuote {
     pub amount_in: u64,
     pub fee: u64,
+    /// Bin arrays crossed by the swap, in swap order
+    pub bin_arrays: Vec<Pubkey>,
 }
 
-fn validate_swap_activation(
+/// Ensure the pair accepts swaps from the public at the given clock. Permission and customizable
+/// permissionless pairs only open for swaps once the activation point is reached.
+pub fn validate_swap_activation(
     lb_pair: &LbPair,
     current_timestamp: u64,
     current_slot: u64,
@@ -34,7 +41,7 @@ fn validate_swap_activation(
     );
 
     let pair_type = lb_pair.pair_type()?;
-    if pair_type.eq(&PairType::Permission) {
+    if pair_type.eq(&PairType::Permission) || pair_type.eq(&PairType::CustomizablePermissionless) {
         let activation_type = ActivationType::try_from(lb_pair.activation_type)?;
         let current_point = match activation_type {
             ActivationType::Slot => current_slot,
@@ -67,6 +74,7 @@ pub fn quote_exact_out(
 
     let mut total_amount_in: u64 = 0;
     let mut total_fee: u64 = 0;
+    let mut crossed_bin_arrays = vec![];
 
     while amount_out > 0 {
         let active_bin_array_pubkey = get_bin_array_pubkeys_for_swap(
@@ -83,6 +91,7 @@ pub fn quote_exact_out(
             .get(&active_bin_array_pubkey)
             .cloned()
             .context("Active bin array not found")?;
+        crossed_bin_arrays.push(active_bin_array_pubkey);
 
         loop {
             if active_bin_array
@@ -136,6 +145,7 @@ pub fn quote_exact_out(
     Ok(SwapExactOutQuote {
         amount_in: total_amount_in,
         fee: total_fee,
+        bin_arrays: crossed_bin_arrays,
     })
 }
 
@@ -156,6 +166,7 @@ pub fn quote_exact_in(
 
     let mut total_amount_out: u64 = 0;
     let mut total_fee: u64 = 0;
+    let mut crossed_bin_arrays = vec![];
 
     while amount_in > 0 {
         let active_bin_array_pubkey = get_bin_array_pubkeys_for_swap(
@@ -172,6 +183,7 @@ pub fn quote_exact_in(
             .get(&active_bin_array_pubkey)
             .cloned()
             .context("Active bin array not found")?;
+        crossed_bin_arrays.push(active_bin_array_pubkey);
 
         loop {
             if active_bin_array
@@ -214,9 +226,29 @@ pub fn quote_exact_in(
     Ok(SwapExactInQuote {
         amount_out: total_amount_out,
         fee: total_fee,
+        bin_arrays: crossed_bin_arrays,
     })
 }
 
+/// Price impact of a swap in percentage. Compares the execution price, fees excluded, with the price
+/// of the active bin before the swap.
+pub fn get_price_impact_pct(
+    lb_pair: &LbPair,
+    amount_in_without_fee: u64,
+    amount_out: u64,
+    swap_for_y: bool,
+) -> Result<f64> {
+    ensure!(amount_in_without_fee > 0, "Amount in is zero");
+
+    let price = get_price_from_id(lb_pair.active_id, lb_pair.bin_step)? as f64
+        / (1u128 << SCALE_OFFSET) as f64;
+    // Price of the input token in the output token
+    let spot_price = if swap_for_y { price } else { 1.0 / price };
+    let execution_price = amount_out as f64 / amount_in_without_fee as f64;
+
+    Ok(((spot_price - execution_price) / spot_price * 100.0).max(0.0))
+}
+
 pub fn get_bin_array_pubkeys_for_swap(
     lb_pair_pubkey: Pubkey,
     lb_pair: &LbPair,
@@ -286,6 +318,24 @@ mod tests {
     };
     use std::{rc::Rc, str::FromStr};
 
+    #[test]
+    fn test_get_price_impact_pct() {
+        // Price of 1 at bin 0
+        let lb_pair = LbPair {
+            active_id: 0,
+            bin_step: 10,
+            ..Default::default()
+        };
+
+        let impact = get_price_impact_pct(&lb_pair, 1_000_000, 990_000, true).unwrap();
+        assert!((impact - 1.0).abs() < 1e-9);
+
+        let impact = get_price_impact_pct(&lb_pair, 1_000_000, 1_000_000, false).unwrap();
+        assert_eq!(impact, 0.0);
+
+        assert!(get_price_impact_pct(&lb_pair, 0, 0, true).is_err());
+    }
+
     /// Get on chain clock
     async fn get_clock(rpc_client: RpcClient) -> Result<Clock> {
         let clock_account = rpc_client
@@ -0,0 +1,414 @@
+use anchor_lang::prelude::Pubkey;
+use anyhow::{Context, Result};
+use lb_clmm::{
+    constants::NUM_REWARDS,
+    math::{
+        u128x128_math::{mul_shr, Rounding},
+        u64x64_math::SCALE_OFFSET,
+    },
+    state::{
+        bin::{Bin, BinArray},
+        lb_pair::LbPair,
+        position::PositionV2,
+    },
+};
+
+const SECONDS_PER_DAY: u64 = 86_400;
+const DAYS_PER_YEAR: f64 = 365.0;
+
+/// UI price of a token and its decimals. All prices passed together must be quoted in the same currency.
+#[derive(Debug, Clone, Copy)]
+pub struct TokenPrice {
+    pub decimals: u8,
+    pub price: f64,
+}
+
+/// Prices used to convert the projected rewards, and the liquidity earning them, into APR.
+#[derive(Debug, Clone, Copy)]
+pub struct RewardPrices {
+    pub token_x: TokenPrice,
+    pub token_y: TokenPrice,
+    /// Price of the reward token for each reward index. None if the price is unknown.
+    pub rewards: [Option<TokenPrice>; NUM_REWARDS],
+}
+
+#[derive(Debug)]
+pub struct RewardProjection {
+    pub reward_index: usize,
+    pub mint: Pubkey,
+    /// Reward amount accrued per day at the current emission rate and liquidity share.
+    pub reward_per_day: f64,
+    /// Reward amount left to be accrued until the farm ends, assuming the liquidity share stays the same.
+    pub remaining_reward: f64,
+    /// Seconds left until the farm ends.
+    pub remaining_seconds: u64,
+    /// Annualized reward value over the value of the liquidity earning it. 1.0 = 100%. None when prices are missing or there's no liquidity.
+    pub apr: Option<f64>,
+}
+
+pub(crate) fn find_bin(bin_arrays: &[BinArray], bin_id: i32) -> Result<Option<&Bin>> {
+    let bin_array_idx = BinArray::bin_id_to_bin_array_index(bin_id)?;
+    let bin = match bin_arrays
+        .iter()
+        .find(|bin_array| bin_array.index == bin_array_idx as i64)
+    {
+        Some(bin_array) => Some(bin_array.get_bin(bin_id)?),
+        None => None,
+    };
+    Ok(bin)
+}
+
+fn to_ui_amount(amount: f64, decimals: u8) -> f64 {
+    amount / 10f64.powi(decimals.into())
+}
+
+/// Rewards are only distributed to the active bin. Liquidity earns a part of the emission proportional to its share of the active bin liquidity supply.
+fn project_rewards(
+    lb_pair: &LbPair,
+    liquidity_share_in_active_bin: u128,
+    active_bin_liquidity_supply: u128,
+    amount_x: u64,
+    amount_y: u64,
+    prices: Option<&RewardPrices>,
+    current_timestamp: u64,
+) -> Result<Vec<RewardProjection>> {
+    let share_ratio = if active_bin_liquidity_supply == 0 {
+        0.0
+    } else {
+        liquidity_share_in_active_bin as f64 / active_bin_liquidity_supply as f64
+    };
+
+    let liquidity_value = prices.map(|prices| {
+        to_ui_amount(amount_x as f64, prices.token_x.decimals) * prices.token_x.price
+            + to_ui_amount(amount_y as f64, prices.token_y.decimals) * prices.token_y.price
+    });
+
+    let mut projections = vec![];
+
+    for (reward_index, reward_info) in lb_pair.reward_infos.iter().enumerate() {
+        if !reward_info.initialized() {
+            continue;
+        }
+
+        let remaining_seconds = reward_info
+            .reward_duration_end
+            .saturating_sub(current_timestamp);
+
+        // reward_rate is Q64.64 token amount emitted per second
+        let reward_per_second = if remaining_seconds > 0 {
+            reward_info.reward_rate as f64 / 2f64.powi(SCALE_OFFSET.into()) * share_ratio
+        } else {
+            0.0
+        };
+
+        let reward_per_day = reward_per_second * SECONDS_PER_DAY as f64;
+        let remaining_reward = reward_per_second * remaining_seconds as f64;
+
+        let reward_price = prices.and_then(|prices| prices.rewards[reward_index]);
+
+        let apr = match (reward_price, liquidity_value) {
+            (Some(reward_price), Some(liquidity_value)) if liquidity_value > 0.0 => {
+                let reward_value_per_day =
+                    to_ui_amount(reward_per_day, reward_price.decimals) * reward_price.price;
+                Some(reward_value_per_day * DAYS_PER_YEAR / liquidity_value)
+            }
+            _ => None,
+        };
+
+        projections.push(RewardProjection {
+            reward_index,
+            mint: reward_info.mint,
+            reward_per_day,
+            remaining_reward,
+            remaining_seconds,
+            apr,
+        });
+    }
+
+    Ok(projections)
+}
+
+/// Project the rewards emitted to the pair. The APR is relative to the liquidity of the active bin, as it's the only bin earning rewards.
+pub fn project_pair_rewards(
+    lb_pair: &LbPair,
+    active_bin: &Bin,
+    prices: Option<&RewardPrices>,
+    current_timestamp: u64,
+) -> Result<Vec<RewardProjection>> {
+    project_rewards(
+        lb_pair,
+        active_bin.liquidity_supply,
+        active_bin.liquidity_supply,
+        active_bin.amount_x,
+        active_bin.amount_y,
+        prices,
+        current_timestamp,
+    )
+}
+
+/// Project the rewards earned by all the liquidity within a bin range. The range earns nothing when the active bin is outside of it. Bins of missing bin arrays are treated as empty.
+pub fn project_bin_range_rewards(
+    lb_pair: &LbPair,
+    bin_arrays: &[BinArray],
+    lower_bin_id: i32,
+    upper_bin_id: i32,
+    prices: Option<&RewardPrices>,
+    current_timestamp: u64,
+) -> Result<Vec<RewardProjection>> {
+    let mut amount_x: u64 = 0;
+    let mut amount_y: u64 = 0;
+
+    for bin_id in lower_bin_id..=upper_bin_id {
+        if let Some(bin) = find_bin(bin_arrays, bin_id)? {
+            amount_x = amount_x.checked_add(bin.amount_x).context("MathOverflow")?;
+            amount_y = amount_y.checked_add(bin.amount_y).context("MathOverflow")?;
+        }
+    }
+
+    let active_bin_liquidity_supply = find_bin(bin_arrays, lb_pair.active_id)?
+        .map(|bin| bin.liquidity_supply)
+        .unwrap_or_default();
+
+    let liquidity_share_in_active_bin =
+        if lb_pair.active_id >= lower_bin_id && lb_pair.active_id <= upper_bin_id {
+            active_bin_liquidity_supply
+        } else {
+            0
+        };
+
+    project_rewards(
+        lb_pair,
+        liquidity_share_in_active_bin,
+        active_bin_liquidity_supply,
+        amount_x,
+        amount_y,
+        prices,
+        current_timestamp,
+    )
+}
+
+/// Project the rewards earned by a position. The bin arrays must cover the whole position.
+pub fn project_position_rewards(
+    lb_pair: &LbPair,
+    position: &PositionV2,
+    bin_arrays: &[BinArray],
+    prices: Option<&RewardPrices>,
+    current_timestamp: u64,
+) -> Result<Vec<RewardProjection>> {
+    let mut amount_x: u64 = 0;
+    let mut amount_y: u64 = 0;
+
+    for (idx, &liquidity_share) in position.liquidity_shares.iter().enumerate() {
+        if liquidity_share == 0 {
+            continue;
+        }
+        let bin_id = position.from_idx_to_bin_id(idx)?;
+        let bin = find_bin(bin_arrays, bin_id)?.context("Bin array of position not found")?;
+        let (bin_amount_x, bin_amount_y) = bin.calculate_out_amount(liquidity_share)?;
+
+        amount_x = amount_x.checked_add(bin_amount_x).context("MathOverflow")?;
+        amount_y = amount_y.checked_add(bin_amount_y).context("MathOverflow")?;
+    }
+
+    let (liquidity_share_in_active_bin, active_bin_liquidity_supply) =
+        if position.id_within_position(lb_pair.active_id).is_ok() {
+            let active_bin =
+                find_bin(bin_arrays, lb_pair.active_id)?.context("Active bin array not found")?;
+            (
+                position.get_liquidity_share_in_bin(lb_pair.active_id)?,
+                active_bin.liquidity_supply,
+            )
+        } else {
+            (0, 0)
+        };
+
+    project_rewards(
+        lb_pair,
+        liquidity_share_in_active_bin,
+        active_bin_liquidity_supply,
+        amount_x,
+        amount_y,
+        prices,
+        current_timestamp,
+    )
+}
+
+/// Reward emitted while the active bin had no liquidity, which the funder can withdraw with `withdraw_ineligible_reward`
+/// once the reward ended. Counts the seconds since the last update of the reward when the active bin is still empty.
+pub fn get_ineligible_reward(
+    lb_pair: &LbPair,
+    active_bin: &Bin,
+    reward_index: usize,
+    current_timestamp: u64,
+) -> Result<u64> {
+    let reward_info = lb_pair
+        .reward_infos
+        .get(reward_index)
+        .context("Invalid reward index")?;
+
+    let mut empty_liquidity_seconds = reward_info.cumulative_seconds_with_empty_liquidity_reward;
+    if active_bin.liquidity_supply == 0 {
+        let elapsed_seconds = std::cmp::min(current_timestamp, reward_info.reward_duration_end)
+            .saturating_sub(reward_info.last_update_time);
+        empty_liquidity_seconds = empty_liquidity_seconds
+            .checked_add(elapsed_seconds)
+            .context("MathOverflow")?;
+    }
+
+    // reward_rate is Q64.64 token amount emitted per second
+    let ineligible_reward = mul_shr(
+        empty_liquidity_seconds.into(),
+        reward_info.reward_rate,
+        SCALE_OFFSET,
+        Rounding::Down,
+    )
+    .context("MathOverflow")?;
+
+    Ok(ineligible_reward.try_into()?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lb_clmm::constants::MAX_BIN_PER_ARRAY;
+
+    const NOW: u64 = 1_700_000_000;
+    const ONE_TOKEN_PER_SECOND: u128 = 1_000_000 << SCALE_OFFSET;
+
+    fn lb_pair_with_reward(active_id: i32) -> LbPair {
+        let mut lb_pair = LbPair {
+            active_id,
+            ..Default::default()
+        };
+        lb_pair.reward_infos[0].mint = Pubkey::new_unique();
+        lb_pair.reward_infos[0].reward_rate = ONE_TOKEN_PER_SECOND;
+        lb_pair.reward_infos[0].reward_duration_end = NOW + 10 * SECONDS_PER_DAY;
+        lb_pair
+    }
+
+    fn bin_array_with_active_bin(active_id: i32, liquidity_supply: u128) -> BinArray {
+        let mut bin_array = BinArray {
+            index: BinArray::bin_id_to_bin_array_index(active_id)
+                .unwrap()
+                .into(),
+            version: 1,
+            _padding: [0u8; 7],
+            lb_pair: Pubkey::default(),
+            bins: [Bin::default(); MAX_BIN_PER_ARRAY],
+        };
+        let bin = bin_array.get_bin_mut(active_id).unwrap();
+        bin.amount_x = 1_000_000;
+        bin.amount_y = 1_000_000;
+        bin.liquidity_supply = liquidity_supply;
+        bin_array
+    }
+
+    fn prices() -> RewardPrices {
+        let price = TokenPrice {
+            decimals: 6,
+            price: 1.0,
+        };
+        RewardPrices {
+            token_x: price,
+            token_y: price,
+            rewards: [Some(price), None],
+        }
+    }
+
+    #[test]
+    fn test_get_ineligible_reward() {
+        let active_id = 5;
+        let mut lb_pair = lb_pair_with_reward(active_id);
+        lb_pair.reward_infos[0].cumulative_seconds_with_empty_liquidity_reward = 100;
+        lb_pair.reward_infos[0].last_update_time = NOW - 50;
+
+        // Active bin with liquidity: only the recorded seconds
+        let bin_array = bin_array_with_active_bin(active_id, 2_000_000 << SCALE_OFFSET);
+        let active_bin = bin_array.get_bin(active_id).unwrap();
+        assert_eq!(
+            get_ineligible_reward(&lb_pair, active_bin, 0, NOW).unwrap(),
+            100 * 1_000_000
+        );
+
+        // Empty active bin: the seconds since the last update too
+        let bin_array = bin_array_with_active_bin(active_id, 0);
+        let active_bin = bin_array.get_bin(active_id).unwrap();
+        assert_eq!(
+            get_ineligible_reward(&lb_pair, active_bin, 0, NOW).unwrap(),
+            150 * 1_000_000
+        );
+
+        assert!(get_ineligible_reward(&lb_pair, active_bin, NUM_REWARDS, NOW).is_err());
+    }
+
+    #[test]
+    fn test_project_pair_rewards() {
+        let active_id = 5;
+        let lb_pair = lb_pair_with_reward(active_id);
+        let bin_array = bin_array_with_active_bin(active_id, 2_000_000 << SCALE_OFFSET);
+        let active_bin = bin_array.get_bin(active_id).unwrap();
+
+        let projections = project_pair_rewards(&lb_pair, active_bin, Some(&prices()), NOW).unwrap();
+
+        // Uninitialized reward is skipped
+        assert_eq!(projections.len(), 1);
+        let projection = &projections[0];
+        assert_eq!(projection.reward_per_day, 86_400_000_000.0);
+        assert_eq!(projection.remaining_seconds, 10 * SECONDS_PER_DAY);
+        assert_eq!(projection.remaining_reward, 864_000_000_000.0);
+        // 86400 USD per day over 2 USD of liquidity
+        assert_eq!(projection.apr, Some(86_400.0 * DAYS_PER_YEAR / 2.0));
+    }
+
+    #[test]
+    fn test_project_position_rewards_by_active_bin_share() {
+        let active_id = 5;
+        let lb_pair = lb_pair_with_reward(active_id);
+        let bin_arrays = [bin_array_with_active_bin(
+            active_id,
+            4_000_000 << SCALE_OFFSET,
+        )];
+
+        let mut position = PositionV2 {
+            lower_bin_id: 0,
+            upper_bin_id: 69,
+            ..Default::default()
+        };
+        position
+            .deposit(active_id, 1_000_000 << SCALE_OFFSET)
+            .unwrap();
+
+        let projections =
+            project_position_rewards(&lb_pair, &position, &bin_arrays, None, NOW).unwrap();
+        assert_eq!(projections[0].reward_per_day, 86_400_000_000.0 / 4.0);
+        assert_eq!(projections[0].apr, None);
+
+        // Out of range position earns nothing
+        let lb_pair = lb_pair_with_reward(100);
+        let projections =
+            project_position_rewards(&lb_pair, &position, &bin_arrays, None, NOW).unwrap();
+        assert_eq!(projections[0].reward_per_day, 0.0);
+    }
+
+    #[test]
+    fn test_project_bin_range_rewards_after_farm_ended() {
+        let active_id = 5;
+        let lb_pair = lb_pair_with_reward(active_id);
+        let bin_arrays = [bin_array_with_active_bin(
+            active_id,
+            2_000_000 << SCALE_OFFSET,
+        )];
+
+        let projections =
+            project_bin_range_rewards(&lb_pair, &bin_arrays, 0, 10, Some(&prices()), NOW).unwrap();
+        assert_eq!(projections[0].reward_per_day, 86_400_000_000.0);
+
+        let ended_at = NOW + 10 * SECONDS_PER_DAY;
+        let projections =
+            project_bin_range_rewards(&lb_pair, &bin_arrays, 0, 10, Some(&prices()), ended_at)
+                .unwrap();
+        assert_eq!(projections[0].reward_per_day, 0.0);
+        assert_eq!(projections[0].remaining_seconds, 0);
+        assert_eq!(projections[0].apr, Some(0.0));
+    }
+}
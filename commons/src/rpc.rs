@@ -0,0 +1,126 @@
+use anchor_client::solana_client::client_error::Result as ClientResult;
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_client::RpcClient as BlockingRpcClient;
+use anchor_client::solana_client::rpc_config::{
+    RpcSendTransactionConfig, RpcSimulateTransactionConfig,
+};
+use anchor_client::solana_client::rpc_response::{
+    RpcPrioritizationFee, RpcSimulateTransactionResult,
+};
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::hash::Hash;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::transaction::VersionedTransaction;
+use anchor_lang::prelude::Pubkey;
+use solana_transaction_status::TransactionStatus;
+use std::future::Future;
+use std::time::Duration;
+
+/// RPC client of the sender and the compute budget estimation. Their logic is written once as async functions over
+/// this client, and the blocking variants run them with [`block_on`]. The calls of a blocking client complete
+/// without yielding.
+#[derive(Clone, Copy)]
+pub(crate) enum Rpc<'a> {
+    Nonblocking(&'a RpcClient),
+    Blocking(&'a BlockingRpcClient),
+}
+
+impl Rpc<'_> {
+    pub fn commitment(&self) -> CommitmentConfig {
+        match self {
+            Rpc::Nonblocking(client) => client.commitment(),
+            Rpc::Blocking(client) => client.commitment(),
+        }
+    }
+
+    pub async fn sleep(&self, duration: Duration) {
+        match self {
+            Rpc::Nonblocking(_) => tokio::time::sleep(duration).await,
+            Rpc::Blocking(_) => std::thread::sleep(duration),
+        }
+    }
+
+    pub async fn get_latest_blockhash_with_commitment(
+        &self,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<(Hash, u64)> {
+        match self {
+            Rpc::Nonblocking(client) => {
+                client
+                    .get_latest_blockhash_with_commitment(commitment)
+                    .await
+            }
+            Rpc::Blocking(client) => client.get_latest_blockhash_with_commitment(commitment),
+        }
+    }
+
+    pub async fn send_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSendTransactionConfig,
+    ) -> ClientResult<Signature> {
+        match self {
+            Rpc::Nonblocking(client) => {
+                client
+                    .send_transaction_with_config(transaction, config)
+                    .await
+            }
+            Rpc::Blocking(client) => client.send_transaction_with_config(transaction, config),
+        }
+    }
+
+    pub async fn is_blockhash_valid(
+        &self,
+        blockhash: &Hash,
+        commitment: CommitmentConfig,
+    ) -> ClientResult<bool> {
+        match self {
+            Rpc::Nonblocking(client) => client.is_blockhash_valid(blockhash, commitment).await,
+            Rpc::Blocking(client) => client.is_blockhash_valid(blockhash, commitment),
+        }
+    }
+
+    pub async fn get_signature_statuses(
+        &self,
+        signatures: &[Signature],
+    ) -> ClientResult<Vec<Option<TransactionStatus>>> {
+        let response = match self {
+            Rpc::Nonblocking(client) => client.get_signature_statuses(signatures).await?,
+            Rpc::Blocking(client) => client.get_signature_statuses(signatures)?,
+        };
+        Ok(response.value)
+    }
+
+    pub async fn simulate_transaction_with_config(
+        &self,
+        transaction: &VersionedTransaction,
+        config: RpcSimulateTransactionConfig,
+    ) -> ClientResult<RpcSimulateTransactionResult> {
+        let response = match self {
+            Rpc::Nonblocking(client) => {
+                client
+                    .simulate_transaction_with_config(transaction, config)
+                    .await?
+            }
+            Rpc::Blocking(client) => {
+                client.simulate_transaction_with_config(transaction, config)?
+            }
+        };
+        Ok(response.value)
+    }
+
+    pub async fn get_recent_prioritization_fees(
+        &self,
+        addresses: &[Pubkey],
+    ) -> ClientResult<Vec<RpcPrioritizationFee>> {
+        match self {
+            Rpc::Nonblocking(client) => client.get_recent_prioritization_fees(addresses).await,
+            Rpc::Blocking(client) => client.get_recent_prioritization_fees(addresses),
+        }
+    }
+}
+
+/// Run a future over [`Rpc::Blocking`] on the current thread.
+pub(crate) fn block_on<T>(future: impl Future<Output = T>) -> T {
+    futures::executor::block_on(future)
+}
@@ -0,0 +1,468 @@
+use crate::rpc::{block_on, Rpc};
+use anchor_client::solana_client::nonblocking::rpc_client::RpcClient;
+use anchor_client::solana_client::rpc_client::RpcClient as BlockingRpcClient;
+use anchor_client::solana_client::rpc_config::RpcSendTransactionConfig;
+use anchor_client::solana_sdk::commitment_config::CommitmentConfig;
+use anchor_client::solana_sdk::instruction::InstructionError;
+use anchor_client::solana_sdk::message::VersionedMessage;
+use anchor_client::solana_sdk::signature::Signature;
+use anchor_client::solana_sdk::transaction::{TransactionError, VersionedTransaction};
+use anyhow::{anyhow, Result};
+use lb_clmm::errors::LBError;
+use solana_transaction_status::TransactionStatus;
+use std::fmt::{self, Display};
+use std::future::Future;
+use std::time::Duration;
+
+/// How transactions are sent and confirmed.
+#[derive(Debug, Clone, Copy)]
+pub struct SendConfig {
+    /// Commitment at which a transaction is confirmed
+    pub commitment: CommitmentConfig,
+    pub rpc_send_config: RpcSendTransactionConfig,
+    /// Interval between status polls. A transaction without status is broadcast again at each poll.
+    pub rebroadcast_interval: Duration,
+    /// Times the transaction is signed with a fresh blockhash before giving up
+    pub max_attempts: usize,
+}
+
+impl Default for SendConfig {
+    fn default() -> Self {
+        Self {
+            commitment: CommitmentConfig::confirmed(),
+            rpc_send_config: RpcSendTransactionConfig::default(),
+            rebroadcast_interval: Duration::from_secs(2),
+            max_attempts: 5,
+        }
+    }
+}
+
+/// Progress of `send_and_confirm`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SendEvent {
+    /// The transaction was signed with a fresh blockhash, valid until about `last_valid_block_height`. Emitted
+    /// before it is sent.
+    Signed {
+        attempt: usize,
+        signature: Signature,
+        last_valid_block_height: u64,
+    },
+    Sent {
+        signature: Signature,
+    },
+    /// Sending failed without a transaction error. It may still have reached the cluster.
+    NetworkError {
+        signature: Signature,
+        error: String,
+    },
+    /// Broadcast again as no status was found for it
+    Rebroadcast {
+        signature: Signature,
+    },
+    /// The blockhash expired without any attempt landing
+    Expired {
+        signature: Signature,
+    },
+    Confirmed {
+        signature: Signature,
+    },
+    /// Failed in preflight or on chain
+    Failed {
+        signature: Signature,
+        error: TransactionError,
+        retryable: bool,
+    },
+}
+
+impl Display for SendEvent {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SendEvent::Signed {
+                attempt,
+                signature,
+                last_valid_block_height,
+            } => write!(
+                f,
+                "Attempt {attempt}: signed {signature}, valid until block height {last_valid_block_height}"
+            ),
+            SendEvent::Sent { signature } => write!(f, "Sent {signature}"),
+            SendEvent::NetworkError { signature, error } => {
+                write!(f, "Failed to send {signature}: {error}")
+            }
+            SendEvent::Rebroadcast { signature } => write!(f, "Rebroadcast {signature}"),
+            SendEvent::Expired { signature } => {
+                write!(f, "Blockhash of {signature} expired before it landed")
+            }
+            SendEvent::Confirmed { signature } => write!(f, "Confirmed {signature}"),
+            SendEvent::Failed {
+                signature,
+                error,
+                retryable,
+            } => write!(
+                f,
+                "{signature} failed: {error} ({})",
+                if *retryable { "retryable" } else { "fatal" }
+            ),
+        }
+    }
+}
+
+/// lb_clmm errors which may clear up on a new attempt, as the price moved back
+const RETRYABLE_LB_ERRORS: [LBError; 3] = [
+    LBError::ExceededAmountSlippageTolerance,
+    LBError::ExceededBinSlippageTolerance,
+    LBError::InsufficientOutAmount,
+];
+
+/// Code of the `LBError` failing the instruction of `message`, if it was raised by lb_clmm.
+pub fn get_lb_error_code(error: &TransactionError, message: &VersionedMessage) -> Option<u32> {
+    let TransactionError::InstructionError(index, InstructionError::Custom(code)) = error else {
+        return None;
+    };
+    let instruction = message.instructions().get(usize::from(*index))?;
+    // Programs are never loaded from lookup tables, so the program id is a static key
+    if message
+        .static_account_keys()
+        .get(usize::from(instruction.program_id_index))
+        != Some(&lb_clmm::ID)
+    {
+        return None;
+    }
+    Some(*code)
+}
+
+/// Whether signing `message` again with a fresh blockhash may succeed after `error`. Congestion, expired
+/// blockhashes and slippage are retryable. Other errors, such as the lb_clmm validation errors, are fatal.
+pub fn is_retryable_error(error: &TransactionError, message: &VersionedMessage) -> bool {
+    match error {
+        TransactionError::BlockhashNotFound
+        | TransactionError::AccountInUse
+        | TransactionError::WouldExceedMaxBlockCostLimit
+        | TransactionError::WouldExceedMaxAccountCostLimit
+        | TransactionError::WouldExceedAccountDataBlockLimit
+        | TransactionError::ClusterMaintenance => true,
+        _ => get_lb_error_code(error, message).map_or(false, |code| {
+            RETRYABLE_LB_ERRORS
+                .iter()
+                .any(|lb_error| u32::from(*lb_error) == code)
+        }),
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SendStatus {
+    Confirmed(Signature),
+    Failed(Signature, TransactionError),
+    /// Seen by the cluster, not yet at the commitment
+    Processing,
+    /// No status, and the blockhash is still valid
+    Pending,
+    /// No status, and the blockhash expired. None of the signatures can land anymore.
+    Expired,
+}
+
+/// Status of the attempts of a transaction from their signature statuses. `blockhash_valid` must be checked
+/// before the statuses are fetched, so an attempt can't land in between.
+fn get_send_status(
+    signatures: &[Signature],
+    statuses: &[Option<TransactionStatus>],
+    blockhash_valid: bool,
+    commitment: CommitmentConfig,
+) -> SendStatus {
+    let mut seen = false;
+    for (signature, status) in signatures.iter().zip(statuses) {
+        let Some(status) = status else {
+            continue;
+        };
+        seen = true;
+        if !status.satisfies_commitment(commitment) {
+            continue;
+        }
+        return match &status.err {
+            None => SendStatus::Confirmed(*signature),
+            Some(error) => SendStatus::Failed(*signature, error.clone()),
+        };
+    }
+
+    match (seen, blockhash_valid) {
+        (true, _) => SendStatus::Processing,
+        (false, true) => SendStatus::Pending,
+        (false, false) => SendStatus::Expired,
+    }
+}
+
+/// What to do after an attempt failed. Errors when the failure is fatal or no attempt is left.
+fn on_failure(
+    signature: Signature,
+    error: TransactionError,
+    message: &VersionedMessage,
+    attempt: usize,
+    max_attempts: usize,
+    on_event: &mut impl FnMut(&SendEvent) -> Result<()>,
+) -> Result<()> {
+    let retryable = is_retryable_error(&error, message);
+    on_event(&SendEvent::Failed {
+        signature,
+        error: error.clone(),
+        retryable,
+    })?;
+    if !retryable || attempt == max_attempts {
+        let lb_error = get_lb_error_code(&error, message)
+            .map(|code| format!(" (lb_clmm error {code})"))
+            .unwrap_or_default();
+        return Err(anyhow!("Transaction {signature} failed: {error}{lb_error}"));
+    }
+    Ok(())
+}
+
+fn get_rebroadcast_config(config: &SendConfig) -> RpcSendTransactionConfig {
+    RpcSendTransactionConfig {
+        skip_preflight: true,
+        ..config.rpc_send_config
+    }
+}
+
+/// Send a transaction and wait for its confirmation. `sign` signs it with a fresh blockhash, and is called again
+/// when the blockhash expires before the transaction lands. Every signature sent is checked before signing again,
+/// so the transaction never lands twice. It's broadcast again while it has no status, and retried on the failures
+/// `is_retryable_error` accepts, up to `config.max_attempts` times. `on_event` receives the progress.
+/// Legacy transactions are signed into a `VersionedTransaction` with `into`.
+pub async fn send_and_confirm<F, Fut>(
+    rpc_client: &RpcClient,
+    sign: F,
+    config: &SendConfig,
+    on_event: impl FnMut(&SendEvent) -> Result<()>,
+) -> Result<Signature>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<VersionedTransaction>>,
+{
+    send_and_confirm_with(Rpc::Nonblocking(rpc_client), sign, config, on_event).await
+}
+
+/// Same as `send_and_confirm` with a blocking RPC client.
+pub fn send_and_confirm_blocking(
+    rpc_client: &BlockingRpcClient,
+    mut sign: impl FnMut() -> Result<VersionedTransaction>,
+    config: &SendConfig,
+    on_event: impl FnMut(&SendEvent) -> Result<()>,
+) -> Result<Signature> {
+    block_on(send_and_confirm_with(
+        Rpc::Blocking(rpc_client),
+        || std::future::ready(sign()),
+        config,
+        on_event,
+    ))
+}
+
+async fn send_and_confirm_with<F, Fut>(
+    rpc: Rpc<'_>,
+    mut sign: F,
+    config: &SendConfig,
+    mut on_event: impl FnMut(&SendEvent) -> Result<()>,
+) -> Result<Signature>
+where
+    F: FnMut() -> Fut,
+    Fut: Future<Output = Result<VersionedTransaction>>,
+{
+    let mut signatures = vec![];
+    for attempt in 1..=config.max_attempts {
+        // Fetched before signing, so the blockhash of the transaction expires at this height or a little later
+        let (_blockhash, last_valid_block_height) = rpc
+            .get_latest_blockhash_with_commitment(config.commitment)
+            .await?;
+        let transaction = sign().await?;
+        let signature = transaction.signatures[0];
+        on_event(&SendEvent::Signed {
+            attempt,
+            signature,
+            last_valid_block_height,
+        })?;
+
+        match rpc
+            .send_transaction_with_config(&transaction, config.rpc_send_config)
+            .await
+        {
+            Ok(_) => on_event(&SendEvent::Sent { signature })?,
+            Err(err) => match err.get_transaction_error() {
+                // Rejected by the preflight simulation, so never broadcast
+                Some(error) => {
+                    on_failure(
+                        signature,
+                        error,
+                        &transaction.message,
+                        attempt,
+                        config.max_attempts,
+                        &mut on_event,
+                    )?;
+                    rpc.sleep(config.rebroadcast_interval).await;
+                    continue;
+                }
+                // It may have reached the cluster. The status polling below finds out.
+                None => on_event(&SendEvent::NetworkError {
+                    signature,
+                    error: err.to_string(),
+                })?,
+            },
+        }
+        signatures.push(signature);
+
+        loop {
+            rpc.sleep(config.rebroadcast_interval).await;
+            let blockhash_valid = rpc
+                .is_blockhash_valid(transaction.message.recent_blockhash(), config.commitment)
+                .await?;
+            let statuses = rpc.get_signature_statuses(&signatures).await?;
+
+            match get_send_status(&signatures, &statuses, blockhash_valid, config.commitment) {
+                SendStatus::Confirmed(signature) => {
+                    on_event(&SendEvent::Confirmed { signature })?;
+                    return Ok(signature);
+                }
+                SendStatus::Failed(signature, error) => {
+                    on_failure(
+                        signature,
+                        error,
+                        &transaction.message,
+                        attempt,
+                        config.max_attempts,
+                        &mut on_event,
+                    )?;
+                    // Failed transactions are final, and must not be taken for the next attempt
+                    signatures.retain(|sent| *sent != signature);
+                    break;
+                }
+                SendStatus::Processing => {}
+                SendStatus::Pending => {
+                    if rpc
+                        .send_transaction_with_config(&transaction, get_rebroadcast_config(config))
+                        .await
+                        .is_ok()
+                    {
+                        on_event(&SendEvent::Rebroadcast { signature })?;
+                    }
+                }
+                SendStatus::Expired => {
+                    on_event(&SendEvent::Expired { signature })?;
+                    break;
+                }
+            }
+        }
+    }
+
+    Err(anyhow!(
+        "Transaction not confirmed after {} attempts",
+        config.max_attempts
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_client::solana_sdk::instruction::Instruction;
+    use anchor_client::solana_sdk::message::Message;
+    use anchor_lang::prelude::Pubkey;
+    use solana_transaction_status::TransactionConfirmationStatus;
+
+    fn status(
+        confirmation_status: TransactionConfirmationStatus,
+        err: Option<TransactionError>,
+    ) -> Option<TransactionStatus> {
+        Some(TransactionStatus {
+            slot: 1,
+            confirmations: None,
+            status: err.clone().map_or(Ok(()), Err),
+            err,
+            confirmation_status: Some(confirmation_status),
+        })
+    }
+
+    #[test]
+    fn test_get_send_status() {
+        let commitment = CommitmentConfig::confirmed();
+        let signatures = [Signature::new_unique(), Signature::new_unique()];
+
+        assert_eq!(
+            get_send_status(&signatures, &[None, None], true, commitment),
+            SendStatus::Pending
+        );
+        assert_eq!(
+            get_send_status(&signatures, &[None, None], false, commitment),
+            SendStatus::Expired
+        );
+        // A processed attempt may still be confirmed, even once the blockhash expired
+        assert_eq!(
+            get_send_status(
+                &signatures,
+                &[status(TransactionConfirmationStatus::Processed, None), None],
+                false,
+                commitment
+            ),
+            SendStatus::Processing
+        );
+        // An earlier attempt which landed is found
+        assert_eq!(
+            get_send_status(
+                &signatures,
+                &[status(TransactionConfirmationStatus::Confirmed, None), None],
+                true,
+                commitment
+            ),
+            SendStatus::Confirmed(signatures[0])
+        );
+        assert_eq!(
+            get_send_status(
+                &signatures,
+                &[
+                    None,
+                    status(
+                        TransactionConfirmationStatus::Finalized,
+                        Some(TransactionError::AccountInUse)
+                    )
+                ],
+                true,
+                commitment
+            ),
+            SendStatus::Failed(signatures[1], TransactionError::AccountInUse)
+        );
+    }
+
+    #[test]
+    fn test_is_retryable_error() {
+        let payer = Pubkey::new_unique();
+        let message = VersionedMessage::Legacy(Message::new(
+            &[
+                Instruction::new_with_bytes(Pubkey::new_unique(), &[], vec![]),
+                Instruction::new_with_bytes(lb_clmm::ID, &[], vec![]),
+            ],
+            Some(&payer),
+        ));
+        let slippage = u32::from(LBError::ExceededBinSlippageTolerance);
+        let invalid_admin = u32::from(LBError::InvalidAdmin);
+
+        assert!(is_retryable_error(
+            &TransactionError::BlockhashNotFound,
+            &message
+        ));
+        assert!(is_retryable_error(
+            &TransactionError::InstructionError(1, InstructionError::Custom(slippage)),
+            &message
+        ));
+        assert!(!is_retryable_error(
+            &TransactionError::InstructionError(1, InstructionError::Custom(invalid_admin)),
+            &message
+        ));
+        // Same code raised by another program
+        assert!(!is_retryable_error(
+            &TransactionError::InstructionError(0, InstructionError::Custom(slippage)),
+            &message
+        ));
+        assert_eq!(
+            get_lb_error_code(
+                &TransactionError::InstructionError(1, InstructionError::Custom(invalid_admin)),
+                &message
+            ),
+            Some(invalid_admin)
+        );
+    }
+}
@@ -0,0 +1,400 @@
+use anchor_lang::AccountDeserialize;
+use anyhow::Result;
+use commons::bindings::{decode_bin_arrays, get_id_from_ui_price, get_ui_price_from_id, quote};
+use commons::position::{get_position_amounts, get_position_pending_fees};
+use lb_clmm::state::bin::{Bin, BinArray};
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::state::position::PositionV2;
+use pyo3::exceptions::PyValueError;
+use pyo3::prelude::*;
+
+#[pyclass(name = "SwapQuote")]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PySwapQuote {
+    /// Total amount in, including fee
+    #[pyo3(get)]
+    pub amount_in: u64,
+    #[pyo3(get)]
+    pub amount_out: u64,
+    #[pyo3(get)]
+    pub fee: u64,
+}
+
+#[pyclass(name = "LbPair")]
+#[derive(Debug, Clone)]
+pub struct PyLbPair {
+    #[pyo3(get)]
+    pub active_id: i32,
+    #[pyo3(get)]
+    pub bin_step: u16,
+    #[pyo3(get)]
+    pub status: u8,
+    #[pyo3(get)]
+    pub pair_type: u8,
+    #[pyo3(get)]
+    pub activation_type: u8,
+    #[pyo3(get)]
+    pub activation_point: u64,
+    #[pyo3(get)]
+    pub token_x_mint: String,
+    #[pyo3(get)]
+    pub token_y_mint: String,
+    #[pyo3(get)]
+    pub reserve_x: String,
+    #[pyo3(get)]
+    pub reserve_y: String,
+    #[pyo3(get)]
+    pub oracle: String,
+    #[pyo3(get)]
+    pub base_factor: u16,
+    #[pyo3(get)]
+    pub variable_fee_control: u32,
+    #[pyo3(get)]
+    pub max_volatility_accumulator: u32,
+    #[pyo3(get)]
+    pub protocol_share: u16,
+    #[pyo3(get)]
+    pub volatility_accumulator: u32,
+    #[pyo3(get)]
+    pub last_update_timestamp: i64,
+    #[pyo3(get)]
+    pub reward_mints: Vec<String>,
+}
+
+#[pyclass(name = "Bin")]
+#[derive(Debug, Clone)]
+pub struct PyBin {
+    #[pyo3(get)]
+    pub bin_id: i32,
+    #[pyo3(get)]
+    pub amount_x: u64,
+    #[pyo3(get)]
+    pub amount_y: u64,
+    /// Q64x64 price
+    #[pyo3(get)]
+    pub price: u128,
+    #[pyo3(get)]
+    pub liquidity_supply: u128,
+    #[pyo3(get)]
+    pub fee_amount_x_per_token_stored: u128,
+    #[pyo3(get)]
+    pub fee_amount_y_per_token_stored: u128,
+}
+
+#[pyclass(name = "BinArray")]
+#[derive(Debug, Clone)]
+pub struct PyBinArray {
+    #[pyo3(get)]
+    pub index: i64,
+    #[pyo3(get)]
+    pub lb_pair: String,
+    #[pyo3(get)]
+    pub bins: Vec<PyBin>,
+}
+
+#[pyclass(name = "Position")]
+#[derive(Debug, Clone)]
+pub struct PyPosition {
+    #[pyo3(get)]
+    pub lb_pair: String,
+    #[pyo3(get)]
+    pub owner: String,
+    #[pyo3(get)]
+    pub fee_owner: String,
+    #[pyo3(get)]
+    pub operator: String,
+    #[pyo3(get)]
+    pub lower_bin_id: i32,
+    #[pyo3(get)]
+    pub upper_bin_id: i32,
+    /// Liquidity share of each bin from `lower_bin_id` to `upper_bin_id`
+    #[pyo3(get)]
+    pub liquidity_shares: Vec<u128>,
+    #[pyo3(get)]
+    pub last_updated_at: i64,
+    #[pyo3(get)]
+    pub total_claimed_fee_x_amount: u64,
+    #[pyo3(get)]
+    pub total_claimed_fee_y_amount: u64,
+    #[pyo3(get)]
+    pub lock_release_point: u64,
+}
+
+impl From<commons::bindings::SwapQuote> for PySwapQuote {
+    fn from(quote: commons::bindings::SwapQuote) -> Self {
+        Self {
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+        }
+    }
+}
+
+impl From<&LbPair> for PyLbPair {
+    fn from(lb_pair: &LbPair) -> Self {
+        Self {
+            active_id: lb_pair.active_id,
+            bin_step: lb_pair.bin_step,
+            status: lb_pair.status,
+            pair_type: lb_pair.pair_type,
+            activation_type: lb_pair.activation_type,
+            activation_point: lb_pair.activation_point,
+            token_x_mint: lb_pair.token_x_mint.to_string(),
+            token_y_mint: lb_pair.token_y_mint.to_string(),
+            reserve_x: lb_pair.reserve_x.to_string(),
+            reserve_y: lb_pair.reserve_y.to_string(),
+            oracle: lb_pair.oracle.to_string(),
+            base_factor: lb_pair.parameters.base_factor,
+            variable_fee_control: lb_pair.parameters.variable_fee_control,
+            max_volatility_accumulator: lb_pair.parameters.max_volatility_accumulator,
+            protocol_share: lb_pair.parameters.protocol_share,
+            volatility_accumulator: lb_pair.v_parameters.volatility_accumulator,
+            last_update_timestamp: lb_pair.v_parameters.last_update_timestamp,
+            reward_mints: lb_pair
+                .reward_infos
+                .iter()
+                .map(|reward_info| reward_info.mint.to_string())
+                .collect(),
+        }
+    }
+}
+
+impl PyBin {
+    fn new(bin_id: i32, bin: &Bin) -> Self {
+        Self {
+            bin_id,
+            amount_x: bin.amount_x,
+            amount_y: bin.amount_y,
+            price: bin.price,
+            liquidity_supply: bin.liquidity_supply,
+            fee_amount_x_per_token_stored: bin.fee_amount_x_per_token_stored,
+            fee_amount_y_per_token_stored: bin.fee_amount_y_per_token_stored,
+        }
+    }
+}
+
+impl TryFrom<&BinArray> for PyBinArray {
+    type Error = anyhow::Error;
+
+    fn try_from(bin_array: &BinArray) -> Result<Self> {
+        let (lower_bin_id, _) = BinArray::get_bin_array_lower_upper_bin_id(bin_array.index as i32)?;
+
+        Ok(Self {
+            index: bin_array.index,
+            lb_pair: bin_array.lb_pair.to_string(),
+            bins: bin_array
+                .bins
+                .iter()
+                .zip(lower_bin_id..)
+                .map(|(bin, bin_id)| PyBin::new(bin_id, bin))
+                .collect(),
+        })
+    }
+}
+
+impl TryFrom<&PositionV2> for PyPosition {
+    type Error = anyhow::Error;
+
+    fn try_from(position: &PositionV2) -> Result<Self> {
+        let width = position.width()? as usize;
+
+        Ok(Self {
+            lb_pair: position.lb_pair.to_string(),
+            owner: position.owner.to_string(),
+            fee_owner: position.fee_owner.to_string(),
+            operator: position.operator.to_string(),
+            lower_bin_id: position.lower_bin_id,
+            upper_bin_id: position.upper_bin_id,
+            liquidity_shares: position.liquidity_shares[..width].to_vec(),
+            last_updated_at: position.last_updated_at,
+            total_claimed_fee_x_amount: position.total_claimed_fee_x_amount,
+            total_claimed_fee_y_amount: position.total_claimed_fee_y_amount,
+            lock_release_point: position.lock_release_point,
+        })
+    }
+}
+
+fn to_py_error(err: anyhow::Error) -> PyErr {
+    PyValueError::new_err(format!("{:#}", err))
+}
+
+fn decode_position_and_bin_arrays(
+    position_data: &[u8],
+    bin_arrays_data: &[&[u8]],
+) -> Result<(PositionV2, Vec<BinArray>)> {
+    let position = PositionV2::try_deserialize(&mut &position_data[..])?;
+    let bin_arrays = decode_bin_arrays(bin_arrays_data)?;
+
+    Ok((position, bin_arrays))
+}
+
+/// Decode a LbPair account data.
+#[pyfunction(name = "decode_lb_pair")]
+fn decode_lb_pair_py(data: &[u8]) -> PyResult<PyLbPair> {
+    let lb_pair = LbPair::try_deserialize(&mut &data[..]).map_err(|e| to_py_error(e.into()))?;
+    Ok(PyLbPair::from(&lb_pair))
+}
+
+/// Decode a BinArray account data.
+#[pyfunction(name = "decode_bin_array")]
+fn decode_bin_array_py(data: &[u8]) -> PyResult<PyBinArray> {
+    let bin_array = BinArray::try_deserialize(&mut &data[..]).map_err(|e| to_py_error(e.into()))?;
+    PyBinArray::try_from(&bin_array).map_err(to_py_error)
+}
+
+/// Decode a PositionV2 account data.
+#[pyfunction(name = "decode_position")]
+fn decode_position_py(data: &[u8]) -> PyResult<PyPosition> {
+    let position =
+        PositionV2::try_deserialize(&mut &data[..]).map_err(|e| to_py_error(e.into()))?;
+    PyPosition::try_from(&position).map_err(to_py_error)
+}
+
+/// Quote a swap of exact `amount_in`. `bin_arrays` are the raw bin array accounts around the active bin.
+#[allow(clippy::too_many_arguments)]
+#[pyfunction(name = "quote_exact_in")]
+#[pyo3(signature = (lb_pair_pubkey, lb_pair, bin_arrays, amount_in, swap_for_y, current_timestamp, current_slot, bitmap_extension=None))]
+fn quote_exact_in_py(
+    lb_pair_pubkey: &str,
+    lb_pair: &[u8],
+    bin_arrays: Vec<&[u8]>,
+    amount_in: u64,
+    swap_for_y: bool,
+    current_timestamp: u64,
+    current_slot: u64,
+    bitmap_extension: Option<&[u8]>,
+) -> PyResult<PySwapQuote> {
+    quote(
+        lb_pair_pubkey,
+        lb_pair,
+        bitmap_extension,
+        &bin_arrays,
+        amount_in,
+        swap_for_y,
+        true,
+        current_timestamp,
+        current_slot,
+    )
+    .map(PySwapQuote::from)
+    .map_err(to_py_error)
+}
+
+/// Quote a swap of exact `amount_out`. `bin_arrays` are the raw bin array accounts around the active bin.
+#[allow(clippy::too_many_arguments)]
+#[pyfunction(name = "quote_exact_out")]
+#[pyo3(signature = (lb_pair_pubkey, lb_pair, bin_arrays, amount_out, swap_for_y, current_timestamp, current_slot, bitmap_extension=None))]
+fn quote_exact_out_py(
+    lb_pair_pubkey: &str,
+    lb_pair: &[u8],
+    bin_arrays: Vec<&[u8]>,
+    amount_out: u64,
+    swap_for_y: bool,
+    current_timestamp: u64,
+    current_slot: u64,
+    bitmap_extension: Option<&[u8]>,
+) -> PyResult<PySwapQuote> {
+    quote(
+        lb_pair_pubkey,
+        lb_pair,
+        bitmap_extension,
+        &bin_arrays,
+        amount_out,
+        swap_for_y,
+        false,
+        current_timestamp,
+        current_slot,
+    )
+    .map(PySwapQuote::from)
+    .map_err(to_py_error)
+}
+
+/// UI price of token X in token Y of the bin.
+#[pyfunction(name = "get_price_from_id")]
+fn get_price_from_id_py(
+    bin_id: i32,
+    bin_step: u16,
+    decimals_x: u8,
+    decimals_y: u8,
+) -> PyResult<f64> {
+    get_ui_price_from_id(bin_id, bin_step, decimals_x, decimals_y).map_err(to_py_error)
+}
+
+/// Bin id of the UI price of token X in token Y. Price in between 2 bins are rounded by `round_down`.
+#[pyfunction(name = "get_id_from_price")]
+#[pyo3(signature = (price, bin_step, decimals_x, decimals_y, round_down=true))]
+fn get_id_from_price_py(
+    price: f64,
+    bin_step: u16,
+    decimals_x: u8,
+    decimals_y: u8,
+    round_down: bool,
+) -> PyResult<i32> {
+    get_id_from_ui_price(price, bin_step, decimals_x, decimals_y, round_down).map_err(to_py_error)
+}
+
+/// Token X and token Y amounts of the position if fully withdrawn. `bin_arrays` must cover the position range.
+#[pyfunction(name = "get_position_amounts")]
+fn get_position_amounts_py(position: &[u8], bin_arrays: Vec<&[u8]>) -> PyResult<(u64, u64)> {
+    decode_position_and_bin_arrays(position, &bin_arrays)
+        .and_then(|(position, bin_arrays)| get_position_amounts(&position, &bin_arrays))
+        .map_err(to_py_error)
+}
+
+/// Claimable fees of the position. `bin_arrays` must cover the position range.
+#[pyfunction(name = "get_position_pending_fees")]
+fn get_position_pending_fees_py(position: &[u8], bin_arrays: Vec<&[u8]>) -> PyResult<(u64, u64)> {
+    decode_position_and_bin_arrays(position, &bin_arrays)
+        .and_then(|(position, bin_arrays)| get_position_pending_fees(&position, &bin_arrays))
+        .map_err(to_py_error)
+}
+
+#[pymodule]
+fn dlmm_py(_py: Python, m: &PyModule) -> PyResult<()> {
+    m.add_class::<PySwapQuote>()?;
+    m.add_class::<PyLbPair>()?;
+    m.add_class::<PyBin>()?;
+    m.add_class::<PyBinArray>()?;
+    m.add_class::<PyPosition>()?;
+
+    m.add_function(wrap_pyfunction!(decode_lb_pair_py, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_bin_array_py, m)?)?;
+    m.add_function(wrap_pyfunction!(decode_position_py, m)?)?;
+    m.add_function(wrap_pyfunction!(quote_exact_in_py, m)?)?;
+    m.add_function(wrap_pyfunction!(quote_exact_out_py, m)?)?;
+    m.add_function(wrap_pyfunction!(get_price_from_id_py, m)?)?;
+    m.add_function(wrap_pyfunction!(get_id_from_price_py, m)?)?;
+    m.add_function(wrap_pyfunction!(get_position_amounts_py, m)?)?;
+    m.add_function(wrap_pyfunction!(get_position_pending_fees_py, m)?)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LB_PAIR: &str = "EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig";
+    const LB_PAIR_DATA: &[u8] = include_bytes!(
+        "../../programs/lb_clmm/tests/fixtures/EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig/lb_pair.bin"
+    );
+    const BIN_ARRAY_1_DATA: &[u8] = include_bytes!(
+        "../../programs/lb_clmm/tests/fixtures/EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig/bin_array_1.bin"
+    );
+
+    #[test]
+    fn test_decode_accounts() {
+        let lb_pair = LbPair::try_deserialize(&mut &LB_PAIR_DATA[..]).unwrap();
+        let py_lb_pair = PyLbPair::from(&lb_pair);
+        assert_eq!(py_lb_pair.active_id, lb_pair.active_id);
+        assert_eq!(py_lb_pair.token_x_mint, lb_pair.token_x_mint.to_string());
+        assert_eq!(py_lb_pair.reward_mints.len(), 2);
+
+        let bin_array = BinArray::try_deserialize(&mut &BIN_ARRAY_1_DATA[..]).unwrap();
+        let py_bin_array = PyBinArray::try_from(&bin_array).unwrap();
+        let (lower_bin_id, upper_bin_id) =
+            BinArray::get_bin_array_lower_upper_bin_id(bin_array.index as i32).unwrap();
+        assert_eq!(py_bin_array.lb_pair, LB_PAIR);
+        assert_eq!(py_bin_array.bins.first().unwrap().bin_id, lower_bin_id);
+        assert_eq!(py_bin_array.bins.last().unwrap().bin_id, upper_bin_id);
+    }
+}
@@ -0,0 +1,279 @@
+use anchor_lang::{AccountDeserialize, AnchorDeserialize};
+use anyhow::{ensure, Context, Result};
+use commons::bindings::{decode_bin_arrays, get_id_from_ui_price, get_ui_price_from_id, quote};
+use commons::position::get_position_pending_fees;
+use lb_clmm::instructions::deposit::{
+    validate_add_liquidity_by_strategy_params, LiquidityParameterByStrategy,
+    LiquidityParameterByStrategyOneSide, StrategyParameters, StrategyType,
+};
+use lb_clmm::state::position::PositionV2;
+use wasm_bindgen::prelude::*;
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct SwapQuote {
+    /// Total amount in, including fee
+    pub amount_in: u64,
+    pub amount_out: u64,
+    pub fee: u64,
+}
+
+impl From<commons::bindings::SwapQuote> for SwapQuote {
+    fn from(quote: commons::bindings::SwapQuote) -> Self {
+        Self {
+            amount_in: quote.amount_in,
+            amount_out: quote.amount_out,
+            fee: quote.fee,
+        }
+    }
+}
+
+#[wasm_bindgen(getter_with_clone)]
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BinAmounts {
+    pub bin_ids: Vec<i32>,
+    pub amounts_x: Vec<u64>,
+    pub amounts_y: Vec<u64>,
+}
+
+#[wasm_bindgen]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct PositionFees {
+    pub fee_x: u64,
+    pub fee_y: u64,
+}
+
+fn to_js_error(err: anyhow::Error) -> JsError {
+    JsError::new(&format!("{:#}", err))
+}
+
+fn to_byte_vecs(accounts: &js_sys::Array) -> Vec<Vec<u8>> {
+    accounts
+        .iter()
+        .map(|account| js_sys::Uint8Array::new(&account).to_vec())
+        .collect()
+}
+
+#[allow(clippy::too_many_arguments)]
+fn to_amounts_by_strategy(
+    strategy_type: u8,
+    min_bin_id: i32,
+    max_bin_id: i32,
+    active_id: i32,
+    bin_step: u16,
+    amount_x: u64,
+    amount_y: u64,
+    amount_x_in_active_bin: u64,
+    amount_y_in_active_bin: u64,
+) -> Result<BinAmounts> {
+    let strategy_parameters = StrategyParameters {
+        min_bin_id,
+        max_bin_id,
+        strategy_type: StrategyType::try_from_slice(&[strategy_type])
+            .context("Invalid strategy type")?,
+        parameteres: [0u8; 64],
+    };
+    validate_add_liquidity_by_strategy_params(active_id, active_id, 0, &strategy_parameters)?;
+
+    let amounts = match strategy_parameters.strategy_type {
+        StrategyType::SpotOneSide | StrategyType::CurveOneSide | StrategyType::BidAskOneSide => {
+            ensure!(
+                amount_x == 0 || amount_y == 0,
+                "One side strategy only deposit token X or token Y"
+            );
+            let deposit_for_y = amount_x == 0;
+            let parameters = LiquidityParameterByStrategyOneSide {
+                amount: amount_x.max(amount_y),
+                active_id,
+                max_active_bin_slippage: 0,
+                strategy_parameters,
+            };
+
+            parameters
+                .to_amounts_into_bin(active_id, bin_step, deposit_for_y)?
+                .into_iter()
+                .map(|(bin_id, amount)| {
+                    if deposit_for_y {
+                        (bin_id, 0, amount)
+                    } else {
+                        (bin_id, amount, 0)
+                    }
+                })
+                .collect::<Vec<_>>()
+        }
+        _ => {
+            let parameters = LiquidityParameterByStrategy {
+                amount_x,
+                amount_y,
+                active_id,
+                max_active_bin_slippage: 0,
+                strategy_parameters,
+            };
+            parameters.to_amounts_into_bin(
+                active_id,
+                bin_step,
+                amount_x_in_active_bin,
+                amount_y_in_active_bin,
+            )?
+        }
+    };
+
+    Ok(BinAmounts {
+        bin_ids: amounts.iter().map(|(bin_id, _, _)| *bin_id).collect(),
+        amounts_x: amounts.iter().map(|(_, amount_x, _)| *amount_x).collect(),
+        amounts_y: amounts.iter().map(|(_, _, amount_y)| *amount_y).collect(),
+    })
+}
+
+fn position_fees(position_data: &[u8], bin_arrays_data: &[Vec<u8>]) -> Result<PositionFees> {
+    let position = PositionV2::try_deserialize(&mut &position_data[..])?;
+    let bin_arrays = decode_bin_arrays(bin_arrays_data)?;
+    let (fee_x, fee_y) = get_position_pending_fees(&position, &bin_arrays)?;
+
+    Ok(PositionFees { fee_x, fee_y })
+}
+
+/// Quote a swap of exact `amount_in`. `bin_arrays` are the raw bin array accounts around the active bin.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = quoteExactIn)]
+pub fn quote_exact_in_js(
+    lb_pair_pubkey: &str,
+    lb_pair: &[u8],
+    bitmap_extension: Option<Vec<u8>>,
+    bin_arrays: &js_sys::Array,
+    amount_in: u64,
+    swap_for_y: bool,
+    current_timestamp: u64,
+    current_slot: u64,
+) -> Result<SwapQuote, JsError> {
+    quote(
+        lb_pair_pubkey,
+        lb_pair,
+        bitmap_extension.as_deref(),
+        &to_byte_vecs(bin_arrays),
+        amount_in,
+        swap_for_y,
+        true,
+        current_timestamp,
+        current_slot,
+    )
+    .map(SwapQuote::from)
+    .map_err(to_js_error)
+}
+
+/// Quote a swap of exact `amount_out`. `bin_arrays` are the raw bin array accounts around the active bin.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = quoteExactOut)]
+pub fn quote_exact_out_js(
+    lb_pair_pubkey: &str,
+    lb_pair: &[u8],
+    bitmap_extension: Option<Vec<u8>>,
+    bin_arrays: &js_sys::Array,
+    amount_out: u64,
+    swap_for_y: bool,
+    current_timestamp: u64,
+    current_slot: u64,
+) -> Result<SwapQuote, JsError> {
+    quote(
+        lb_pair_pubkey,
+        lb_pair,
+        bitmap_extension.as_deref(),
+        &to_byte_vecs(bin_arrays),
+        amount_out,
+        swap_for_y,
+        false,
+        current_timestamp,
+        current_slot,
+    )
+    .map(SwapQuote::from)
+    .map_err(to_js_error)
+}
+
+/// UI price of token X in token Y of the bin.
+#[wasm_bindgen(js_name = getPriceFromId)]
+pub fn get_price_from_id_js(
+    bin_id: i32,
+    bin_step: u16,
+    decimals_x: u8,
+    decimals_y: u8,
+) -> Result<f64, JsError> {
+    get_ui_price_from_id(bin_id, bin_step, decimals_x, decimals_y).map_err(to_js_error)
+}
+
+/// Bin id of the UI price of token X in token Y. Price in between 2 bins are rounded by `round_down`.
+#[wasm_bindgen(js_name = getIdFromPrice)]
+pub fn get_id_from_price_js(
+    price: f64,
+    bin_step: u16,
+    decimals_x: u8,
+    decimals_y: u8,
+    round_down: bool,
+) -> Result<i32, JsError> {
+    get_id_from_ui_price(price, bin_step, decimals_x, decimals_y, round_down).map_err(to_js_error)
+}
+
+/// Distribute the amounts into bins the same way as add_liquidity_by_strategy and add_liquidity_by_strategy_one_side.
+/// `strategy_type` follows the program StrategyType order. One side strategies take either `amount_x` or `amount_y`.
+#[allow(clippy::too_many_arguments)]
+#[wasm_bindgen(js_name = toAmountsByStrategy)]
+pub fn to_amounts_by_strategy_js(
+    strategy_type: u8,
+    min_bin_id: i32,
+    max_bin_id: i32,
+    active_id: i32,
+    bin_step: u16,
+    amount_x: u64,
+    amount_y: u64,
+    amount_x_in_active_bin: u64,
+    amount_y_in_active_bin: u64,
+) -> Result<BinAmounts, JsError> {
+    to_amounts_by_strategy(
+        strategy_type,
+        min_bin_id,
+        max_bin_id,
+        active_id,
+        bin_step,
+        amount_x,
+        amount_y,
+        amount_x_in_active_bin,
+        amount_y_in_active_bin,
+    )
+    .map_err(to_js_error)
+}
+
+/// Claimable fees of the position. `bin_arrays` must cover the position range.
+#[wasm_bindgen(js_name = getPositionPendingFees)]
+pub fn get_position_pending_fees_js(
+    position: &[u8],
+    bin_arrays: &js_sys::Array,
+) -> Result<PositionFees, JsError> {
+    position_fees(position, &to_byte_vecs(bin_arrays)).map_err(to_js_error)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_to_amounts_by_strategy() {
+        // SpotBalanced around the active bin
+        let amounts = to_amounts_by_strategy(3, -5, 5, 0, 10, 1_000_000, 1_000_000, 0, 0).unwrap();
+        assert_eq!(amounts.bin_ids, (-5..=5).collect::<Vec<_>>());
+        assert!(amounts.amounts_x.iter().sum::<u64>() <= 1_000_000);
+        assert!(amounts.amounts_y.iter().sum::<u64>() <= 1_000_000);
+        // Only token Y below the active bin, only token X above
+        assert_eq!(amounts.amounts_x[0], 0);
+        assert_eq!(amounts.amounts_y[10], 0);
+
+        // SpotOneSide with token X
+        let amounts = to_amounts_by_strategy(0, 1, 10, 0, 10, 1_000_000, 0, 0, 0).unwrap();
+        assert_eq!(amounts.bin_ids.len(), 10);
+        assert!(amounts.amounts_y.iter().all(|amount| *amount == 0));
+        assert!(amounts.amounts_x.iter().sum::<u64>() <= 1_000_000);
+
+        // One side strategy can't take both tokens
+        assert!(to_amounts_by_strategy(0, 1, 10, 0, 10, 1, 1, 0, 0).is_err());
+        // Unknown strategy
+        assert!(to_amounts_by_strategy(100, 1, 10, 0, 10, 1, 0, 0, 0).is_err());
+    }
+}
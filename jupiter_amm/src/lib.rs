@@ -0,0 +1,257 @@
+use anchor_lang::prelude::{AccountMeta, Clock, Pubkey};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{AccountDeserialize, ToAccountMetas};
+use anyhow::{ensure, Context, Result};
+use commons::quote::{
+    get_bin_array_pubkeys_for_swap, quote_exact_in, quote_exact_out, validate_swap_activation,
+};
+use jupiter_amm_interface::{
+    try_get_account_data, AccountMap, Amm, KeyedAccount, Quote, QuoteParams, Swap,
+    SwapAndAccountMetas, SwapMode, SwapParams,
+};
+use lb_clmm::accounts;
+use lb_clmm::state::bin::BinArray;
+use lb_clmm::state::bin_array_bitmap_extension::BinArrayBitmapExtension;
+use lb_clmm::state::lb_pair::LbPair;
+use lb_clmm::utils::pda::{derive_bin_array_bitmap_extension, derive_event_authority_pda};
+use rust_decimal::Decimal;
+use std::collections::HashMap;
+
+/// Number of bin arrays tracked on each side of the active bin, and passed to the swap instruction.
+pub const BIN_ARRAY_TAKE_COUNT: u8 = 3;
+
+#[derive(Clone)]
+pub struct DlmmAmm {
+    key: Pubkey,
+    lb_pair: LbPair,
+    bitmap_extension_key: Pubkey,
+    bitmap_extension: Option<BinArrayBitmapExtension>,
+    bin_arrays: HashMap<Pubkey, BinArray>,
+    clock: Clock,
+}
+
+impl DlmmAmm {
+    fn swap_for_y(&self, input_mint: Pubkey, output_mint: Pubkey) -> Result<bool> {
+        let swap_for_y = input_mint == self.lb_pair.token_x_mint;
+        let (expected_input_mint, expected_output_mint) = if swap_for_y {
+            (self.lb_pair.token_x_mint, self.lb_pair.token_y_mint)
+        } else {
+            (self.lb_pair.token_y_mint, self.lb_pair.token_x_mint)
+        };
+
+        ensure!(
+            input_mint == expected_input_mint && output_mint == expected_output_mint,
+            "Invalid mint pair {} -> {} for pair {}",
+            input_mint,
+            output_mint,
+            self.key
+        );
+
+        Ok(swap_for_y)
+    }
+
+    fn get_bin_array_pubkeys(&self, swap_for_y: bool) -> Result<Vec<Pubkey>> {
+        get_bin_array_pubkeys_for_swap(
+            self.key,
+            &self.lb_pair,
+            self.bitmap_extension.as_ref(),
+            swap_for_y,
+            BIN_ARRAY_TAKE_COUNT,
+        )
+    }
+}
+
+impl Amm for DlmmAmm {
+    fn from_keyed_account(keyed_account: &KeyedAccount) -> Result<Self> {
+        let lb_pair = LbPair::try_deserialize(&mut keyed_account.account.data.as_ref())?;
+        let (bitmap_extension_key, _bump) = derive_bin_array_bitmap_extension(keyed_account.key);
+
+        Ok(Self {
+            key: keyed_account.key,
+            lb_pair,
+            bitmap_extension_key,
+            bitmap_extension: None,
+            bin_arrays: HashMap::new(),
+            clock: Clock::default(),
+        })
+    }
+
+    fn label(&self) -> String {
+        "Meteora DLMM".to_string()
+    }
+
+    fn program_id(&self) -> Pubkey {
+        lb_clmm::ID
+    }
+
+    fn key(&self) -> Pubkey {
+        self.key
+    }
+
+    fn get_reserve_mints(&self) -> Vec<Pubkey> {
+        vec![self.lb_pair.token_x_mint, self.lb_pair.token_y_mint]
+    }
+
+    fn get_accounts_to_update(&self) -> Vec<Pubkey> {
+        let mut accounts = vec![self.key, self.bitmap_extension_key, sysvar::clock::ID];
+
+        // Bin arrays with liquidity on both sides of the active bin. Empty when the side is out of liquidity.
+        for swap_for_y in [true, false] {
+            accounts.extend(self.get_bin_array_pubkeys(swap_for_y).unwrap_or_default());
+        }
+
+        accounts
+    }
+
+    fn update(&mut self, account_map: &AccountMap) -> Result<()> {
+        let mut lb_pair_data = try_get_account_data(account_map, &self.key)?;
+        self.lb_pair = LbPair::try_deserialize(&mut lb_pair_data)?;
+
+        // The bitmap extension only exists for pairs with liquidity far away from the active bin
+        self.bitmap_extension = match try_get_account_data(account_map, &self.bitmap_extension_key)
+        {
+            Ok(mut data) if !data.is_empty() => {
+                Some(BinArrayBitmapExtension::try_deserialize(&mut data)?)
+            }
+            _ => None,
+        };
+
+        if let Ok(data) = try_get_account_data(account_map, &sysvar::clock::ID) {
+            self.clock = bincode::deserialize(data)?;
+        }
+
+        let mut bin_arrays = HashMap::new();
+        for swap_for_y in [true, false] {
+            for key in self.get_bin_array_pubkeys(swap_for_y)? {
+                if let Some(account) = account_map.get(&key) {
+                    let bin_array = BinArray::try_deserialize(&mut account.data.as_ref())?;
+                    bin_arrays.insert(key, bin_array);
+                }
+            }
+        }
+        self.bin_arrays = bin_arrays;
+
+        Ok(())
+    }
+
+    fn quote(&self, quote_params: &QuoteParams) -> Result<Quote> {
+        let swap_for_y = self.swap_for_y(quote_params.input_mint, quote_params.output_mint)?;
+        let current_timestamp = self.clock.unix_timestamp as u64;
+        let current_slot = self.clock.slot;
+
+        let (in_amount, out_amount, fee_amount) = match quote_params.swap_mode {
+            SwapMode::ExactIn => {
+                let quote = quote_exact_in(
+                    self.key,
+                    &self.lb_pair,
+                    quote_params.amount,
+                    swap_for_y,
+                    self.bin_arrays.clone(),
+                    self.bitmap_extension.as_ref(),
+                    current_timestamp,
+                    current_slot,
+                )?;
+                (quote_params.amount, quote.amount_out, quote.fee)
+            }
+            SwapMode::ExactOut => {
+                let quote = quote_exact_out(
+                    self.key,
+                    &self.lb_pair,
+                    quote_params.amount,
+                    swap_for_y,
+                    self.bin_arrays.clone(),
+                    self.bitmap_extension.as_ref(),
+                    current_timestamp,
+                    current_slot,
+                )?;
+                let in_amount = quote
+                    .amount_in
+                    .checked_add(quote.fee)
+                    .context("MathOverflow")?;
+                (in_amount, quote_params.amount, quote.fee)
+            }
+        };
+
+        let fee_pct = if in_amount == 0 {
+            Decimal::ZERO
+        } else {
+            Decimal::from(fee_amount) / Decimal::from(in_amount)
+        };
+
+        Ok(Quote {
+            in_amount,
+            out_amount,
+            fee_amount,
+            fee_mint: quote_params.input_mint,
+            fee_pct,
+            ..Quote::default()
+        })
+    }
+
+    fn get_swap_and_account_metas(&self, swap_params: &SwapParams) -> Result<SwapAndAccountMetas> {
+        let swap_for_y = self.swap_for_y(swap_params.source_mint, swap_params.destination_mint)?;
+        let (event_authority, _bump) = derive_event_authority_pda();
+
+        let mut account_metas = accounts::Swap {
+            lb_pair: self.key,
+            bin_array_bitmap_extension: self
+                .bitmap_extension
+                .map(|_| self.bitmap_extension_key)
+                .or(Some(lb_clmm::ID)),
+            reserve_x: self.lb_pair.reserve_x,
+            reserve_y: self.lb_pair.reserve_y,
+            user_token_in: swap_params.source_token_account,
+            user_token_out: swap_params.destination_token_account,
+            token_x_mint: self.lb_pair.token_x_mint,
+            token_y_mint: self.lb_pair.token_y_mint,
+            oracle: self.lb_pair.oracle,
+            host_fee_in: Some(lb_clmm::ID),
+            user: swap_params.token_transfer_authority,
+            token_x_program: anchor_spl::token::ID,
+            token_y_program: anchor_spl::token::ID,
+            event_authority,
+            program: lb_clmm::ID,
+        }
+        .to_account_metas(None);
+
+        let bin_array_pubkeys = self.get_bin_array_pubkeys(swap_for_y)?;
+        ensure!(!bin_array_pubkeys.is_empty(), "Pool out of liquidity");
+
+        account_metas.extend(
+            bin_array_pubkeys
+                .into_iter()
+                .map(|key| AccountMeta::new(key, false)),
+        );
+
+        Ok(SwapAndAccountMetas {
+            swap: Swap::MeteoraDlmm,
+            account_metas,
+        })
+    }
+
+    fn clone_amm(&self) -> Box<dyn Amm + Send + Sync> {
+        Box::new(self.clone())
+    }
+
+    fn has_dynamic_accounts(&self) -> bool {
+        true
+    }
+
+    fn supports_exact_out(&self) -> bool {
+        true
+    }
+
+    fn is_active(&self) -> bool {
+        validate_swap_activation(
+            &self.lb_pair,
+            self.clock.unix_timestamp as u64,
+            self.clock.slot,
+        )
+        .is_ok()
+    }
+
+    fn get_accounts_len(&self) -> usize {
+        // Swap accounts, event CPI accounts and bin arrays
+        15 + BIN_ARRAY_TAKE_COUNT as usize
+    }
+}
@@ -0,0 +1,232 @@
+use anchor_lang::prelude::{Clock, Pubkey};
+use anchor_lang::solana_program::sysvar;
+use anchor_lang::{AccountDeserialize, Discriminator};
+use jupiter_amm::DlmmAmm;
+use jupiter_amm_interface::{
+    AccountMap, Amm, KeyedAccount, QuoteParams, Swap, SwapMode, SwapParams,
+};
+use lb_clmm::pair_action_access::ActivationType;
+use lb_clmm::state::lb_pair::{LbPair, PairType};
+use lb_clmm::utils::pda::derive_bin_array_bitmap_extension;
+use solana_sdk::account::Account;
+use std::str::FromStr;
+
+const LB_PAIR: &str = "EtAdVRLFH22rjWh3mcUasKFF27WtHhsaCvK27tPFFWig";
+const BIN_ARRAY_1: &str = "5Sm2ecMeqohRkNpFJPWSqHL1BkA7AEW4ck8TmdF1gD4t";
+const BIN_ARRAY_2: &str = "E6gur9Jw8675DCR7GpJVhoSrkruRgt8EdEVqLAc5RLUt";
+
+fn read_fixture(name: &str) -> Vec<u8> {
+    let path = format!(
+        "{}/../programs/lb_clmm/tests/fixtures/{}/{}",
+        env!("CARGO_MANIFEST_DIR"),
+        LB_PAIR,
+        name
+    );
+    std::fs::read(path).unwrap()
+}
+
+fn program_account(data: Vec<u8>) -> Account {
+    Account {
+        lamports: 1_000_000_000,
+        data,
+        owner: lb_clmm::ID,
+        executable: false,
+        rent_epoch: 0,
+    }
+}
+
+fn serialize_lb_pair(lb_pair: &LbPair) -> Vec<u8> {
+    let mut data = LbPair::discriminator().to_vec();
+    data.extend_from_slice(bytemuck::bytes_of(lb_pair));
+    data
+}
+
+fn load_lb_pair() -> LbPair {
+    LbPair::try_deserialize(&mut read_fixture("lb_pair.bin").as_ref()).unwrap()
+}
+
+fn clock_at(lb_pair: &LbPair) -> Clock {
+    Clock {
+        slot: 280_000_000,
+        unix_timestamp: lb_pair.v_parameters.last_update_timestamp + 1,
+        ..Clock::default()
+    }
+}
+
+fn build_account_map(lb_pair: &LbPair, clock: &Clock) -> AccountMap {
+    let mut account_map = AccountMap::new();
+    account_map.insert(
+        Pubkey::from_str(LB_PAIR).unwrap(),
+        program_account(serialize_lb_pair(lb_pair)),
+    );
+    account_map.insert(
+        Pubkey::from_str(BIN_ARRAY_1).unwrap(),
+        program_account(read_fixture("bin_array_1.bin")),
+    );
+    account_map.insert(
+        Pubkey::from_str(BIN_ARRAY_2).unwrap(),
+        program_account(read_fixture("bin_array_2.bin")),
+    );
+    account_map.insert(
+        sysvar::clock::ID,
+        Account {
+            lamports: 1,
+            data: bincode::serialize(clock).unwrap(),
+            owner: sysvar::ID,
+            executable: false,
+            rent_epoch: 0,
+        },
+    );
+    account_map
+}
+
+fn load_amm(lb_pair: &LbPair) -> DlmmAmm {
+    let key = Pubkey::from_str(LB_PAIR).unwrap();
+    let keyed_account = KeyedAccount {
+        key,
+        account: program_account(serialize_lb_pair(lb_pair)),
+        params: None,
+    };
+    let mut amm = DlmmAmm::from_keyed_account(&keyed_account).unwrap();
+    amm.update(&build_account_map(lb_pair, &clock_at(lb_pair)))
+        .unwrap();
+    amm
+}
+
+#[test]
+fn test_accounts_to_update() {
+    let lb_pair = load_lb_pair();
+    let amm = load_amm(&lb_pair);
+    let key = Pubkey::from_str(LB_PAIR).unwrap();
+
+    assert_eq!(amm.key(), key);
+    assert_eq!(
+        amm.get_reserve_mints(),
+        vec![lb_pair.token_x_mint, lb_pair.token_y_mint]
+    );
+
+    let accounts = amm.get_accounts_to_update();
+    assert_eq!(accounts[0], key);
+    assert_eq!(accounts[1], derive_bin_array_bitmap_extension(key).0);
+    assert_eq!(accounts[2], sysvar::clock::ID);
+    assert!(accounts.contains(&Pubkey::from_str(BIN_ARRAY_1).unwrap()));
+    assert!(accounts.contains(&Pubkey::from_str(BIN_ARRAY_2).unwrap()));
+}
+
+#[test]
+fn test_quote_exact_in_and_exact_out() {
+    let lb_pair = load_lb_pair();
+    let amm = load_amm(&lb_pair);
+
+    let in_amount = 100_000;
+    let exact_in_quote = amm
+        .quote(&QuoteParams {
+            amount: in_amount,
+            input_mint: lb_pair.token_y_mint,
+            output_mint: lb_pair.token_x_mint,
+            swap_mode: SwapMode::ExactIn,
+        })
+        .unwrap();
+
+    assert_eq!(exact_in_quote.in_amount, in_amount);
+    assert!(exact_in_quote.out_amount > 0);
+    assert!(exact_in_quote.fee_amount > 0);
+    assert_eq!(exact_in_quote.fee_mint, lb_pair.token_y_mint);
+
+    let exact_out_quote = amm
+        .quote(&QuoteParams {
+            amount: exact_in_quote.out_amount,
+            input_mint: lb_pair.token_y_mint,
+            output_mint: lb_pair.token_x_mint,
+            swap_mode: SwapMode::ExactOut,
+        })
+        .unwrap();
+
+    assert_eq!(exact_out_quote.out_amount, exact_in_quote.out_amount);
+    // Rounding makes exact out require slightly less or equal input than the original exact in
+    assert!(exact_out_quote.in_amount <= in_amount);
+    assert!(exact_out_quote.in_amount * 1000 >= in_amount * 999);
+}
+
+#[test]
+fn test_quote_invalid_mint() {
+    let lb_pair = load_lb_pair();
+    let amm = load_amm(&lb_pair);
+
+    let result = amm.quote(&QuoteParams {
+        amount: 100_000,
+        input_mint: lb_pair.token_y_mint,
+        output_mint: Pubkey::new_unique(),
+        swap_mode: SwapMode::ExactIn,
+    });
+
+    assert!(result.is_err());
+}
+
+#[test]
+fn test_swap_and_account_metas() {
+    let lb_pair = load_lb_pair();
+    let amm = load_amm(&lb_pair);
+
+    let jupiter_program_id = Pubkey::new_unique();
+    let user = Pubkey::new_unique();
+    let source_token_account = Pubkey::new_unique();
+    let destination_token_account = Pubkey::new_unique();
+
+    let swap_and_account_metas = amm
+        .get_swap_and_account_metas(&SwapParams {
+            in_amount: 100_000,
+            out_amount: 0,
+            source_mint: lb_pair.token_y_mint,
+            destination_mint: lb_pair.token_x_mint,
+            source_token_account,
+            destination_token_account,
+            token_transfer_authority: user,
+            open_order_address: None,
+            quote_mint_to_referrer: None,
+            jupiter_program_id: &jupiter_program_id,
+            missing_dynamic_accounts_as_default: false,
+        })
+        .unwrap();
+
+    assert!(matches!(swap_and_account_metas.swap, Swap::MeteoraDlmm));
+
+    let account_metas = swap_and_account_metas.account_metas;
+    assert_eq!(account_metas[0].pubkey, Pubkey::from_str(LB_PAIR).unwrap());
+    // No bitmap extension, program id as placeholder
+    assert_eq!(account_metas[1].pubkey, lb_clmm::ID);
+    assert_eq!(account_metas[4].pubkey, source_token_account);
+    assert_eq!(account_metas[5].pubkey, destination_token_account);
+    assert!(account_metas[10].is_signer);
+    assert_eq!(account_metas[10].pubkey, user);
+
+    let bin_array_metas = &account_metas[15..];
+    assert!(!bin_array_metas.is_empty());
+    assert!(bin_array_metas.iter().all(|meta| meta.is_writable));
+    assert!(account_metas.len() <= amm.get_accounts_len());
+}
+
+#[test]
+fn test_activation_gating() {
+    let mut lb_pair = load_lb_pair();
+    let clock = clock_at(&lb_pair);
+
+    lb_pair.pair_type = PairType::CustomizablePermissionless.into();
+    lb_pair.activation_type = ActivationType::Timestamp.into();
+    lb_pair.activation_point = clock.unix_timestamp as u64 + 3600;
+
+    let amm = load_amm(&lb_pair);
+    assert!(!amm.is_active());
+
+    let result = amm.quote(&QuoteParams {
+        amount: 100_000,
+        input_mint: lb_pair.token_y_mint,
+        output_mint: lb_pair.token_x_mint,
+        swap_mode: SwapMode::ExactIn,
+    });
+    assert!(result.is_err());
+
+    lb_pair.activation_point = clock.unix_timestamp as u64;
+    let amm = load_amm(&lb_pair);
+    assert!(amm.is_active());
+}
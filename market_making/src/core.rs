@@ -26,6 +26,7 @@ use anchor_spl::token::Mint;
 use anchor_spl::token::TokenAccount;
 use anyhow::Ok;
 use anyhow::*;
+use commons::compute_budget::ComputeBudgetConfig;
 use lb_clmm::accounts;
 use lb_clmm::constants::MAX_BIN_PER_ARRAY;
 use lb_clmm::constants::MAX_BIN_PER_POSITION;
@@ -46,6 +47,8 @@ pub struct Core {
     pub owner: Pubkey,
     pub config: Vec<PairConfig>,
     pub state: Arc<Mutex<AllPosition>>,
+    /// Compute unit limit and priority fee estimation of the sent transactions
+    pub compute_budget: ComputeBudgetConfig,
 }
 
 impl Core {
@@ -177,7 +180,14 @@ impl Core {
         )?;
         let token_mints = self.get_all_token_mints();
         for &token_mint_pk in token_mints.iter() {
-            get_or_create_ata(&program, token_mint_pk, payer.pubkey(), &payer).await?;
+            get_or_create_ata(
+                &program,
+                token_mint_pk,
+                payer.pubkey(),
+                &payer,
+                &self.compute_budget,
+            )
+            .await?;
         }
         Ok(())
     }
@@ -288,7 +298,13 @@ impl Core {
                     simulate_transaction(vec![&payer], payer.pubkey(), &program, &builder)?;
                 println!("{:?}", response);
             } else {
-                let signature = send_tx(vec![&payer], payer.pubkey(), &program, &builder)?;
+                let signature = send_tx(
+                    vec![&payer],
+                    payer.pubkey(),
+                    &program,
+                    &builder,
+                    &self.compute_budget,
+                )?;
                 info!("close popsition {position} {signature}");
             }
         }
@@ -417,7 +433,13 @@ impl Core {
             });
         }
 
-        let signature = send_tx(vec![&payer], payer.pubkey(), &program, &builder)?;
+        let signature = send_tx(
+            vec![&payer],
+            payer.pubkey(),
+            &program,
+            &builder,
+            &self.compute_budget,
+        )?;
         info!("swap {amount_in} {swap_for_y} {signature}");
 
         // TODO should handle if cannot get swap eevent
@@ -575,6 +597,7 @@ impl Core {
                 payer.pubkey(),
                 &program,
                 &builder,
+                &self.compute_budget,
             )?;
             info!("deposit {amount_x} {amount_y} {position} {signature}");
         }
@@ -828,6 +851,7 @@ mod core_test {
             owner: payer.pubkey(),
             config: config.clone(),
             state: Arc::new(Mutex::new(AllPosition::new(&config))),
+            compute_budget: ComputeBudgetConfig::default(),
         };
 
         core.refresh_state().await.unwrap();
@@ -859,6 +883,7 @@ mod core_test {
             owner: payer.pubkey(),
             config: config.clone(),
             state: Arc::new(Mutex::new(AllPosition::new(&config))),
+            compute_budget: ComputeBudgetConfig::default(),
         };
 
         core.refresh_state().await.unwrap();
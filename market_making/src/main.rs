@@ -9,6 +9,7 @@ use anchor_client::solana_sdk::signature::read_keypair_file;
 use anchor_client::solana_sdk::signer::Signer;
 use anchor_client::Cluster;
 use clap::Parser;
+use commons::compute_budget::{ComputeBudgetConfig, PriorityFee, DEFAULT_COMPUTE_UNIT_MARGIN_BPS};
 use core::Core;
 use hyper::Server;
 use pair_config::{get_config_from_file, should_market_making};
@@ -75,6 +76,19 @@ pub struct Args {
     /// config path
     #[clap(long)]
     config_file: String,
+    /// Priority fee in micro lamports per compute unit
+    #[clap(long, default_value_t = 0)]
+    priority_fee: u64,
+    /// Set the priority fee to this percentile (0 - 100) of the recent prioritization fees paid to write the
+    /// accounts of each transaction, instead of --priority-fee
+    #[clap(long, conflicts_with = "priority_fee", value_parser = clap::value_parser!(u8).range(0..=100))]
+    priority_fee_percentile: Option<u8>,
+    /// Max priority fee paid by a transaction, in lamports
+    #[clap(long)]
+    max_priority_fee: Option<u64>,
+    /// Margin added to the simulated compute units of each transaction, in bps
+    #[clap(long, default_value_t = DEFAULT_COMPUTE_UNIT_MARGIN_BPS)]
+    compute_unit_margin_bps: u16,
     // /// public key pair address,
     // #[clap(long)]
     // pair_address: Pubkey,
@@ -97,6 +111,10 @@ async fn main() {
         wallet,
         user_public_key,
         config_file,
+        priority_fee,
+        priority_fee_percentile,
+        max_priority_fee,
+        compute_unit_margin_bps,
     } = Args::parse();
 
     let config = get_config_from_file(&config_file).unwrap();
@@ -117,6 +135,14 @@ async fn main() {
         owner: user_wallet,
         config: config.clone(),
         state: Arc::new(Mutex::new(AllPosition::new(&config))),
+        compute_budget: ComputeBudgetConfig {
+            compute_unit_margin_bps,
+            priority_fee: match priority_fee_percentile {
+                Some(percentile) => PriorityFee::Percentile(percentile),
+                None => PriorityFee::Fixed(priority_fee),
+            },
+            max_priority_fee_lamports: max_priority_fee,
+        },
     };
 
     // init some state
@@ -16,6 +16,8 @@ use anchor_lang::{AnchorDeserialize, AnchorSerialize, Discriminator};
 use anchor_spl::associated_token::get_associated_token_address;
 use anchor_spl::token::spl_token;
 use anyhow::*;
+use commons::compute_budget::{estimate_compute_budget_blocking, ComputeBudgetConfig};
+use commons::sender::{send_and_confirm_blocking, SendConfig};
 use lb_clmm::events::Swap as SwapEvent;
 use solana_transaction_status::option_serializer::OptionSerializer;
 use solana_transaction_status::{UiInstruction, UiTransactionEncoding};
@@ -50,6 +52,7 @@ pub async fn get_or_create_ata<C: Deref<Target = impl Signer> + Clone>(
     token_mint: Pubkey,
     wallet_address: Pubkey,
     payer: &Keypair,
+    compute_budget: &ComputeBudgetConfig,
 ) -> Result<Pubkey> {
     let user_ata = get_associated_token_address(&wallet_address, &token_mint);
 
@@ -68,7 +71,13 @@ pub async fn get_or_create_ata<C: Deref<Target = impl Signer> + Clone>(
                     &spl_token::ID,
                 ));
 
-            let signature = send_tx(vec![payer], payer.pubkey(), program, &builder)?;
+            let signature = send_tx(
+                vec![payer],
+                payer.pubkey(),
+                program,
+                &builder,
+                compute_budget,
+            )?;
             println!("create ata {token_mint} {wallet_address} {signature}");
             Ok(user_ata)
         }
@@ -86,22 +95,46 @@ pub fn get_transaction_config() -> RpcSendTransactionConfig {
     }
 }
 
+/// Send the instructions of `builder` with a compute unit limit and priority fee estimated by `compute_budget`.
+/// The transaction is signed again with a fresh blockhash when it expires before landing.
 pub fn send_tx<C: Clone + std::ops::Deref<Target = impl Signer>>(
     keypairs: Vec<&Keypair>,
     payer: Pubkey,
     program: &Program<C>,
     builder: &RequestBuilder<C>,
+    compute_budget: &ComputeBudgetConfig,
 ) -> Result<Signature> {
     let rpc_client = program.rpc();
-    let latest_blockhash = rpc_client.get_latest_blockhash()?;
-    let tx = Transaction::new_signed_with_payer(
+    let instructions = estimate_compute_budget_blocking(
+        &rpc_client,
         &builder.instructions()?,
-        Some(&payer),
-        &keypairs,
-        latest_blockhash,
-    );
-
-    let signature = rpc_client.send_and_confirm_transaction(&tx)?;
+        &payer,
+        &[],
+        compute_budget,
+    )?;
+    let send_config = SendConfig {
+        rpc_send_config: get_transaction_config(),
+        ..Default::default()
+    };
+
+    let signature = send_and_confirm_blocking(
+        &rpc_client,
+        || {
+            let latest_blockhash = rpc_client.get_latest_blockhash()?;
+            Ok(Transaction::new_signed_with_payer(
+                &instructions,
+                Some(&payer),
+                &keypairs,
+                latest_blockhash,
+            )
+            .into())
+        },
+        &send_config,
+        |event| {
+            info!("{event}");
+            Ok(())
+        },
+    )?;
     Ok(signature)
 }
 